@@ -1,3 +1,5 @@
+use std::path::Path;
+
 fn main() {
     // Pass version information to the build
     println!(
@@ -14,4 +16,103 @@ fn main() {
         let git_hash = String::from_utf8_lossy(&output.stdout);
         println!("cargo:rustc-env=GIT_HASH={}", git_hash.trim());
     }
+
+    // Rebuild when HEAD moves so the hash stays current; setting any
+    // rerun-if-changed replaces cargo's default of watching every file
+    for git_path in [".git/HEAD", ".git/refs"] {
+        if Path::new(git_path).exists() {
+            println!("cargo:rerun-if-changed={}", git_path);
+        }
+    }
+
+    record_crypto_versions();
+}
+
+/// Record the versions of the crypto crates for `crypto_versions()`.
+///
+/// The resolved versions come from the Cargo.lock of the workspace being
+/// built, found above `OUT_DIR`. Without one (e.g. a custom target
+/// directory) the version requirements from our Cargo.toml are used.
+fn record_crypto_versions() {
+    let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    println!("cargo:rerun-if-changed={}", manifest_path.display());
+    let manifest = read_toml(&manifest_path).unwrap_or_default();
+    let dependencies = manifest.get("dependencies").and_then(|d| d.as_table());
+    let dependency = |name: &str| dependencies.and_then(|d| d.get(name));
+
+    let lock_path = std::env::var_os("OUT_DIR").and_then(|out_dir| {
+        Path::new(&out_dir)
+            .ancestors()
+            .map(|dir| dir.join("Cargo.lock"))
+            .find(|path| path.is_file())
+    });
+    if let Some(lock_path) = &lock_path {
+        println!("cargo:rerun-if-changed={}", lock_path.display());
+    }
+    let lock = lock_path.as_deref().and_then(read_toml);
+    let version = |name: &str, requirement: Option<&str>| {
+        lock.as_ref()
+            .and_then(|lock| locked_version(lock, name, requirement))
+            .or_else(|| requirement.map(str::to_string))
+    };
+
+    for (name, var) in [
+        ("rcgen", "FASTCERT_RCGEN_VERSION"),
+        ("ring", "FASTCERT_RING_VERSION"),
+    ] {
+        if let Some(version) = version(name, dependency(name).and_then(requirement)) {
+            println!("cargo:rustc-env={}={}", var, version);
+        }
+    }
+
+    // rcgen signs with aws-lc-rs when its aws_lc_rs feature is enabled,
+    // which takes precedence over ring
+    if let Some(rcgen) = dependency("rcgen") {
+        let aws_lc_rs = rcgen
+            .get("features")
+            .and_then(|f| f.as_array())
+            .is_some_and(|features| features.iter().any(|f| f.as_str() == Some("aws_lc_rs")));
+        let provider = if aws_lc_rs { "aws-lc-rs" } else { "ring" };
+        let provider = match version(provider, None) {
+            Some(version) => format!("{} {}", provider, version),
+            None => provider.to_string(),
+        };
+        println!("cargo:rustc-env=FASTCERT_SIGNATURE_PROVIDER={}", provider);
+    }
+}
+
+/// Read and parse a TOML file, returning `None` if either step fails
+fn read_toml(path: &Path) -> Option<toml::Table> {
+    std::fs::read_to_string(path).ok()?.parse().ok()
+}
+
+/// Get the version requirement of a dependency, declared either as
+/// `"0.14"` or as a table with a `version` key
+fn requirement(dependency: &toml::Value) -> Option<&str> {
+    match dependency {
+        toml::Value::String(version) => Some(version),
+        toml::Value::Table(table) => table.get("version")?.as_str(),
+        _ => None,
+    }
+}
+
+/// Find the locked version of package `name` in a parsed Cargo.lock.
+///
+/// A lock file may hold several versions of a package; with a
+/// `requirement` such as `0.14`, the one it selects is preferred.
+fn locked_version(lock: &toml::Table, name: &str, requirement: Option<&str>) -> Option<String> {
+    let versions: Vec<&str> = lock
+        .get("package")?
+        .as_array()?
+        .iter()
+        .filter(|package| package.get("name").and_then(|n| n.as_str()) == Some(name))
+        .filter_map(|package| package.get("version")?.as_str())
+        .collect();
+    let selected = requirement.and_then(|requirement| {
+        let prefix = format!("{}.", requirement.trim_start_matches(['^', '=']));
+        versions.iter().find(|version| version.starts_with(&prefix))
+    });
+    selected
+        .or(versions.first())
+        .map(|version| version.to_string())
 }
@@ -0,0 +1,99 @@
+//! Audit log of issued certificates
+//!
+//! With `FASTCERT_AUDIT` set, every leaf certificate issued from a CA is
+//! recorded as one JSON line in `issued.jsonl` in its CAROOT. The file is
+//! only ever appended to.
+
+use crate::cert::GenerationResult;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use time::OffsetDateTime;
+
+/// Name of the audit log inside the CAROOT
+pub const AUDIT_FILE: &str = "issued.jsonl";
+
+/// One issued certificate in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the certificate was issued
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    /// Serial number, uppercase hex
+    pub serial: String,
+    /// Hosts the certificate was issued for
+    pub hosts: Vec<String>,
+    /// Expiration date of the certificate
+    #[serde(with = "time::serde::rfc3339")]
+    pub not_after: OffsetDateTime,
+    /// SHA-256 fingerprint of the certificate, colon-separated hex
+    pub fingerprint: String,
+}
+
+/// Check whether auditing is enabled via `FASTCERT_AUDIT`
+pub fn is_enabled() -> bool {
+    crate::env_flag("FASTCERT_AUDIT")
+}
+
+/// Append an issued certificate to the audit log in `caroot`, if auditing
+/// is enabled.
+pub(crate) fn record(caroot: &Path, result: &GenerationResult) -> Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+
+    let entry = AuditEntry {
+        timestamp: OffsetDateTime::now_utc(),
+        serial: result.serial.clone(),
+        hosts: result.hosts.clone(),
+        not_after: result.not_after,
+        fingerprint: result.fingerprint.clone(),
+    };
+    let mut line = serde_json::to_string(&entry)
+        .map_err(|e| Error::Certificate(format!("Failed to serialize audit entry: {}", e)))?;
+    line.push('\n');
+
+    fs::create_dir_all(caroot)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(caroot.join(AUDIT_FILE))?;
+    // A single write keeps concurrent appends from interleaving
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Read the audit log of the current CA.
+///
+/// Returns an empty list if nothing has been recorded yet.
+///
+/// # Errors
+///
+/// Returns an error if the CAROOT cannot be determined, the log cannot be
+/// read, or a line is not a valid entry.
+pub fn read_audit_log() -> Result<Vec<AuditEntry>> {
+    let path = crate::ca::get_ca()?.root_path().join(AUDIT_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                Error::Certificate(format!(
+                    "Invalid audit log entry on line {} of {}: {}",
+                    index + 1,
+                    path.display(),
+                    e
+                ))
+            })
+        })
+        .collect()
+}
@@ -1,10 +1,11 @@
 //! Certificate Authority management
 
+use crate::cert::KeyAlgorithm;
 use crate::{Error, Result};
 use colored::*;
 use rcgen::{
-    BasicConstraints, Certificate, CertificateParams, DistinguishedName, DnType, IsCa, KeyPair,
-    RsaKeySize,
+    BasicConstraints, Certificate, CertificateParams, CertificateRevocationListParams,
+    DistinguishedName, DnType, IsCa, Issuer, KeyIdMethod, KeyPair, RevokedCertParams, SerialNumber,
 };
 use std::fs::{self, File};
 use std::io::Write;
@@ -16,6 +17,15 @@ use std::os::unix::fs::PermissionsExt;
 
 const ROOT_CERT_FILE: &str = "rootCA.pem";
 const ROOT_KEY_FILE: &str = "rootCA-key.pem";
+const INTERMEDIATE_CERT_FILE: &str = "intermediateCA.pem";
+const INTERMEDIATE_KEY_FILE: &str = "intermediateCA-key.pem";
+const SERIAL_FILE: &str = "serial.txt";
+/// Held while a process creates the CA so others wait and load it instead
+const LOCK_FILE: &str = ".fastcert.lock";
+/// A lock older than this is assumed to be left over from a crashed process
+const LOCK_STALE_AFTER: std::time::Duration = std::time::Duration::from_secs(120);
+/// Default lifetime of a new root CA: 10 years
+const DEFAULT_CA_VALIDITY_DAYS: u32 = 3650;
 
 /// Certificate Authority for generating and managing locally-trusted certificates
 ///
@@ -66,47 +76,249 @@ pub fn get_caroot() -> Result<String> {
 /// - Windows: `%LOCALAPPDATA%\fastcert`
 /// - Linux: `~/.local/share/fastcert`
 ///
+/// Inside a Flatpak or Snap sandbox (see [`is_sandboxed`]) the Linux
+/// default is resolved against the host's home directory rather than the
+/// sandbox's private one.
+///
+/// When `FASTCERT_PROFILE` is set, the profile name is appended as a
+/// subdirectory so each profile has its own CA.
+///
 /// # Returns
 ///
 /// The CAROOT directory path as a `PathBuf`.
 ///
 /// # Errors
 ///
-/// Returns an error if the directory cannot be determined.
+/// Returns an error if the directory cannot be determined or the profile
+/// name is invalid.
 fn get_caroot_path() -> Result<PathBuf> {
+    // Reject a bad CA file name override before anything uses the CAROOT
+    root_cert_file()?;
+    root_key_file()?;
+    profile_dir(base_caroot_path()?)
+}
+
+/// Get the CA profile selected by `FASTCERT_PROFILE`.
+///
+/// Profile names may contain ASCII letters, digits, `-`, `_` and `.`, and
+/// must not start with a dot.
+///
+/// # Returns
+///
+/// `None` when no profile is selected (the variable is unset or empty).
+///
+/// # Errors
+///
+/// Returns an error if the profile name is not a plain directory name.
+pub fn active_profile() -> Result<Option<String>> {
+    let profile = match std::env::var("FASTCERT_PROFILE") {
+        Ok(profile) if !profile.is_empty() => profile,
+        _ => return Ok(None),
+    };
+
+    let valid = !profile.starts_with('.')
+        && profile
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(Error::Certificate(format!(
+            "Invalid FASTCERT_PROFILE '{}'. Use letters, digits, '-', '_' or '.'",
+            profile
+        )));
+    }
+
+    Ok(Some(profile))
+}
+
+/// Resolve the CA directory for the active profile under a base CAROOT.
+pub(crate) fn profile_dir(base: PathBuf) -> Result<PathBuf> {
+    Ok(match active_profile()? {
+        Some(profile) => base.join(profile),
+        None => base,
+    })
+}
+
+/// Get the base CAROOT directory, before any profile is applied.
+///
+/// Without `CAROOT`, a CA found at the legacy location is first moved to the
+/// platform default (see [`migrate_caroot`]), so it keeps being used instead
+/// of a new CA being created next to it.
+fn base_caroot_path() -> Result<PathBuf> {
     // Check CAROOT environment variable
     if let Ok(caroot) = std::env::var("CAROOT") {
         return Ok(PathBuf::from(caroot));
     }
 
-    // Get default location based on platform
+    let cert_file = root_cert_file()?;
+    let caroot = default_caroot_path()
+        .ok_or_else(|| Error::Certificate("Could not determine CAROOT directory".to_string()))?;
+    if let Some(legacy) = legacy_caroot_path()
+        && legacy != caroot
+        && legacy.join(&cert_file).exists()
+        && !caroot.join(&cert_file).exists()
+    {
+        migrate_caroot(&legacy, &caroot)?;
+    }
+    Ok(caroot)
+}
+
+/// Get the platform default CAROOT directory.
+fn default_caroot_path() -> Option<PathBuf> {
     #[cfg(target_os = "macos")]
     {
-        if let Some(home) = dirs::home_dir() {
-            return Ok(home
-                .join("Library")
+        dirs::home_dir().map(|home| {
+            home.join("Library")
                 .join("Application Support")
-                .join("fastcert"));
-        }
+                .join("fastcert")
+        })
     }
 
     #[cfg(target_os = "windows")]
     {
-        if let Some(local_app_data) = dirs::data_local_dir() {
-            return Ok(local_app_data.join("fastcert"));
-        }
+        dirs::data_local_dir().map(|local_app_data| local_app_data.join("fastcert"))
     }
 
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     {
-        if let Some(data_dir) = dirs::data_dir() {
-            return Ok(data_dir.join("fastcert"));
+        if let Some(data_dir) = sandbox_host_data_dir() {
+            return Some(data_dir.join("fastcert"));
         }
+        dirs::data_dir().map(|data_dir| data_dir.join("fastcert"))
     }
+}
 
-    Err(Error::Certificate(
-        "Could not determine CAROOT directory".to_string(),
-    ))
+/// Whether fastcert is running inside a Flatpak or Snap sandbox.
+///
+/// Detected from the `FLATPAK_ID` and `SNAP` variables the sandboxes set.
+/// Sandboxed apps get a private home or data directory that host browsers
+/// never look at, so the default CAROOT is moved to the host's data
+/// directory instead. An explicit `CAROOT` is always used as given.
+pub fn is_sandboxed() -> bool {
+    ["FLATPAK_ID", "SNAP"]
+        .iter()
+        .any(|name| std::env::var_os(name).is_some_and(|value| !value.is_empty()))
+}
+
+/// Get the host's data directory when running inside a sandbox.
+///
+/// Snap points `HOME` into the snap and keeps the real one in
+/// `SNAP_REAL_HOME`; Flatpak keeps `HOME` but redirects `XDG_DATA_HOME`,
+/// passing the host value as `HOST_XDG_DATA_HOME`.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn sandbox_host_data_dir() -> Option<PathBuf> {
+    if !is_sandboxed() {
+        return None;
+    }
+
+    let var = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+    let data_dir = var("HOST_XDG_DATA_HOME").map(PathBuf::from).or_else(|| {
+        var("SNAP_REAL_HOME")
+            .or_else(|| var("HOME"))
+            .map(|home| PathBuf::from(home).join(".local").join("share"))
+    })?;
+
+    static NOTED: std::sync::Once = std::sync::Once::new();
+    NOTED.call_once(|| {
+        eprintln!(
+            "Note: Running in a Flatpak or Snap sandbox, so the local CA is kept in {} where \
+             the host can reach it. Grant the app access to that directory, or set CAROOT.",
+            data_dir.join("fastcert").display()
+        );
+    });
+    Some(data_dir)
+}
+
+/// Get the CAROOT default used before sandboxed runs switched to the host's
+/// data directory: the data directory as the process itself sees it.
+///
+/// Inside a Flatpak or Snap this is the sandbox's private directory, where
+/// earlier versions created the CA. Elsewhere it is the current default, so
+/// nothing is migrated.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn legacy_caroot_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|data_dir| data_dir.join("fastcert"))
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn legacy_caroot_path() -> Option<PathBuf> {
+    None
+}
+
+/// Staging directory inside the target CAROOT used by [`migrate_caroot`]
+const MIGRATION_STAGING_DIR: &str = ".fastcert-migrate";
+
+/// Move a CA from one CAROOT directory to another.
+///
+/// Moves the root and intermediate certificates and keys and the serial
+/// counter, keeping their permissions. Does nothing if `from` has no CA or
+/// `to` already has one, so it is safe to call repeatedly; an existing CA
+/// at `to` is never overwritten. `from` is removed if it ends up empty.
+///
+/// The files are first copied into a staging directory inside `to` and
+/// then renamed into place, with the root certificate last, so `to` only
+/// counts as having a CA once every file has arrived. The originals are
+/// deleted only after that, so a failed migration leaves `from` intact and
+/// can simply be retried.
+///
+/// # Errors
+///
+/// Returns an error if `to` cannot be created or a file cannot be copied.
+pub fn migrate_caroot(from: &Path, to: &Path) -> Result<()> {
+    let cert_file = root_cert_file()?;
+    if !from.join(&cert_file).exists() || to.join(&cert_file).exists() {
+        return Ok(());
+    }
+
+    // The root certificate goes last: its presence marks a complete CA
+    let names: Vec<String> = [
+        root_key_file()?,
+        INTERMEDIATE_CERT_FILE.to_string(),
+        INTERMEDIATE_KEY_FILE.to_string(),
+        SERIAL_FILE.to_string(),
+        cert_file,
+    ]
+    .into_iter()
+    .filter(|name| from.join(name).exists())
+    .collect();
+
+    fs::create_dir_all(to)?;
+    let staging = to.join(MIGRATION_STAGING_DIR);
+    if staging.exists() {
+        // Left over from an earlier attempt that failed
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir(&staging)?;
+
+    // fs::copy copies the permissions too
+    let staged = names
+        .iter()
+        .try_for_each(|name| fs::copy(from.join(name), staging.join(name)).map(|_| ()));
+    if let Err(e) = staged {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e.into());
+    }
+    for name in &names {
+        fs::rename(staging.join(name), to.join(name))?;
+    }
+    let _ = fs::remove_dir(&staging);
+
+    for name in &names {
+        if let Err(e) = fs::remove_file(from.join(name)) {
+            eprintln!(
+                "Warning: Failed to remove {} after moving it: {}",
+                from.join(name).display(),
+                e
+            );
+        }
+    }
+    let _ = fs::remove_dir(from);
+
+    eprintln!(
+        "Moved the local CA from {} to {}",
+        from.display(),
+        to.display()
+    );
+    Ok(())
 }
 
 /// Install the CA certificate into the system trust store.
@@ -157,6 +369,21 @@ pub fn uninstall() -> Result<()> {
     ca.uninstall()
 }
 
+/// Create an intermediate CA under the root CA in CAROOT.
+///
+/// Loads (or creates) the root CA and writes `intermediateCA.pem` and
+/// `intermediateCA-key.pem` signed by it. Once present, new leaf certificates
+/// are issued by the intermediate and their PEM files include it as a chain.
+///
+/// # Errors
+///
+/// Returns an error if the root CA cannot be loaded or the intermediate
+/// cannot be generated or saved.
+pub fn create_intermediate() -> Result<()> {
+    let ca = CA::load_or_create()?;
+    ca.create_intermediate()
+}
+
 /// Get the CertificateAuthority instance for the default CAROOT location.
 ///
 /// Creates a new `CertificateAuthority` instance pointing to the default
@@ -174,6 +401,279 @@ pub fn get_ca() -> Result<CertificateAuthority> {
     Ok(CertificateAuthority::new(caroot))
 }
 
+/// Get the SHA-256 fingerprint of the current CA certificate.
+///
+/// Formatted as uppercase, colon-separated hex, the same as
+/// `openssl x509 -noout -fingerprint -sha256`, so it can be compared
+/// out-of-band before trusting a shared root.
+///
+/// # Errors
+///
+/// Returns an error if the CAROOT cannot be determined or the CA
+/// certificate cannot be read or parsed.
+pub fn fingerprint_sha256() -> Result<String> {
+    get_ca()?.fingerprint_sha256()
+}
+
+/// SHA-256 fingerprint of the first certificate in a PEM string.
+pub(crate) fn fingerprint_from_pem(cert_pem: &str) -> Result<String> {
+    let cert_der = crate::cert::parse_pem_cert(cert_pem.as_bytes())?;
+    Ok(sha256_fingerprint(&cert_der))
+}
+
+/// Whether a PEM block tag is one of the private key formats
+fn is_private_key_tag(tag: &str) -> bool {
+    matches!(
+        tag,
+        "PRIVATE KEY" | "ENCRYPTED PRIVATE KEY" | "RSA PRIVATE KEY" | "EC PRIVATE KEY"
+    )
+}
+
+/// Check that a CA certificate file holds a certificate and its key file a
+/// private key.
+///
+/// CAROOTs set up by hand sometimes have the two files swapped, or a
+/// combined certificate and key in the certificate file. Catching that
+/// here names the file at fault instead of failing later during signing.
+fn check_ca_file_contents(
+    cert_path: &Path,
+    cert_pem: &str,
+    key_path: &Path,
+    key_pem: &str,
+) -> Result<()> {
+    let tags = |pem: &str| -> Vec<String> {
+        ::pem::parse_many(pem)
+            .map(|blocks| blocks.iter().map(|b| b.tag().to_string()).collect())
+            .unwrap_or_default()
+    };
+
+    let cert_tags = tags(cert_pem);
+    let cert_has_key = cert_tags.iter().any(|tag| is_private_key_tag(tag));
+    if !cert_tags.iter().any(|tag| tag == "CERTIFICATE") {
+        return Err(Error::Certificate(if cert_has_key {
+            format!(
+                "CA certificate file {} contains a private key instead of a certificate. Are the certificate and key files swapped?",
+                cert_path.display()
+            )
+        } else {
+            format!(
+                "CA certificate file {} does not contain a PEM certificate",
+                cert_path.display()
+            )
+        }));
+    }
+    if cert_has_key {
+        return Err(Error::Certificate(format!(
+            "CA certificate file {} also contains a private key. Keep only the certificate there and move the key to {}",
+            cert_path.display(),
+            key_path.display()
+        )));
+    }
+
+    let key_tags = tags(key_pem);
+    if !key_tags.iter().any(|tag| is_private_key_tag(tag)) {
+        return Err(Error::Certificate(
+            if key_tags.iter().any(|tag| tag == "CERTIFICATE") {
+                format!(
+                    "CA key file {} contains a certificate instead of a private key. Are the certificate and key files swapped?",
+                    key_path.display()
+                )
+            } else {
+                format!(
+                    "CA key file {} does not contain a PEM private key",
+                    key_path.display()
+                )
+            },
+        ));
+    }
+
+    Ok(())
+}
+
+/// Build the trust store nickname for a CA certificate.
+///
+/// The name includes the certificate serial so that several local CAs can
+/// coexist in the same store.
+pub(crate) fn unique_name_from_pem(cert_pem: &str) -> Result<String> {
+    // Parse PEM to get DER
+    let pem_data = pem::parse(cert_pem)
+        .map_err(|e| Error::Certificate(format!("Failed to parse PEM: {}", e)))?;
+
+    // Parse X.509 certificate
+    let cert = x509_parser::parse_x509_certificate(pem_data.contents())
+        .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?
+        .1;
+
+    let serial = cert.serial.to_str_radix(10);
+    Ok(format!("fastcert development CA {}", serial))
+}
+
+/// Check that a new CA can be written to the given directory.
+///
+/// Creates the directory if needed and writes a probe file, so a read-only
+/// CAROOT is reported with its path instead of failing later with a bare
+/// I/O error.
+fn ensure_caroot_writable(root: &Path) -> Result<()> {
+    let not_writable =
+        |e: std::io::Error| Error::CARootNotWritable(root.to_path_buf(), e.to_string());
+
+    fs::create_dir_all(root).map_err(not_writable)?;
+    let probe = root.join(".fastcert-write-test");
+    File::create(&probe).map_err(not_writable)?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Rotate the CA in CAROOT and re-issue the leaves stored next to it.
+///
+/// See [`CertificateAuthority::rotate`]. The new root still has to be
+/// installed in the trust stores (`fastcert --install`).
+///
+/// # Errors
+///
+/// Returns `Error::CARootNotFound` if there is no CA to rotate, or an error
+/// if the new CA cannot be created or saved.
+pub fn rotate() -> Result<RotationReport> {
+    let mut ca = get_ca()?;
+    if !ca.cert_exists() {
+        return Err(Error::CARootNotFound);
+    }
+    ca.load()?;
+    ca.rotate(true)
+}
+
+/// Create the CA in CAROOT without issuing any certificate.
+///
+/// Useful for setup scripts that want the CA to exist (and maybe be
+/// installed) before the first certificate is generated. Later calls to
+/// [`crate::cert::generate_certificate`] reuse it.
+///
+/// # Errors
+///
+/// Returns an error if a CA already exists in CAROOT (use
+/// [`CertificateAuthority::generate`] with `overwrite` to replace it), or if
+/// the CA cannot be created or saved.
+pub fn generate_ca() -> Result<CaCreated> {
+    get_ca()?.generate(false)
+}
+
+/// Details of a newly created CA.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaCreated {
+    /// Path of the CA certificate (`rootCA.pem` by default)
+    pub cert_path: PathBuf,
+    /// Path of the CA private key (`rootCA-key.pem` by default)
+    pub key_path: PathBuf,
+    /// Subject distinguished name of the CA certificate
+    pub subject: String,
+    /// SHA-256 fingerprint of the CA certificate, as colon-separated hex
+    pub fingerprint: String,
+}
+
+/// Result of a CA rotation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RotationReport {
+    /// Where the previous CA files were moved to
+    pub backups: Vec<PathBuf>,
+    /// Leaf certificates re-issued under the new CA
+    pub reissued: Vec<PathBuf>,
+    /// Certificates in CAROOT that were left alone, with the reason
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+/// Options for creating a new CA.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaOptions {
+    /// Algorithm of the CA key (default: RSA-3072)
+    pub key_algorithm: KeyAlgorithm,
+    /// DNS suffixes the CA may issue for, written as a Name Constraints
+    /// extension (default: empty, unconstrained)
+    pub permitted_dns: Vec<String>,
+    /// Lifetime of the root certificate in days (default: 3650)
+    pub validity_days: u32,
+    /// Path length constraint of the root, i.e. how many intermediates may
+    /// follow it (default: none, unlimited)
+    pub ca_path_len: Option<u8>,
+}
+
+impl Default for CaOptions {
+    fn default() -> Self {
+        Self {
+            key_algorithm: KeyAlgorithm::Rsa3072,
+            permitted_dns: Vec::new(),
+            validity_days: DEFAULT_CA_VALIDITY_DAYS,
+            ca_path_len: None,
+        }
+    }
+}
+
+impl CaOptions {
+    /// Build CA options from environment variables.
+    ///
+    /// `FASTCERT_CA_KEY` selects the key algorithm (`rsa3072`, `rsa2048`,
+    /// `ecdsa-p256`, `ecdsa-p384` or `ed25519`).
+    ///
+    /// `FASTCERT_CA_NAME_CONSTRAINTS` is a comma-separated list of permitted
+    /// DNS suffixes such as `dev.local,test`. A leading `*.` or `.` is
+    /// accepted and ignored, since a DNS constraint always covers subdomains.
+    ///
+    /// `FASTCERT_CA_VALIDITY_DAYS` sets the lifetime of the root, for
+    /// short-lived CAs in ephemeral environments.
+    ///
+    /// `FASTCERT_CA_PATH_LEN` sets the root's `pathlen` basic constraint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `FASTCERT_CA_KEY` names an unsupported algorithm,
+    /// a name constraint is not a valid domain, the validity is not a
+    /// positive number of days, or the path length is not a number from 0
+    /// to 255.
+    pub fn from_env() -> Result<Self> {
+        let mut options = Self::default();
+        if let Ok(value) = std::env::var("FASTCERT_CA_KEY") {
+            options.key_algorithm = value.parse()?;
+        }
+        if let Ok(value) = std::env::var("FASTCERT_CA_NAME_CONSTRAINTS") {
+            options.permitted_dns = parse_name_constraints(&value)?;
+        }
+        if let Ok(value) = std::env::var("FASTCERT_CA_VALIDITY_DAYS") {
+            options.validity_days = value.trim().parse().map_err(|_| {
+                Error::Certificate(format!(
+                    "Invalid FASTCERT_CA_VALIDITY_DAYS '{}': expected a number of days",
+                    value
+                ))
+            })?;
+        }
+        if let Ok(value) = std::env::var("FASTCERT_CA_PATH_LEN") {
+            options.ca_path_len = Some(value.trim().parse().map_err(|_| {
+                Error::Certificate(format!(
+                    "Invalid FASTCERT_CA_PATH_LEN '{}': expected a number from 0 to 255",
+                    value
+                ))
+            })?);
+        }
+        Ok(options)
+    }
+}
+
+/// Parse a comma-separated list of permitted DNS suffixes.
+fn parse_name_constraints(value: &str) -> Result<Vec<String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|suffix| !suffix.is_empty())
+        .map(|suffix| {
+            let suffix = suffix
+                .strip_prefix("*.")
+                .or_else(|| suffix.strip_prefix('.'))
+                .unwrap_or(suffix)
+                .to_ascii_lowercase();
+            crate::cert::validate_hostname(&suffix)?;
+            Ok(suffix)
+        })
+        .collect()
+}
+
 /// Certificate Authority management structure.
 ///
 /// Manages the local CA certificate and private key used to sign
@@ -182,6 +682,9 @@ pub fn get_ca() -> Result<CertificateAuthority> {
 pub struct CertificateAuthority {
     /// Path to the directory containing CA files
     root_path: PathBuf,
+    /// File names of the CA certificate and key within `root_path`
+    cert_file: String,
+    key_file: String,
     /// The CA certificate (loaded or generated)
     cert: Option<Certificate>,
     /// PEM-encoded CA certificate
@@ -193,6 +696,11 @@ pub struct CertificateAuthority {
 impl CertificateAuthority {
     /// Create a new CertificateAuthority instance.
     ///
+    /// The CA file names are read from `FASTCERT_CA_CERT_NAME` and
+    /// `FASTCERT_CA_KEY_NAME` here, defaulting to `rootCA.pem` and
+    /// `rootCA-key.pem`. A name that is not a plain file name makes
+    /// `init_ca`, `load` and `create_ca` fail.
+    ///
     /// # Arguments
     ///
     /// * `root_path` - Directory where CA certificate and key will be stored
@@ -203,6 +711,8 @@ impl CertificateAuthority {
     pub fn new(root_path: PathBuf) -> Self {
         Self {
             root_path,
+            cert_file: ca_file_override("FASTCERT_CA_CERT_NAME", ROOT_CERT_FILE),
+            key_file: ca_file_override("FASTCERT_CA_KEY_NAME", ROOT_KEY_FILE),
             cert: None,
             cert_pem: None,
             key_pem: None,
@@ -245,7 +755,8 @@ impl CertificateAuthority {
     /// This method:
     /// 1. Creates the CA directory if needed
     /// 2. Loads the existing CA certificate if present
-    /// 3. Generates and saves a new CA if no certificate exists
+    /// 3. Generates and saves a new CA if no certificate exists, unless
+    ///    `FASTCERT_NO_AUTO_CA` is set
     ///
     /// # Returns
     ///
@@ -254,15 +765,36 @@ impl CertificateAuthority {
     /// # Errors
     ///
     /// Returns an error if:
+    /// - No CA exists and `FASTCERT_NO_AUTO_CA` is set (`Error::CARootNotFound`)
+    /// - No CA exists and the CA directory cannot be created or written to
+    ///   (`Error::CARootNotWritable`)
     /// - Directory creation fails
     /// - CA generation fails
     /// - File operations fail
     pub fn init_ca(&mut self) -> Result<&mut Self> {
+        self.check_file_names()?;
+        if !self.cert_exists() && auto_ca_disabled() {
+            return Err(Error::CARootNotFound);
+        }
+
+        if !self.cert_exists() {
+            ensure_caroot_writable(&self.root_path)?;
+        }
         self.init()?;
 
+        // Another process may be creating the CA right now; wait for it and
+        // then load what it wrote rather than creating a second one
+        let _lock = if !self.cert_exists() || CaRootLock::is_held(&self.root_path) {
+            Some(CaRootLock::acquire(&self.root_path)?)
+        } else {
+            None
+        };
+
         if self.cert_exists() {
+            crate::log_event("ca", &format!("Loading CA from {:?}", self.root_path));
             self.load()?;
         } else {
+            crate::log_event("ca", &format!("Creating a new CA in {:?}", self.root_path));
             self.create_ca()?;
             self.save()?;
             println!("{}", "Created a new local CA".green().bold());
@@ -271,6 +803,12 @@ impl CertificateAuthority {
         Ok(self)
     }
 
+    /// Check that the CA file name overrides are plain file names.
+    fn check_file_names(&self) -> Result<()> {
+        check_ca_file_name("FASTCERT_CA_CERT_NAME", &self.cert_file)?;
+        check_ca_file_name("FASTCERT_CA_KEY_NAME", &self.key_file)
+    }
+
     /// Get the root path of this CA.
     ///
     /// # Returns
@@ -298,18 +836,18 @@ impl CertificateAuthority {
     ///
     /// # Returns
     ///
-    /// Full path to `rootCA.pem`.
+    /// Full path to `rootCA.pem`, or to the name in `FASTCERT_CA_CERT_NAME`.
     pub fn cert_path(&self) -> PathBuf {
-        self.root_path.join(ROOT_CERT_FILE)
+        self.root_path.join(&self.cert_file)
     }
 
     /// Get the path to the CA private key file.
     ///
     /// # Returns
     ///
-    /// Full path to `rootCA-key.pem`.
+    /// Full path to `rootCA-key.pem`, or to the name in `FASTCERT_CA_KEY_NAME`.
     pub fn key_path(&self) -> PathBuf {
-        self.root_path.join(ROOT_KEY_FILE)
+        self.root_path.join(&self.key_file)
     }
 
     /// Check if the CA certificate file exists.
@@ -330,13 +868,36 @@ impl CertificateAuthority {
         self.key_path().exists()
     }
 
+    /// Get the path to the intermediate CA certificate file.
+    ///
+    /// # Returns
+    ///
+    /// Full path to `intermediateCA.pem`.
+    pub fn intermediate_cert_path(&self) -> PathBuf {
+        self.root_path.join(INTERMEDIATE_CERT_FILE)
+    }
+
+    /// Get the path to the intermediate CA private key file.
+    ///
+    /// # Returns
+    ///
+    /// Full path to `intermediateCA-key.pem`.
+    pub fn intermediate_key_path(&self) -> PathBuf {
+        self.root_path.join(INTERMEDIATE_KEY_FILE)
+    }
+
+    /// Check if an intermediate CA certificate and key exist.
+    ///
+    /// When they do, leaf certificates are signed by the intermediate
+    /// instead of the root.
+    pub fn intermediate_exists(&self) -> bool {
+        self.intermediate_cert_path().exists() && self.intermediate_key_path().exists()
+    }
+
     /// Create a new CA certificate and key pair.
     ///
-    /// Generates a new 3072-bit RSA key pair and creates a self-signed
-    /// CA certificate valid for 10 years. The certificate includes:
-    /// - Subject: `fastcert <user>@<hostname>`
-    /// - Basic Constraints: CA=true
-    /// - Key Usage: Certificate Sign, CRL Sign
+    /// Uses the options from the environment (see [`CaOptions::from_env`]),
+    /// which default to a 3072-bit RSA key.
     ///
     /// # Returns
     ///
@@ -344,15 +905,52 @@ impl CertificateAuthority {
     ///
     /// # Errors
     ///
-    /// Returns an error if certificate generation or serialization fails.
+    /// Returns an error if `FASTCERT_CA_KEY` is invalid or if certificate
+    /// generation or serialization fails.
     pub fn create_ca(&mut self) -> Result<()> {
+        let options = CaOptions::from_env()?;
+        self.create_ca_with(&options)
+    }
+
+    /// Create a new CA certificate and key pair with explicit options.
+    ///
+    /// Generates a key pair using the configured algorithm and creates a
+    /// self-signed CA certificate valid for `options.validity_days` (10 years
+    /// by default). The certificate includes:
+    /// - Subject: `fastcert <user>@<hostname>`
+    /// - Basic Constraints: CA=true, path length from `options.ca_path_len`
+    /// - Key Usage: Certificate Sign, CRL Sign
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - CA creation options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the validity is zero days, or if certificate
+    /// generation or serialization fails.
+    pub fn create_ca_with(&mut self, options: &CaOptions) -> Result<()> {
+        self.check_file_names()?;
+        if options.validity_days == 0 {
+            return Err(Error::Certificate(
+                "CA validity must be at least one day".to_string(),
+            ));
+        }
+        if options.validity_days < crate::cert::DEFAULT_VALIDITY_DAYS {
+            eprintln!(
+                "Warning: The CA is valid for {} days; certificates it issues will expire with it",
+                options.validity_days
+            );
+        }
         eprintln!("{}", "Generating CA certificate...".cyan());
 
-        // Generate RSA-3072 key pair for the CA
-        let key_pair = KeyPair::generate_rsa_for(&rcgen::PKCS_RSA_SHA256, RsaKeySize::_3072)
-            .map_err(|e| Error::Certificate(format!("Failed to generate CA key pair: {}", e)))?;
+        crate::log_event(
+            "ca",
+            &format!("Generating {:?} CA key", options.key_algorithm),
+        );
+        let key_pair = options.key_algorithm.generate()?;
 
-        let params = create_ca_params()
+        let params = create_ca_params(options)
             .map_err(|e| Error::Certificate(format!("Failed to create CA parameters: {}", e)))?;
 
         // Create self-signed CA certificate
@@ -361,7 +959,7 @@ impl CertificateAuthority {
             .map_err(|e| Error::Certificate(format!("Failed to generate CA certificate: {}", e)))?;
 
         let cert_pem = cert.pem();
-        let key_pem = key_pair.serialize_pem();
+        let key_pem = serialize_ca_key(&key_pair)?;
 
         self.cert = Some(cert);
         self.cert_pem = Some(cert_pem);
@@ -372,9 +970,9 @@ impl CertificateAuthority {
 
     /// Save the CA certificate and private key to disk.
     ///
-    /// Writes the CA certificate to `rootCA.pem` with permissions 0644,
-    /// and the private key to `rootCA-key.pem` with permissions 0400
-    /// (Unix only).
+    /// Writes the CA certificate to [`cert_path`](Self::cert_path) with
+    /// permissions 0644, and the private key to [`key_path`](Self::key_path)
+    /// with permissions 0400 (Unix only). Existing files are first moved to `.bak` backups.
     ///
     /// # Returns
     ///
@@ -397,50 +995,263 @@ impl CertificateAuthority {
             .as_ref()
             .ok_or_else(|| Error::Certificate("No private key available to save".to_string()))?;
 
-        // Save certificate
-        let cert_path = self.cert_path();
-        let mut file = File::create(&cert_path).map_err(|e| {
-            Error::Certificate(format!(
-                "Failed to create certificate file at {:?}: {}",
-                cert_path, e
-            ))
-        })?;
-        file.write_all(cert_pem.as_bytes()).map_err(|e| {
-            Error::Certificate(format!(
-                "Failed to write certificate to {:?}: {}",
-                cert_path, e
-            ))
-        })?;
-        #[cfg(unix)]
-        fs::set_permissions(&cert_path, fs::Permissions::from_mode(0o644)).map_err(|e| {
-            Error::Certificate(format!(
-                "Failed to set permissions on {:?}: {}",
-                cert_path, e
-            ))
-        })?;
+        write_ca_files(&self.cert_path(), cert_pem, &self.key_path(), key_pem)
+    }
 
-        // Save private key
-        let key_path = self.key_path();
-        let mut file = File::create(&key_path).map_err(|e| {
-            Error::Certificate(format!(
-                "Failed to create key file at {:?}: {}",
-                key_path, e
-            ))
-        })?;
-        file.write_all(key_pem.as_bytes()).map_err(|e| {
-            Error::Certificate(format!("Failed to write key to {:?}: {}", key_path, e))
-        })?;
-        #[cfg(unix)]
-        fs::set_permissions(&key_path, fs::Permissions::from_mode(0o400)).map_err(|e| {
+    /// Create an intermediate CA signed by this root and save it to disk.
+    ///
+    /// Writes `intermediateCA.pem` and `intermediateCA-key.pem` next to the
+    /// root. The intermediate has a path length of zero, so it can only sign
+    /// leaf certificates. Existing intermediate files are backed up first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The root CA has not been loaded or created
+    /// - `FASTCERT_CA_KEY` is invalid
+    /// - Certificate generation or file operations fail
+    pub fn create_intermediate(&self) -> Result<()> {
+        let root_cert_pem = self.cert_pem.as_deref().ok_or(Error::CARootNotFound)?;
+        let root_key_pem = self.key_pem.as_deref().ok_or(Error::CAKeyMissing)?;
+
+        let issuer = load_issuer(root_cert_pem, root_key_pem)?;
+
+        let root_der = crate::cert::parse_pem_cert(root_cert_pem.as_bytes())?;
+        let (_, root) = x509_parser::parse_x509_certificate(&root_der)
+            .map_err(|e| Error::Certificate(format!("Failed to parse CA certificate: {}", e)))?;
+        if let Ok(Some(bc)) = root.basic_constraints()
+            && bc.value.path_len_constraint == Some(0)
+        {
+            return Err(Error::Certificate(
+                "The root CA has a path length of 0 and cannot sign an intermediate CA".to_string(),
+            ));
+        }
+
+        let key_pair = CaOptions::from_env()?.key_algorithm.generate()?;
+        let params = create_intermediate_params(root.validity().not_after.to_datetime())?;
+        let cert = params.signed_by(&key_pair, &issuer).map_err(|e| {
             Error::Certificate(format!(
-                "Failed to set permissions on {:?}: {}",
-                key_path, e
+                "Failed to generate intermediate certificate: {}",
+                e
             ))
         })?;
 
+        write_ca_files(
+            &self.intermediate_cert_path(),
+            &cert.pem(),
+            &self.intermediate_key_path(),
+            &serialize_ca_key(&key_pair)?,
+        )?;
+        println!("{}", "Created a new intermediate CA".green().bold());
+
         Ok(())
     }
 
+    /// Create and save a new CA in this directory.
+    ///
+    /// Unlike [`CertificateAuthority::init_ca`], this never loads an
+    /// existing CA. With `overwrite`, the current root and intermediate are
+    /// moved to `.bak` backups first; certificates they signed stop being
+    /// trusted once the new root replaces the old one in the trust stores.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A CA already exists and `overwrite` is false
+    /// - The CA directory cannot be created or written to
+    ///   (`Error::CARootNotWritable`)
+    /// - CA generation or file operations fail
+    pub fn generate(&mut self, overwrite: bool) -> Result<CaCreated> {
+        if self.cert_exists() {
+            if !overwrite {
+                return Err(Error::Certificate(format!(
+                    "A CA already exists in {}",
+                    self.root_path.display()
+                )));
+            }
+            for path in [self.intermediate_cert_path(), self.intermediate_key_path()] {
+                crate::fileutil::backup_existing(&path)?;
+            }
+        }
+
+        ensure_caroot_writable(&self.root_path)?;
+        self.init()?;
+        let _lock = CaRootLock::acquire(&self.root_path)?;
+        crate::log_event("ca", &format!("Creating a new CA in {:?}", self.root_path));
+        self.create_ca()?;
+        self.save()?;
+        println!("{}", "Created a new local CA".green().bold());
+
+        let cert_pem = self.cert_pem.as_deref().ok_or(Error::CARootNotFound)?;
+        let cert_der = crate::cert::parse_pem_cert(cert_pem.as_bytes())?;
+        let info = crate::cert::inspect_certificate(cert_pem.as_bytes())?;
+        Ok(CaCreated {
+            cert_path: self.cert_path(),
+            key_path: self.key_path(),
+            subject: info.subject,
+            fingerprint: sha256_fingerprint(&cert_der),
+        })
+    }
+
+    /// Replace this CA with a newly generated one.
+    ///
+    /// The current root (and intermediate, if any) are moved to backups, then
+    /// a new root is created with the options from the environment. With
+    /// `reissue_leaves`, every leaf `.pem` in the CA directory that was
+    /// signed by the old CA is re-issued for the same SANs, key type and
+    /// client-auth usage, at the same paths, under the new CA.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The CA has not been loaded
+    /// - Backing up the old files fails
+    /// - The new CA cannot be created or saved
+    pub fn rotate(&mut self, reissue_leaves: bool) -> Result<RotationReport> {
+        let old_root_pem = self.cert_pem.clone().ok_or(Error::CARootNotFound)?;
+        let mut old_ca_ders = vec![crate::cert::parse_pem_cert(old_root_pem.as_bytes())?];
+        if self.intermediate_exists() {
+            let intermediate_pem = fs::read(self.intermediate_cert_path())?;
+            old_ca_ders.push(crate::cert::parse_pem_cert(&intermediate_pem)?);
+        }
+
+        let mut report = RotationReport::default();
+        let leaves = if reissue_leaves {
+            self.find_leaves(&old_ca_ders, &mut report)?
+        } else {
+            Vec::new()
+        };
+
+        crate::log_event("ca", &format!("Rotating CA in {:?}", self.root_path));
+        for path in [
+            self.cert_path(),
+            self.key_path(),
+            self.intermediate_cert_path(),
+            self.intermediate_key_path(),
+        ] {
+            report
+                .backups
+                .extend(crate::fileutil::backup_existing(&path)?);
+        }
+
+        self.create_ca()?;
+        self.save()?;
+        println!("{}", "Created a new local CA".green().bold());
+
+        let (ca_cert_pem, ca_key_pem) = self.signing_pems()?;
+        for (path, mut config) in leaves {
+            config.cert_file = Some(path.clone());
+            let result = crate::cert::generate_certificate_internal(
+                &config,
+                &ca_cert_pem,
+                &ca_key_pem,
+                self.next_serial()?,
+            )?;
+            crate::audit::record(&self.root_path, &result)?;
+            report.reissued.push(path);
+        }
+
+        Ok(report)
+    }
+
+    /// Find leaf certificates in the CA directory signed by one of the given
+    /// CA certificates, with the configuration needed to re-issue them.
+    fn find_leaves(
+        &self,
+        ca_ders: &[Vec<u8>],
+        report: &mut RotationReport,
+    ) -> Result<Vec<(PathBuf, crate::cert::CertificateConfig)>> {
+        let ca_files = [
+            self.cert_file.as_str(),
+            self.key_file.as_str(),
+            INTERMEDIATE_CERT_FILE,
+            INTERMEDIATE_KEY_FILE,
+        ];
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.root_path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                name.ends_with(".pem") && !name.ends_with("-key.pem") && !ca_files.contains(&name)
+            })
+            .collect();
+        paths.sort();
+
+        let mut leaves = Vec::new();
+        for path in paths {
+            let blocks = match crate::cert::parse_pem_blocks(&fs::read(&path)?) {
+                Ok(blocks) => blocks,
+                Err(e) => {
+                    report.skipped.push((path, e.to_string()));
+                    continue;
+                }
+            };
+            let Some(cert) = crate::cert::first_of_tag(&blocks, "CERTIFICATE") else {
+                report
+                    .skipped
+                    .push((path, "no certificate found".to_string()));
+                continue;
+            };
+            let cert_der = cert.contents().to_vec();
+
+            let signed_by_old_ca = ca_ders.iter().any(|ca_der| {
+                crate::cert::validate_chain(std::slice::from_ref(&cert_der), ca_der).is_ok()
+            });
+            if !signed_by_old_ca {
+                report
+                    .skipped
+                    .push((path, "not issued by this CA".to_string()));
+                continue;
+            }
+
+            let mut config = crate::cert::config_from_cert(&cert_der)?;
+            if config.hosts.is_empty() {
+                report
+                    .skipped
+                    .push((path, "no subject alternative names".to_string()));
+                continue;
+            }
+
+            // A key stored in the same file stays combined
+            let combined = blocks.iter().any(|block| is_private_key_tag(block.tag()));
+            config.key_file = Some(if combined {
+                path.clone()
+            } else {
+                let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("cert");
+                path.with_file_name(format!("{}-key.pem", stem))
+            });
+
+            leaves.push((path, config));
+        }
+
+        Ok(leaves)
+    }
+
+    /// Get the certificate and key PEMs that should sign leaf certificates.
+    ///
+    /// Returns the intermediate CA if one exists, otherwise the root.
+    pub(crate) fn signing_pems(&self) -> Result<(String, String)> {
+        if self.intermediate_exists() {
+            let cert_pem = fs::read_to_string(self.intermediate_cert_path())?;
+            let key_pem = fs::read_to_string(self.intermediate_key_path())?;
+            check_ca_file_contents(
+                &self.intermediate_cert_path(),
+                &cert_pem,
+                &self.intermediate_key_path(),
+                &key_pem,
+            )?;
+            return Ok((cert_pem, key_pem));
+        }
+
+        let cert_pem = self.cert_pem.clone().ok_or_else(|| {
+            Error::Certificate("CA not loaded. Call load_or_create() first.".to_string())
+        })?;
+        let key_pem = self.key_pem.clone().ok_or_else(|| {
+            Error::Certificate("CA key not loaded. Call load_or_create() first.".to_string())
+        })?;
+        Ok((cert_pem, key_pem))
+    }
+
     /// Load an existing CA certificate and private key from disk.
     ///
     /// Reads the CA certificate and private key PEM files and stores them in memory.
@@ -454,7 +1265,10 @@ impl CertificateAuthority {
     /// Returns an error if:
     /// - The certificate or key file doesn't exist
     /// - The files cannot be read
+    /// - The certificate file holds no certificate or also holds a private
+    ///   key, or the key file holds no private key (e.g. swapped files)
     pub fn load(&mut self) -> Result<()> {
+        self.check_file_names()?;
         let cert_path = self.cert_path();
         if !cert_path.exists() {
             return Err(Error::Certificate("CA certificate not found".to_string()));
@@ -467,6 +1281,7 @@ impl CertificateAuthority {
 
         let cert_pem = fs::read_to_string(&cert_path)?;
         let key_pem = fs::read_to_string(&key_path)?;
+        check_ca_file_contents(&cert_path, &cert_pem, &key_path, &key_pem)?;
 
         self.cert_pem = Some(cert_pem);
         self.key_pem = Some(key_pem);
@@ -493,18 +1308,18 @@ impl CertificateAuthority {
     pub fn unique_name(&self) -> Result<String> {
         // Parse the certificate to get the serial number
         let cert_pem = fs::read_to_string(self.cert_path())?;
+        unique_name_from_pem(&cert_pem)
+    }
 
-        // Parse PEM to get DER
-        let pem_data = pem::parse(&cert_pem)
-            .map_err(|e| Error::Certificate(format!("Failed to parse PEM: {}", e)))?;
-
-        // Parse X.509 certificate
-        let cert = x509_parser::parse_x509_certificate(pem_data.contents())
-            .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?
-            .1;
-
-        let serial = cert.serial.to_str_radix(10);
-        Ok(format!("fastcert development CA {}", serial))
+    /// Get the SHA-256 fingerprint of the CA certificate.
+    ///
+    /// See [`fingerprint_sha256`] for the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the certificate file cannot be read or parsed.
+    pub fn fingerprint_sha256(&self) -> Result<String> {
+        fingerprint_from_pem(&fs::read_to_string(self.cert_path())?)
     }
 
     /// Get the serial number of the CA certificate.
@@ -513,7 +1328,7 @@ impl CertificateAuthority {
     ///
     /// # Returns
     ///
-    /// The serial number as a hexadecimal string.
+    /// The serial number as uppercase hex, as printed by `openssl x509 -serial`.
     ///
     /// # Errors
     ///
@@ -527,7 +1342,25 @@ impl CertificateAuthority {
         let cert = x509_parser::parse_x509_certificate(pem_data.contents())
             .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?
             .1;
-        Ok(cert.serial.to_str_radix(16))
+        Ok(crate::cert::format_serial(cert.raw_serial()))
+    }
+
+    /// Take the next serial number from the counter stored in the CA directory.
+    ///
+    /// The counter lives in `serial.txt` and is advanced on every call, so
+    /// serials keep increasing across runs. The high 64 bits hold the counter
+    /// and the low 64 bits are random.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the counter file is corrupt or cannot be written.
+    pub fn serial_from_counter(&self) -> Result<u128> {
+        counter_serial(&self.root_path)
+    }
+
+    /// Serial number for the next leaf certificate signed by this CA.
+    pub(crate) fn next_serial(&self) -> Result<[u8; 16]> {
+        next_leaf_serial(&self.root_path)
     }
 
     /// Create a certificate builder for issuing new certificates
@@ -550,17 +1383,11 @@ impl CertificateAuthority {
     ///
     /// Returns an error if the CA certificate or key hasn't been loaded.
     pub fn issue_certificate(&self) -> Result<crate::cert::CertificateBuilder> {
-        let ca_cert_pem = self.cert_pem.as_ref().ok_or_else(|| {
-            Error::Certificate("CA not loaded. Call load_or_create() first.".to_string())
-        })?;
-
-        let ca_key_pem = self.key_pem.as_ref().ok_or_else(|| {
-            Error::Certificate("CA key not loaded. Call load_or_create() first.".to_string())
-        })?;
-
+        let (ca_cert_pem, ca_key_pem) = self.signing_pems()?;
         Ok(crate::cert::CertificateBuilder::new(
-            ca_cert_pem.clone(),
-            ca_key_pem.clone(),
+            ca_cert_pem,
+            ca_key_pem,
+            self.root_path.clone(),
         ))
     }
 
@@ -588,6 +1415,11 @@ impl CertificateAuthority {
             ));
         }
 
+        crate::log_event(
+            "install",
+            &format!("Installing {:?} into trust stores", self.cert_path()),
+        );
+
         #[cfg(target_os = "macos")]
         {
             crate::truststore::install_macos(&self.cert_path())?;
@@ -614,6 +1446,7 @@ impl CertificateAuthority {
             );
         }
 
+        println!("CA fingerprint (SHA-256): {}", self.fingerprint_sha256()?);
         Ok(())
     }
 
@@ -638,6 +1471,11 @@ impl CertificateAuthority {
             return Ok(());
         }
 
+        crate::log_event(
+            "uninstall",
+            &format!("Removing {:?} from trust stores", self.cert_path()),
+        );
+
         #[cfg(target_os = "macos")]
         {
             crate::truststore::uninstall_macos(&self.cert_path())?;
@@ -698,6 +1536,430 @@ pub fn is_serial_unique(serial: &str, ca_path: &Path) -> Result<bool> {
     Ok(existing_serial != serial)
 }
 
+/// Pick the serial number for a new leaf certificate issued from `caroot`.
+///
+/// Serials are random unless `FASTCERT_SERIAL_MODE=counter`, in which case
+/// they come from the counter persisted in the CA directory.
+pub(crate) fn next_leaf_serial(caroot: &Path) -> Result<[u8; 16]> {
+    let counter_mode = std::env::var("FASTCERT_SERIAL_MODE")
+        .map(|mode| mode.eq_ignore_ascii_case("counter"))
+        .unwrap_or(false);
+    if counter_mode {
+        Ok(counter_serial(caroot)?.to_be_bytes())
+    } else {
+        Ok(crate::cert::generate_serial_number())
+    }
+}
+
+/// Advance the counter in `caroot/serial.txt` and build a serial from it.
+///
+/// The counter fills the high 64 bits and a random value the low 64 bits, so
+/// serials never collide even when the random suffix does, and later serials
+/// always sort after earlier ones.
+fn counter_serial(caroot: &Path) -> Result<u128> {
+    let path = caroot.join(SERIAL_FILE);
+    let last = match fs::read_to_string(&path) {
+        Ok(contents) => contents.trim().parse::<u64>().map_err(|_| {
+            Error::Certificate(format!("Invalid serial counter in {}", path.display()))
+        })?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(e.into()),
+    };
+    // Keep the top bit clear so the serial stays a positive integer
+    let counter = last
+        .checked_add(1)
+        .filter(|counter| *counter <= i64::MAX as u64)
+        .ok_or_else(|| {
+            Error::Certificate(format!("Serial counter in {} is exhausted", path.display()))
+        })?;
+
+    // Write the new value before using it so a crash can only skip numbers
+    let tmp_path = caroot.join(format!("{}.tmp", SERIAL_FILE));
+    fs::write(&tmp_path, format!("{}\n", counter))?;
+    fs::rename(&tmp_path, &path)?;
+
+    let random = crate::cert::generate_serial_number();
+    let suffix = u64::from_be_bytes(random[8..].try_into().expect("8-byte slice"));
+    Ok((u128::from(counter) << 64) | u128::from(suffix))
+}
+
+/// Generate a certificate revocation list signed by the root CA.
+///
+/// Loads the CA from the CAROOT directory and builds a CRL listing the given
+/// serial numbers as revoked. Serials are hex strings as printed for issued
+/// certificates (colon separators are accepted).
+///
+/// # Arguments
+///
+/// * `revoked_serials` - Hex-encoded serial numbers of revoked certificates
+/// * `next_update_days` - Number of days until the next CRL is due
+///
+/// # Returns
+///
+/// The DER-encoded CRL.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The CA cannot be loaded
+/// - A serial number is not valid hex
+/// - `next_update_days` is zero
+/// - CRL signing fails
+pub fn generate_crl(revoked_serials: &[String], next_update_days: u32) -> Result<Vec<u8>> {
+    if next_update_days == 0 {
+        return Err(Error::Certificate(
+            "CRL next update must be at least one day away".to_string(),
+        ));
+    }
+
+    let mut ca = get_ca()?;
+    ca.load()?;
+    let cert_pem = ca.cert_pem.as_deref().ok_or(Error::CARootNotFound)?;
+    let key_pem = ca.key_pem.as_deref().ok_or(Error::CAKeyMissing)?;
+
+    let issuer = load_issuer(cert_pem, key_pem)?;
+
+    let now = OffsetDateTime::now_utc();
+    let revoked_certs = revoked_serials
+        .iter()
+        .map(|serial| {
+            Ok(RevokedCertParams {
+                serial_number: parse_hex_serial(serial)?,
+                revocation_time: now,
+                reason_code: None,
+                invalidity_date: None,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let params = CertificateRevocationListParams {
+        this_update: now,
+        next_update: now + Duration::days(i64::from(next_update_days)),
+        // Seconds since the epoch keep CRL numbers increasing across runs
+        crl_number: SerialNumber::from(now.unix_timestamp() as u64),
+        issuing_distribution_point: None,
+        revoked_certs,
+        key_identifier_method: KeyIdMethod::Sha256,
+    };
+
+    let crl = params
+        .signed_by(&issuer)
+        .map_err(|e| Error::Certificate(format!("Failed to sign CRL: {}", e)))?;
+
+    Ok(crl.der().to_vec())
+}
+
+/// Get the root CA certificate as base64-encoded DER.
+///
+/// This is the form expected by Apple configuration profiles and Android
+/// network security configs, which cannot take PEM directly.
+///
+/// # Errors
+///
+/// Returns an error if the CA certificate does not exist or cannot be parsed.
+pub fn ca_cert_base64_der() -> Result<String> {
+    use base64::Engine;
+
+    let ca = get_ca()?;
+    if !ca.cert_exists() {
+        return Err(Error::CARootNotFound);
+    }
+
+    let cert_pem = fs::read_to_string(ca.cert_path())?;
+    let pem_data = pem::parse(&cert_pem)
+        .map_err(|e| Error::Certificate(format!("Failed to parse PEM: {}", e)))?;
+
+    Ok(base64::engine::general_purpose::STANDARD.encode(pem_data.contents()))
+}
+
+/// Build a minimal Apple configuration profile installing the root CA.
+///
+/// The returned XML can be saved as a `.mobileconfig` file and opened on
+/// iOS or macOS to add the CA as a trusted root.
+///
+/// # Errors
+///
+/// Returns an error if the CA certificate does not exist or cannot be parsed.
+pub fn ca_mobileconfig() -> Result<String> {
+    let cert_base64 = ca_cert_base64_der()?;
+    let ca = get_ca()?;
+    let name = ca.unique_name()?;
+
+    Ok(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>PayloadContent</key>
+    <array>
+        <dict>
+            <key>PayloadCertificateFileName</key>
+            <string>{file}</string>
+            <key>PayloadContent</key>
+            <data>{cert}</data>
+            <key>PayloadDisplayName</key>
+            <string>{name}</string>
+            <key>PayloadIdentifier</key>
+            <string>dev.fastcert.ca.{cert_uuid}</string>
+            <key>PayloadType</key>
+            <string>com.apple.security.root</string>
+            <key>PayloadUUID</key>
+            <string>{cert_uuid}</string>
+            <key>PayloadVersion</key>
+            <integer>1</integer>
+        </dict>
+    </array>
+    <key>PayloadDisplayName</key>
+    <string>{name}</string>
+    <key>PayloadIdentifier</key>
+    <string>dev.fastcert.profile.{profile_uuid}</string>
+    <key>PayloadType</key>
+    <string>Configuration</string>
+    <key>PayloadUUID</key>
+    <string>{profile_uuid}</string>
+    <key>PayloadVersion</key>
+    <integer>1</integer>
+</dict>
+</plist>
+"#,
+        file = ca.cert_file,
+        cert = cert_base64,
+        name = name,
+        cert_uuid = random_uuid(),
+        profile_uuid = random_uuid(),
+    ))
+}
+
+/// Generate a random (version 4) UUID string for configuration profiles.
+fn random_uuid() -> String {
+    let mut bytes = crate::cert::generate_serial_number();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = hex::encode_upper(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Get the CA certificates as a DER PKCS#7 (`.p7b`) bundle.
+///
+/// The bundle is a certificates-only `SignedData` holding the root CA and,
+/// when present, the intermediate CA. Java `keytool -importcert` and the
+/// Windows certificate import wizard accept this form.
+///
+/// # Errors
+///
+/// Returns an error if the CA certificate does not exist or cannot be parsed.
+pub fn export_ca_pkcs7() -> Result<Vec<u8>> {
+    use yasna::models::ObjectIdentifier;
+
+    let ca = get_ca()?;
+    if !ca.cert_exists() {
+        return Err(Error::CARootNotFound);
+    }
+
+    let mut cert_ders = Vec::new();
+    for path in [ca.cert_path(), ca.intermediate_cert_path()] {
+        if path.exists() {
+            let blocks = crate::cert::parse_pem_blocks(&fs::read(&path)?)?;
+            let cert = crate::cert::first_of_tag(&blocks, "CERTIFICATE").ok_or_else(|| {
+                Error::Certificate(format!("No certificate found in {}", path.display()))
+            })?;
+            cert_ders.push(cert.contents().to_vec());
+        }
+    }
+
+    // ContentInfo { signedData, SignedData { v1, no digests, data, certs, no signers } }
+    Ok(yasna::construct_der(|w| {
+        w.write_sequence(|w| {
+            w.next()
+                .write_oid(&ObjectIdentifier::from_slice(&[1, 2, 840, 113549, 1, 7, 2]));
+            w.next().write_tagged(yasna::Tag::context(0), |w| {
+                w.write_sequence(|w| {
+                    w.next().write_u8(1);
+                    w.next().write_set(|_| {});
+                    w.next().write_sequence(|w| {
+                        w.next().write_oid(&ObjectIdentifier::from_slice(&[
+                            1, 2, 840, 113549, 1, 7, 1,
+                        ]));
+                    });
+                    w.next().write_tagged_implicit(yasna::Tag::context(0), |w| {
+                        w.write_set_of(|w| {
+                            for der in &cert_ders {
+                                w.next().write_der(der);
+                            }
+                        });
+                    });
+                    w.next().write_set(|_| {});
+                });
+            });
+        });
+    }))
+}
+
+/// Encode a DER PKCS#7 bundle as PEM (`PKCS7` block).
+pub fn pkcs7_to_pem(pkcs7_der: &[u8]) -> String {
+    pem::encode(&pem::Pem::new("PKCS7", pkcs7_der))
+}
+
+/// SHA-256 fingerprint of DER data as uppercase, colon-separated hex.
+pub(crate) fn sha256_fingerprint(der: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Encode a DER CRL as PEM (`X509 CRL` block).
+pub fn crl_to_pem(crl_der: &[u8]) -> String {
+    pem::encode(&pem::Pem::new("X509 CRL", crl_der))
+}
+
+/// Parse a hex serial number, tolerating colons and an odd number of digits.
+fn parse_hex_serial(serial: &str) -> Result<SerialNumber> {
+    let mut digits: String = serial.chars().filter(|c| *c != ':').collect();
+    if digits.len() % 2 == 1 {
+        digits.insert(0, '0');
+    }
+
+    let bytes = hex::decode(&digits)
+        .map_err(|e| Error::Certificate(format!("Invalid serial number {:?}: {}", serial, e)))?;
+    if bytes.is_empty() {
+        return Err(Error::Certificate("Serial number is empty".to_string()));
+    }
+
+    Ok(SerialNumber::from_slice(&bytes))
+}
+
+/// Serialize a CA private key as PEM, encrypting it if a passphrase is set.
+///
+/// With `FASTCERT_CA_PASSPHRASE` set the key is written as an encrypted
+/// PKCS#8 `ENCRYPTED PRIVATE KEY` (PBES2 with PBKDF2-SHA256 and AES-256-CBC);
+/// otherwise as a plain `PRIVATE KEY`.
+fn serialize_ca_key(key_pair: &KeyPair) -> Result<String> {
+    match ca_passphrase() {
+        Some(passphrase) => crate::cert::key_to_encrypted_pem(key_pair, &passphrase),
+        None => Ok(key_pair.serialize_pem()),
+    }
+}
+
+/// Parse a CA private key PEM, decrypting it with `FASTCERT_CA_PASSPHRASE`
+/// when it is encrypted.
+///
+/// # Errors
+///
+/// Returns `Error::CAKeyPassphrase` if the key is encrypted and the
+/// passphrase is missing or wrong, or `Error::Certificate` if the key cannot
+/// be parsed.
+pub(crate) fn load_ca_key_pair(key_pem: &str) -> Result<KeyPair> {
+    let pem_data = pem::parse(key_pem)
+        .map_err(|e| Error::Certificate(format!("Failed to parse CA key: {}", e)))?;
+    if pem_data.tag() != "ENCRYPTED PRIVATE KEY" {
+        return KeyPair::from_pem(key_pem)
+            .map_err(|e| Error::Certificate(format!("Failed to parse CA key: {}", e)));
+    }
+
+    let passphrase = ca_passphrase().ok_or_else(|| {
+        Error::CAKeyPassphrase("the key is encrypted and no passphrase was given".to_string())
+    })?;
+    let encrypted = pkcs8::EncryptedPrivateKeyInfo::try_from(pem_data.contents())
+        .map_err(|e| Error::Certificate(format!("Failed to parse encrypted CA key: {}", e)))?;
+    let key_der = encrypted
+        .decrypt(passphrase.as_bytes())
+        .map_err(|_| Error::CAKeyPassphrase("wrong passphrase".to_string()))?;
+
+    KeyPair::try_from(key_der.as_bytes())
+        .map_err(|e| Error::Certificate(format!("Failed to parse CA key: {}", e)))
+}
+
+/// Build a signing issuer from a CA certificate and its private key.
+///
+/// # Errors
+///
+/// Returns an error if the key cannot be loaded (see [`load_ca_key_pair`]),
+/// or if it does not belong to the certificate, which would otherwise
+/// produce certificates that fail verification.
+pub(crate) fn load_issuer(cert_pem: &str, key_pem: &str) -> Result<Issuer<'static, KeyPair>> {
+    let key_pair = load_ca_key_pair(key_pem)?;
+    let cert_der = crate::cert::parse_pem_cert(cert_pem.as_bytes())?;
+    if !crate::cert::key_pair_matches_cert(&key_pair, &cert_der)? {
+        return Err(Error::Certificate(
+            "The CA private key does not match the CA certificate. Check that the key and certificate files in CAROOT belong together".to_string(),
+        ));
+    }
+
+    Issuer::from_ca_cert_der(&cert_der.into(), key_pair)
+        .map_err(|e| Error::Certificate(format!("Failed to create issuer from CA cert: {}", e)))
+}
+
+/// Get the CA key passphrase from `FASTCERT_CA_PASSPHRASE`, if set.
+fn ca_passphrase() -> Option<String> {
+    std::env::var("FASTCERT_CA_PASSPHRASE")
+        .ok()
+        .filter(|p| !p.is_empty())
+}
+
+/// File name of the root CA certificate in CAROOT.
+///
+/// `FASTCERT_CA_CERT_NAME` overrides the default `rootCA.pem`, so an
+/// existing CA directory laid out for another tool can be used unchanged.
+fn root_cert_file() -> Result<String> {
+    let name = ca_file_override("FASTCERT_CA_CERT_NAME", ROOT_CERT_FILE);
+    check_ca_file_name("FASTCERT_CA_CERT_NAME", &name)?;
+    Ok(name)
+}
+
+/// File name of the root CA private key in CAROOT.
+///
+/// `FASTCERT_CA_KEY_NAME` overrides the default `rootCA-key.pem`.
+fn root_key_file() -> Result<String> {
+    let name = ca_file_override("FASTCERT_CA_KEY_NAME", ROOT_KEY_FILE);
+    check_ca_file_name("FASTCERT_CA_KEY_NAME", &name)?;
+    Ok(name)
+}
+
+/// Read a CA file name override from `var`, falling back to `default` when
+/// it is unset or empty. The name is not validated.
+fn ca_file_override(var: &str, default: &str) -> String {
+    std::env::var(var)
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Check that a CA file name read from `var` is a bare file name.
+///
+/// Anything with a directory part is rejected rather than replaced by the
+/// default, which would silently use or create a different CA.
+fn check_ca_file_name(var: &str, name: &str) -> Result<()> {
+    if Path::new(name).file_name() == Some(std::ffi::OsStr::new(name)) {
+        Ok(())
+    } else {
+        Err(Error::Certificate(format!(
+            "{} must be a file name inside CAROOT, not a path: '{}'",
+            var, name
+        )))
+    }
+}
+
+/// Check whether automatic CA creation has been disabled.
+///
+/// Set `FASTCERT_NO_AUTO_CA` to make operations fail instead of silently
+/// minting a new root CA when none exists (useful in CI).
+fn auto_ca_disabled() -> bool {
+    crate::env_flag("FASTCERT_NO_AUTO_CA")
+}
+
 /// Get the current username and hostname in "user@hostname" format.
 ///
 /// Used to personalize the CA certificate subject. Falls back to
@@ -722,10 +1984,14 @@ fn get_user_and_hostname() -> String {
 /// Create certificate parameters for a new CA certificate.
 ///
 /// Generates parameters for a self-signed CA certificate with:
-/// - Subject: fastcert development CA / user@hostname / fastcert user@hostname
-/// - Validity: 10 years from now
-/// - Basic Constraints: CA=true (unconstrained)
+/// - Subject: fastcert development CA / user@hostname / fastcert user@hostname,
+///   with the `FASTCERT_PROFILE` name appended when one is selected
+/// - Validity: `options.validity_days` from now (10 years by default),
+///   backdated like leaf certificates
+/// - Basic Constraints: CA=true, with `options.ca_path_len` as the path
+///   length constraint (unconstrained by default)
 /// - Key Usage: Certificate Sign, CRL Sign
+/// - Name Constraints: the permitted DNS suffixes from `options`, if any
 ///
 /// # Returns
 ///
@@ -734,8 +2000,12 @@ fn get_user_and_hostname() -> String {
 /// # Errors
 ///
 /// Returns an error if parameter creation fails.
-fn create_ca_params() -> Result<CertificateParams> {
-    let user_host = get_user_and_hostname();
+fn create_ca_params(options: &CaOptions) -> Result<CertificateParams> {
+    // Keep CAs from different profiles distinguishable in trust stores
+    let user_host = match active_profile()? {
+        Some(profile) => format!("{} ({})", get_user_and_hostname(), profile),
+        None => get_user_and_hostname(),
+    };
 
     let mut params = CertificateParams::default();
 
@@ -745,22 +2015,196 @@ fn create_ca_params() -> Result<CertificateParams> {
     dn.push(DnType::CommonName, format!("fastcert {}", user_host));
     params.distinguished_name = dn;
 
-    // Valid for 10 years
+    // Backdated like leaf certificates
     let now = OffsetDateTime::now_utc();
-    params.not_before = now;
-    params.not_after = now + Duration::days(3650);
+    params.not_before = now - crate::cert::default_backdate();
+    params.not_after = now + Duration::days(i64::from(options.validity_days));
 
-    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params.is_ca = IsCa::Ca(match options.ca_path_len {
+        Some(path_len) => BasicConstraints::Constrained(path_len),
+        None => BasicConstraints::Unconstrained,
+    });
     params.key_usages = vec![
         rcgen::KeyUsagePurpose::KeyCertSign,
         rcgen::KeyUsagePurpose::CrlSign,
     ];
 
+    if !options.permitted_dns.is_empty() {
+        params.name_constraints = Some(rcgen::NameConstraints {
+            permitted_subtrees: options
+                .permitted_dns
+                .iter()
+                .cloned()
+                .map(rcgen::GeneralSubtree::DnsName)
+                .collect(),
+            excluded_subtrees: Vec::new(),
+        });
+    }
+
     // Let rcgen generate the key pair automatically
 
     Ok(params)
 }
 
+/// Create certificate parameters for an intermediate CA certificate.
+///
+/// The intermediate is valid for 5 years, but never beyond `root_not_after`
+/// so it cannot outlive the root that signs it, and may only issue
+/// end-entity certificates (path length 0).
+fn create_intermediate_params(root_not_after: OffsetDateTime) -> Result<CertificateParams> {
+    let user_host = get_user_and_hostname();
+
+    let mut params = CertificateParams::default();
+
+    let mut dn = DistinguishedName::new();
+    dn.push(DnType::OrganizationName, "fastcert development CA");
+    dn.push(DnType::OrganizationalUnitName, &user_host);
+    dn.push(
+        DnType::CommonName,
+        format!("fastcert intermediate {}", user_host),
+    );
+    params.distinguished_name = dn;
+
+    let now = OffsetDateTime::now_utc();
+    params.not_before = now - crate::cert::default_backdate();
+    params.not_after = (now + Duration::days(1825)).min(root_not_after);
+    params.serial_number = Some(SerialNumber::from_slice(
+        &crate::cert::generate_serial_number(),
+    ));
+
+    params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+    params.key_usages = vec![
+        rcgen::KeyUsagePurpose::KeyCertSign,
+        rcgen::KeyUsagePurpose::CrlSign,
+    ];
+    params.use_authority_key_identifier_extension = true;
+
+    Ok(params)
+}
+
+/// Lock file in a CA directory, held while the CA is being created.
+///
+/// The file is created with `create_new`, so only one process can hold it;
+/// others poll until it is removed. It is removed again on drop.
+struct CaRootLock {
+    path: PathBuf,
+}
+
+impl CaRootLock {
+    /// Whether another process currently holds the lock on `root`.
+    fn is_held(root: &Path) -> bool {
+        root.join(LOCK_FILE).exists()
+    }
+
+    /// Take the lock on `root`, waiting while another process holds it.
+    ///
+    /// A lock left behind by a process that died is taken over once it is
+    /// older than [`LOCK_STALE_AFTER`].
+    fn acquire(root: &Path) -> Result<Self> {
+        let path = root.join(LOCK_FILE);
+        let mut waited = false;
+        loop {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    // The PID is only informational, for whoever finds a stuck lock
+                    let _ = writeln!(file, "{}", std::process::id());
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|meta| meta.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > LOCK_STALE_AFTER);
+                    if stale {
+                        crate::log_event("ca", &format!("Removing stale lock {:?}", path));
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if !waited {
+                        crate::log_event("ca", &format!("Waiting for lock {:?}", path));
+                        waited = true;
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(50));
+                }
+                Err(e) => {
+                    return Err(Error::Certificate(format!(
+                        "Failed to create lock file {:?}: {}",
+                        path, e
+                    )));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for CaRootLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Write a CA certificate (0644) and private key (0400) to disk.
+///
+/// Existing files are moved to backups first so anything they signed can
+/// still be recovered.
+fn write_ca_files(cert_path: &Path, cert_pem: &str, key_path: &Path, key_pem: &str) -> Result<()> {
+    for path in [cert_path, key_path] {
+        if let Some(backup) = crate::fileutil::backup_existing(path)? {
+            eprintln!(
+                "{} Backed up existing {:?} to {:?}",
+                "Note:".yellow(),
+                path,
+                backup
+            );
+        }
+    }
+
+    // Save certificate
+    let mut file = File::create(cert_path).map_err(|e| {
+        Error::Certificate(format!(
+            "Failed to create certificate file at {:?}: {}",
+            cert_path, e
+        ))
+    })?;
+    file.write_all(cert_pem.as_bytes()).map_err(|e| {
+        Error::Certificate(format!(
+            "Failed to write certificate to {:?}: {}",
+            cert_path, e
+        ))
+    })?;
+    #[cfg(unix)]
+    fs::set_permissions(cert_path, fs::Permissions::from_mode(0o644)).map_err(|e| {
+        Error::Certificate(format!(
+            "Failed to set permissions on {:?}: {}",
+            cert_path, e
+        ))
+    })?;
+
+    // Save private key
+    let mut file = File::create(key_path).map_err(|e| {
+        Error::Certificate(format!(
+            "Failed to create key file at {:?}: {}",
+            key_path, e
+        ))
+    })?;
+    file.write_all(key_pem.as_bytes())
+        .map_err(|e| Error::Certificate(format!("Failed to write key to {:?}: {}", key_path, e)))?;
+    #[cfg(unix)]
+    fs::set_permissions(key_path, fs::Permissions::from_mode(0o400)).map_err(|e| {
+        Error::Certificate(format!(
+            "Failed to set permissions on {:?}: {}",
+            key_path, e
+        ))
+    })?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -891,4 +2335,25 @@ mod tests {
         // Check that serial1 is unique against ca2's path
         assert!(is_serial_unique(&serial, temp_dir2.path()).unwrap());
     }
+
+    #[test]
+    fn test_random_uuid_format() {
+        let uuid = random_uuid();
+        let groups: Vec<&str> = uuid.split('-').collect();
+        assert_eq!(
+            groups.iter().map(|g| g.len()).collect::<Vec<_>>(),
+            vec![8, 4, 4, 4, 12]
+        );
+        assert!(groups[2].starts_with('4'));
+        assert_ne!(uuid, random_uuid());
+    }
+
+    #[test]
+    fn test_parse_hex_serial() {
+        assert_eq!(parse_hex_serial("0a1b").unwrap().as_ref(), &[0x0a, 0x1b]);
+        assert_eq!(parse_hex_serial("a:1b").unwrap().as_ref(), &[0x0a, 0x1b]);
+        assert_eq!(parse_hex_serial("abc").unwrap().as_ref(), &[0x0a, 0xbc]);
+        assert!(parse_hex_serial("xyz").is_err());
+        assert!(parse_hex_serial("").is_err());
+    }
 }
@@ -16,13 +16,16 @@ use crate::{Error, Result};
 use colored::*;
 use rcgen::string::Ia5String;
 use rcgen::{
-    CertificateParams, ExtendedKeyUsagePurpose, Issuer, KeyPair, KeyUsagePurpose,
-    PKCS_ECDSA_P256_SHA256, PKCS_RSA_SHA256, RsaKeySize, SanType,
+    CertificateParams, CrlDistributionPoint, CustomExtension, ExtendedKeyUsagePurpose, KeyPair,
+    KeyUsagePurpose, PKCS_ECDSA_P256_SHA256, PKCS_ECDSA_P384_SHA384, PKCS_ED25519, PKCS_RSA_SHA256,
+    PKCS_RSA_SHA384, PKCS_RSA_SHA512, RsaKeySize, SanType, SerialNumber,
 };
 use regex::Regex;
+use serde::Serialize;
 use std::fs;
 use std::net::IpAddr;
 use std::path::PathBuf;
+use time::format_description::well_known::Rfc3339;
 use time::{Duration, OffsetDateTime};
 
 #[cfg(unix)]
@@ -32,11 +35,22 @@ use std::os::unix::fs::PermissionsExt;
 ///
 /// Specifies all parameters needed to generate a certificate including
 /// the hosts it should be valid for and output file locations.
+///
+/// Construct one with [`CertificateConfig::new`] or
+/// [`CertificateConfig::builder`]; fields can then be read and changed
+/// directly. The struct is `#[non_exhaustive]` so that new options can be
+/// added without breaking callers, which means it cannot be built with a
+/// struct literal outside this crate.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct CertificateConfig {
     /// List of hostnames, IP addresses, emails, or URIs for the certificate
     pub hosts: Vec<String>,
     /// Use ECDSA instead of RSA for the key pair
     pub use_ecdsa: bool,
+    /// Without `use_ecdsa`, generate the key with the CA's key algorithm
+    /// instead of RSA-2048
+    pub inherit_ca_algorithm: bool,
     /// Generate a client authentication certificate
     pub client_cert: bool,
     /// Generate PKCS#12 bundle instead of PEM files
@@ -47,6 +61,47 @@ pub struct CertificateConfig {
     pub key_file: Option<PathBuf>,
     /// Custom path for PKCS#12 bundle output file
     pub p12_file: Option<PathBuf>,
+    /// OCSP responder URL for the Authority Information Access extension
+    pub ocsp_url: Option<String>,
+    /// CA Issuers URL for the Authority Information Access extension
+    pub issuer_url: Option<String>,
+    /// URL for the CRL Distribution Points extension
+    pub crl_url: Option<String>,
+    /// Encryption used for the PKCS#12 bundle
+    pub p12_encryption: P12Encryption,
+    /// Include the CA certificate in the PKCS#12 bundle (default: true)
+    pub p12_include_ca: bool,
+    /// Block order when `cert_file` and `key_file` are the same file
+    pub combined_order: CombinedOrder,
+    /// Encrypt the written private key with this passphrase (PEM output only)
+    pub key_passphrase: Option<String>,
+    /// How the CA signs the certificate
+    pub signature_algorithm: SignatureAlgorithm,
+    /// Hash for the CA's signature; `None` uses the usual one for the CA key
+    pub hash: Option<HashAlgorithm>,
+    /// Certificate lifetime in days (default: 820)
+    pub validity_days: Option<u32>,
+    /// How far notBefore is set in the past (default: [`default_backdate`])
+    pub backdate: Option<Duration>,
+    /// Exact notBefore, taking precedence over `backdate`
+    pub not_before: Option<OffsetDateTime>,
+    /// Exact notAfter, taking precedence over `validity_days`
+    pub not_after: Option<OffsetDateTime>,
+    /// Unix permissions for the private key or PKCS#12 file (default: 0600 / 0644)
+    pub key_mode: Option<u32>,
+    /// Unix permissions for the certificate file (default: 0644)
+    pub cert_mode: Option<u32>,
+    /// Explicitly typed SANs, used as-is instead of classifying `hosts`
+    ///
+    /// Set by [`CertificateConfig::from_host_types`]; `hosts` then only
+    /// provides file names and display text.
+    pub host_types: Option<Vec<HostType>>,
+    /// Put the single DNS host in the Common Name and leave out the
+    /// subjectAltName extension, for clients that predate SAN checking
+    pub omit_san: bool,
+    /// User Principal Name added as an `otherName` SAN, for smartcard-style
+    /// client authentication (client certificates only)
+    pub upn: Option<String>,
 }
 
 impl CertificateConfig {
@@ -63,15 +118,436 @@ impl CertificateConfig {
         Self {
             hosts,
             use_ecdsa: false,
+            inherit_ca_algorithm: false,
             client_cert: false,
             pkcs12: false,
             cert_file: None,
             key_file: None,
             p12_file: None,
+            ocsp_url: None,
+            issuer_url: None,
+            crl_url: None,
+            p12_encryption: P12Encryption::default(),
+            p12_include_ca: true,
+            combined_order: CombinedOrder::default(),
+            key_passphrase: None,
+            signature_algorithm: SignatureAlgorithm::default(),
+            hash: None,
+            validity_days: None,
+            backdate: None,
+            not_before: None,
+            not_after: None,
+            key_mode: None,
+            cert_mode: None,
+            host_types: None,
+            omit_san: false,
+            upn: None,
+        }
+    }
+
+    /// Create a configuration from explicitly typed SANs.
+    ///
+    /// Unlike [`CertificateConfig::new`], entries are not classified by
+    /// their format, so a caller can force e.g. `127.0.0.1` to be a DNS name
+    /// or any string to be a URI.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fastcert::cert::{CertificateConfig, HostType};
+    ///
+    /// let config = CertificateConfig::from_host_types(vec![
+    ///     HostType::DnsName("127.0.0.1".to_string()),
+    ///     HostType::Uri("spiffe://example/service".to_string()),
+    /// ]);
+    /// assert_eq!(config.hosts, vec!["127.0.0.1", "spiffe://example/service"]);
+    /// ```
+    pub fn from_host_types(host_types: Vec<HostType>) -> Self {
+        let mut config = Self::new(host_types.iter().map(HostType::to_string).collect());
+        config.host_types = Some(host_types);
+        config
+    }
+
+    /// Get the SAN entries for this configuration, classifying `hosts`
+    /// unless typed entries were given.
+    fn resolved_host_types(&self) -> Result<Vec<HostType>> {
+        match &self.host_types {
+            Some(host_types) => Ok(host_types.clone()),
+            None => expand_hosts(&self.hosts),
+        }
+    }
+
+    /// Start building a certificate configuration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fastcert::cert::CertificateConfig;
+    ///
+    /// let config = CertificateConfig::builder()
+    ///     .host("example.com")
+    ///     .host("127.0.0.1")
+    ///     .ecdsa()
+    ///     .validity_days(30)
+    ///     .build()?;
+    /// assert_eq!(config.hosts.len(), 2);
+    /// # Ok::<(), fastcert::Error>(())
+    /// ```
+    pub fn builder() -> CertificateConfigBuilder {
+        CertificateConfigBuilder {
+            config: Self::new(Vec::new()),
+        }
+    }
+}
+
+/// Fluent builder for [`CertificateConfig`].
+///
+/// Created by [`CertificateConfig::builder`]. Validation happens in `build()`.
+#[derive(Debug, Clone)]
+pub struct CertificateConfigBuilder {
+    config: CertificateConfig,
+}
+
+impl CertificateConfigBuilder {
+    /// Add a DNS name, IP address, email, or URI
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.config.hosts.push(host.into());
+        self
+    }
+
+    /// Add several hosts at once
+    pub fn hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.hosts.extend(hosts.into_iter().map(Into::into));
+        self
+    }
+
+    /// Use an ECDSA P-256 key instead of RSA-2048
+    pub fn ecdsa(mut self) -> Self {
+        self.config.use_ecdsa = true;
+        self
+    }
+
+    /// Match the key algorithm of the signing CA unless
+    /// [`ecdsa`](Self::ecdsa) is also set
+    pub fn inherit_ca_algorithm(mut self) -> Self {
+        self.config.inherit_ca_algorithm = true;
+        self
+    }
+
+    /// Generate a client authentication certificate
+    pub fn client(mut self) -> Self {
+        self.config.client_cert = true;
+        self
+    }
+
+    /// Generate a PKCS#12 bundle instead of PEM files
+    pub fn pkcs12(mut self) -> Self {
+        self.config.pkcs12 = true;
+        self
+    }
+
+    /// Set the certificate output path
+    pub fn cert_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.cert_file = Some(path.into());
+        self
+    }
+
+    /// Set the private key output path
+    pub fn key_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.key_file = Some(path.into());
+        self
+    }
+
+    /// Set the PKCS#12 output path (implies PKCS#12 mode)
+    pub fn p12_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.p12_file = Some(path.into());
+        self.config.pkcs12 = true;
+        self
+    }
+
+    /// Set the PKCS#12 encryption scheme
+    pub fn p12_encryption(mut self, encryption: P12Encryption) -> Self {
+        self.config.p12_encryption = encryption;
+        self
+    }
+
+    /// Set whether the PKCS#12 bundle includes the CA certificate
+    pub fn p12_include_ca(mut self, include: bool) -> Self {
+        self.config.p12_include_ca = include;
+        self
+    }
+
+    /// Set the block order of a combined certificate and key file
+    pub fn combined_order(mut self, order: CombinedOrder) -> Self {
+        self.config.combined_order = order;
+        self
+    }
+
+    /// Write the private key encrypted with a passphrase
+    pub fn key_passphrase(mut self, passphrase: impl Into<String>) -> Self {
+        self.config.key_passphrase = Some(passphrase.into());
+        self
+    }
+
+    /// Set how the CA signs the certificate
+    pub fn signature_algorithm(mut self, algorithm: SignatureAlgorithm) -> Self {
+        self.config.signature_algorithm = algorithm;
+        self
+    }
+
+    /// Set the hash the CA signs the certificate with
+    pub fn hash(mut self, hash: HashAlgorithm) -> Self {
+        self.config.hash = Some(hash);
+        self
+    }
+
+    /// Set the certificate lifetime in days
+    pub fn validity_days(mut self, days: u32) -> Self {
+        self.config.validity_days = Some(days);
+        self
+    }
+
+    /// Set how far in the past notBefore is placed, to tolerate clients
+    /// whose clock runs behind
+    pub fn backdate(mut self, backdate: Duration) -> Self {
+        self.config.backdate = Some(backdate);
+        self
+    }
+
+    /// Set the exact start of the validity period, overriding `backdate`
+    pub fn not_before(mut self, not_before: OffsetDateTime) -> Self {
+        self.config.not_before = Some(not_before);
+        self
+    }
+
+    /// Set the exact end of the validity period, overriding `validity_days`
+    ///
+    /// A date in the past gives an already-expired certificate, for testing
+    /// how clients reject one.
+    pub fn not_after(mut self, not_after: OffsetDateTime) -> Self {
+        self.config.not_after = Some(not_after);
+        self
+    }
+
+    /// Set the Unix permissions of the private key file (e.g. `0o640`)
+    pub fn key_mode(mut self, mode: u32) -> Self {
+        self.config.key_mode = Some(mode);
+        self
+    }
+
+    /// Set the Unix permissions of the certificate file
+    pub fn cert_mode(mut self, mode: u32) -> Self {
+        self.config.cert_mode = Some(mode);
+        self
+    }
+
+    /// Use the host as the Common Name and omit the subjectAltName extension
+    ///
+    /// Only valid with exactly one DNS host.
+    pub fn omit_san(mut self) -> Self {
+        self.config.omit_san = true;
+        self
+    }
+
+    /// Add a User Principal Name (`user@domain`) to a client certificate
+    pub fn upn(mut self, upn: impl Into<String>) -> Self {
+        self.config.upn = Some(upn.into());
+        self
+    }
+
+    /// Set the OCSP responder URL
+    pub fn ocsp_url(mut self, url: impl Into<String>) -> Self {
+        self.config.ocsp_url = Some(url.into());
+        self
+    }
+
+    /// Set the CA Issuers URL
+    pub fn issuer_url(mut self, url: impl Into<String>) -> Self {
+        self.config.issuer_url = Some(url.into());
+        self
+    }
+
+    /// Set the CRL distribution point URL
+    pub fn crl_url(mut self, url: impl Into<String>) -> Self {
+        self.config.crl_url = Some(url.into());
+        self
+    }
+
+    /// Validate and return the configuration
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no hosts were added, the validity is zero days,
+    /// `not_before` is not before `not_after`, a file mode is invalid (see
+    /// [`validate_file_modes`]), `omit_san`
+    /// is set without exactly one DNS host, or the UPN is invalid or set on
+    /// a non-client certificate.
+    pub fn build(self) -> Result<CertificateConfig> {
+        if self.config.hosts.is_empty() {
+            return Err(Error::Certificate(
+                "No hosts specified. Use .host() to add hosts.".to_string(),
+            ));
+        }
+        if self.config.validity_days == Some(0) {
+            return Err(Error::Certificate(
+                "Certificate validity must be at least one day".to_string(),
+            ));
+        }
+        if let (Some(not_before), Some(not_after)) = (self.config.not_before, self.config.not_after)
+        {
+            validate_validity_window(not_before, not_after)?;
         }
+        validate_file_modes(&self.config)?;
+        if self.config.omit_san {
+            validate_omit_san(&self.config.resolved_host_types()?)?;
+        }
+        validate_upn_config(&self.config)?;
+        Ok(self.config)
     }
 }
 
+/// Details of a freshly issued certificate.
+///
+/// Returned by the generation functions so callers can locate the written
+/// files without re-deriving their names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerationResult {
+    /// Path of the certificate (PEM), or of the PKCS#12 bundle in PKCS#12 mode
+    pub cert_path: PathBuf,
+    /// Path of the private key, if one was written
+    pub key_path: Option<PathBuf>,
+    /// Path of the PKCS#12 bundle, if one was written
+    pub p12_path: Option<PathBuf>,
+    /// Hosts the certificate is valid for
+    pub hosts: Vec<String>,
+    /// Certificate serial number, uppercase hex as printed by
+    /// `openssl x509 -serial`
+    pub serial: String,
+    /// Expiration date of the certificate
+    pub not_after: OffsetDateTime,
+    /// SHA-256 fingerprint of the certificate, colon-separated hex
+    pub fingerprint: String,
+    /// How long each issuance step took, when `FASTCERT_TIMINGS` is set
+    pub timings: Option<Timings>,
+}
+
+/// Wall-clock durations of the steps of issuing a certificate.
+///
+/// Only measured locally, for callers that want to record them; nothing is
+/// sent anywhere.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Loading (or creating) the CA; zero when the caller already had it
+    pub ca_load: std::time::Duration,
+    /// Generating the certificate's key pair
+    pub key_gen: std::time::Duration,
+    /// Building and signing the certificate
+    pub sign: std::time::Duration,
+    /// Writing the certificate and key files
+    pub write: std::time::Duration,
+}
+
+/// Check whether issuance timings are enabled via `FASTCERT_TIMINGS`
+pub fn timings_enabled() -> bool {
+    crate::env_flag("FASTCERT_TIMINGS")
+}
+
+/// Subject attributes for [`generate_csr`].
+///
+/// These are the attributes [`sign_csr`] copies into the certificate it
+/// issues.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubjectFields {
+    /// Common Name (CN)
+    pub common_name: Option<String>,
+    /// Organization (O)
+    pub organization: Option<String>,
+    /// Organizational Unit (OU)
+    pub organizational_unit: Option<String>,
+}
+
+/// A freshly generated key pair, from [`generate_key_pair`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyPairPem {
+    /// Private key as an unencrypted PKCS#8 `PRIVATE KEY` PEM
+    pub private_pem: String,
+    /// Public key as a `PUBLIC KEY` (SubjectPublicKeyInfo) PEM
+    pub public_pem: String,
+}
+
+/// Files deleted by [`remove_cert`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemovedFiles {
+    /// Removed paths, the given certificate first
+    pub removed: Vec<PathBuf>,
+}
+
+/// Result of [`renew_if_expiring`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenewOutcome {
+    /// The certificate was close to expiry and has been re-issued
+    Renewed(Box<GenerationResult>),
+    /// The certificate is valid for longer than the threshold and was left
+    /// alone
+    Skipped {
+        /// Whole days until the existing certificate expires
+        days_remaining: i64,
+    },
+}
+
+/// Summary of an existing certificate.
+///
+/// Produced by [`inspect_certificate`] from a PEM-encoded certificate.
+/// Serializes with the field names given in each field's doc comment;
+/// [`inspect_certificate_json`] emits that form directly.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CertInfo {
+    /// Subject distinguished name (`subject`)
+    pub subject: String,
+    /// Issuer distinguished name (`issuer`)
+    pub issuer: String,
+    /// Serial number as uppercase hex, as printed by `openssl x509 -serial`
+    /// (`serial`)
+    pub serial: String,
+    /// Start of the validity period, RFC 3339 (`not_before`)
+    #[serde(serialize_with = "serialize_rfc3339")]
+    pub not_before: OffsetDateTime,
+    /// End of the validity period, RFC 3339 (`not_after`)
+    #[serde(serialize_with = "serialize_rfc3339")]
+    pub not_after: OffsetDateTime,
+    /// DNS names from the SAN extension (`san_dns`)
+    #[serde(rename = "san_dns")]
+    pub dns_names: Vec<String>,
+    /// IP addresses from the SAN extension, canonical form (`san_ip`)
+    #[serde(rename = "san_ip")]
+    pub ip_addresses: Vec<IpAddr>,
+    /// Email addresses from the SAN extension (`san_email`)
+    #[serde(rename = "san_email")]
+    pub emails: Vec<String>,
+    /// URIs from the SAN extension (`san_uri`)
+    #[serde(rename = "san_uri")]
+    pub uris: Vec<String>,
+    /// Whether the certificate is a CA (basicConstraints CA:TRUE) (`is_ca`)
+    pub is_ca: bool,
+    /// Subject Key Identifier (hex), if present (`subject_key_id`)
+    pub subject_key_id: Option<String>,
+    /// Key identifier from the Authority Key Identifier (hex), if present
+    /// (`authority_key_id`)
+    pub authority_key_id: Option<String>,
+}
+
+fn serialize_rfc3339<S: serde::Serializer>(
+    value: &OffsetDateTime,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    let formatted = value.format(&Rfc3339).map_err(serde::ser::Error::custom)?;
+    serializer.serialize_str(&formatted)
+}
+
 /// Type of host identifier in a certificate.
 ///
 /// Represents the different types of subject alternative names
@@ -98,6 +574,160 @@ pub enum KeyType {
     ECDSA,
 }
 
+/// Encryption scheme for PKCS#12 bundles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum P12Encryption {
+    /// RC2-40 for certificates and 3DES for the key, with a SHA-1 MAC.
+    /// Needed by older Java and Windows; OpenSSL 3 requires `-legacy`.
+    Legacy,
+    /// PBES2 with PBKDF2-HMAC-SHA256 and AES-256-CBC (default)
+    #[default]
+    Modern,
+}
+
+/// Block order in a combined certificate and key file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CombinedOrder {
+    /// Certificate chain, then private key, as nginx accepts (default)
+    #[default]
+    CertFirst,
+    /// Private key, then certificate chain, as HAProxy expects
+    KeyFirst,
+}
+
+/// Signature scheme a CA uses to sign certificates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignatureAlgorithm {
+    /// The usual scheme for the CA key: PKCS#1 v1.5 with SHA-256 for RSA,
+    /// ECDSA or Ed25519 otherwise (default)
+    #[default]
+    Standard,
+    /// RSASSA-PSS with SHA-256, MGF1-SHA-256 and a 32-byte salt. Needs an
+    /// RSA CA key.
+    RsaPss,
+}
+
+impl std::str::FromStr for SignatureAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "standard" => Ok(Self::Standard),
+            "rsa-pss" | "pss" => Ok(Self::RsaPss),
+            _ => Err(Error::Certificate(format!(
+                "Unknown signature algorithm '{}'. Use \"standard\" or \"rsa-pss\"",
+                s
+            ))),
+        }
+    }
+}
+
+/// Hash function used in the CA's signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashAlgorithm {
+    /// SHA-256 (default)
+    #[default]
+    Sha256,
+    /// SHA-384
+    Sha384,
+    /// SHA-512
+    Sha512,
+}
+
+impl std::str::FromStr for HashAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "").as_str() {
+            "sha256" => Ok(Self::Sha256),
+            "sha384" => Ok(Self::Sha384),
+            "sha512" => Ok(Self::Sha512),
+            _ => Err(Error::Certificate(format!(
+                "Unknown hash algorithm '{}'. Use \"sha256\", \"sha384\" or \"sha512\"",
+                s
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Sha256 => "SHA-256",
+            Self::Sha384 => "SHA-384",
+            Self::Sha512 => "SHA-512",
+        })
+    }
+}
+
+/// Encoding of a private key, for [`convert_key`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyFormat {
+    /// PKCS#8 `PRIVATE KEY` PEM, as fastcert writes keys (default)
+    #[default]
+    Pkcs8Pem,
+    /// PKCS#8 DER
+    Pkcs8Der,
+    /// PKCS#1 `RSA PRIVATE KEY` or SEC1 `EC PRIVATE KEY` PEM, for tools
+    /// that predate PKCS#8
+    TraditionalPem,
+    /// PKCS#1 or SEC1 DER
+    TraditionalDer,
+}
+
+/// Key algorithm for generating key pairs
+///
+/// Covers the algorithms supported for CA keys. Parsed from strings such as
+/// `rsa3072`, `ecdsa-p384` or `ed25519`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAlgorithm {
+    /// RSA 2048-bit key
+    Rsa2048,
+    /// RSA 3072-bit key
+    Rsa3072,
+    /// ECDSA key on the P-256 curve
+    EcdsaP256,
+    /// ECDSA key on the P-384 curve
+    EcdsaP384,
+    /// Ed25519 key
+    Ed25519,
+}
+
+impl KeyAlgorithm {
+    /// Generate a new key pair for this algorithm
+    pub fn generate(&self) -> Result<KeyPair> {
+        let key_pair = match self {
+            Self::Rsa2048 => KeyPair::generate_rsa_for(&PKCS_RSA_SHA256, RsaKeySize::_2048),
+            Self::Rsa3072 => KeyPair::generate_rsa_for(&PKCS_RSA_SHA256, RsaKeySize::_3072),
+            Self::EcdsaP256 => KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256),
+            Self::EcdsaP384 => KeyPair::generate_for(&PKCS_ECDSA_P384_SHA384),
+            Self::Ed25519 => KeyPair::generate_for(&PKCS_ED25519),
+        };
+
+        key_pair.map_err(|e| {
+            Error::Certificate(format!("Failed to generate {:?} key pair: {}", self, e))
+        })
+    }
+}
+
+impl std::str::FromStr for KeyAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "rsa2048" | "rsa-2048" => Ok(Self::Rsa2048),
+            "rsa" | "rsa3072" | "rsa-3072" => Ok(Self::Rsa3072),
+            "ecdsa" | "p256" | "ecdsa-p256" => Ok(Self::EcdsaP256),
+            "p384" | "ecdsa-p384" => Ok(Self::EcdsaP384),
+            "ed25519" => Ok(Self::Ed25519),
+            _ => Err(Error::Certificate(format!(
+                "Unsupported key algorithm: {}",
+                s
+            ))),
+        }
+    }
+}
+
 /// Builder for certificate generation
 ///
 /// Created by calling `CA::issue_certificate()`. Configure the certificate
@@ -117,26 +747,46 @@ pub enum KeyType {
 pub struct CertificateBuilder {
     ca_cert_pem: String,
     ca_key_pem: String,
+    caroot: PathBuf,
     domains: Vec<String>,
     key_type: KeyType,
     client_cert: bool,
     cert_file: Option<String>,
     key_file: Option<String>,
     p12_file: Option<String>,
+    ocsp_url: Option<String>,
+    issuer_url: Option<String>,
+    crl_url: Option<String>,
+    p12_encryption: P12Encryption,
+    p12_include_ca: bool,
+    combined_order: CombinedOrder,
+    signature_algorithm: SignatureAlgorithm,
+    hash: Option<HashAlgorithm>,
+    omit_san: bool,
 }
 
 impl CertificateBuilder {
     /// Create a new certificate builder (internal use only)
-    pub(crate) fn new(ca_cert_pem: String, ca_key_pem: String) -> Self {
+    pub(crate) fn new(ca_cert_pem: String, ca_key_pem: String, caroot: PathBuf) -> Self {
         Self {
             ca_cert_pem,
             ca_key_pem,
+            caroot,
             domains: Vec::new(),
             key_type: KeyType::default(),
             client_cert: false,
             cert_file: None,
             key_file: None,
             p12_file: None,
+            ocsp_url: None,
+            issuer_url: None,
+            crl_url: None,
+            p12_encryption: P12Encryption::default(),
+            p12_include_ca: true,
+            combined_order: CombinedOrder::default(),
+            signature_algorithm: SignatureAlgorithm::default(),
+            hash: None,
+            omit_san: false,
         }
     }
 
@@ -223,11 +873,88 @@ impl CertificateBuilder {
         self
     }
 
+    /// Set the PKCS#12 encryption scheme (default: Modern)
+    ///
+    /// Use `P12Encryption::Legacy` for consumers that cannot read AES-encrypted bundles.
+    pub fn p12_encryption(mut self, encryption: P12Encryption) -> Self {
+        self.p12_encryption = encryption;
+        self
+    }
+
+    /// Set whether the PKCS#12 bundle includes the CA certificate (default: true)
+    ///
+    /// Some importers expect a bundle holding only the leaf and its key.
+    pub fn p12_include_ca(mut self, include: bool) -> Self {
+        self.p12_include_ca = include;
+        self
+    }
+
+    /// Set the OCSP responder URL (default: none)
+    ///
+    /// Adds an Authority Information Access extension pointing at the responder.
+    pub fn ocsp_url(mut self, url: impl Into<String>) -> Self {
+        self.ocsp_url = Some(url.into());
+        self
+    }
+
+    /// Set the CA Issuers URL (default: none)
+    ///
+    /// Adds an Authority Information Access extension pointing at the CA certificate.
+    pub fn issuer_url(mut self, url: impl Into<String>) -> Self {
+        self.issuer_url = Some(url.into());
+        self
+    }
+
+    /// Set the CRL distribution point URL (default: none)
+    ///
+    /// Adds a CRL Distribution Points extension so clients know where to fetch the CRL.
+    pub fn crl_url(mut self, url: impl Into<String>) -> Self {
+        self.crl_url = Some(url.into());
+        self
+    }
+
+    /// Set the block order when cert and key share a file (default: CertFirst)
+    ///
+    /// Use `CombinedOrder::KeyFirst` for HAProxy, which wants the private
+    /// key before the certificate.
+    pub fn combined_order(mut self, order: CombinedOrder) -> Self {
+        self.combined_order = order;
+        self
+    }
+
+    /// Set how the CA signs the certificate (default: Standard)
+    ///
+    /// `SignatureAlgorithm::RsaPss` needs an RSA CA key.
+    pub fn signature_algorithm(mut self, algorithm: SignatureAlgorithm) -> Self {
+        self.signature_algorithm = algorithm;
+        self
+    }
+
+    /// Set the hash the CA signs the certificate with (default: the usual
+    /// one for the CA key)
+    ///
+    /// RSA CA keys support every hash; ECDSA P-256 keys only SHA-256 and
+    /// P-384 keys only SHA-384.
+    pub fn hash(mut self, hash: HashAlgorithm) -> Self {
+        self.hash = Some(hash);
+        self
+    }
+
+    /// Leave out the subjectAltName extension (default: false)
+    ///
+    /// The single DNS domain is put in the Common Name instead, for testing
+    /// clients that predate SAN enforcement. Fails at build time with more
+    /// than one domain or a non-DNS domain.
+    pub fn omit_san(mut self, omit: bool) -> Self {
+        self.omit_san = omit;
+        self
+    }
+
     /// Generate the certificate and write to disk
     ///
     /// # Returns
     ///
-    /// `Ok(())` on success.
+    /// A `GenerationResult` describing the issued certificate and written files.
     ///
     /// # Errors
     ///
@@ -236,7 +963,7 @@ impl CertificateBuilder {
     /// - Domain validation fails
     /// - Certificate generation fails
     /// - File writing fails
-    pub fn build(self) -> Result<()> {
+    pub fn build(self) -> Result<GenerationResult> {
         if self.domains.is_empty() {
             return Err(Error::Certificate(
                 "No domains specified. Use .domains() to set domains.".to_string(),
@@ -251,9 +978,22 @@ impl CertificateBuilder {
         config.cert_file = self.cert_file.map(PathBuf::from);
         config.key_file = self.key_file.map(PathBuf::from);
         config.p12_file = self.p12_file.map(PathBuf::from);
+        config.ocsp_url = self.ocsp_url;
+        config.issuer_url = self.issuer_url;
+        config.crl_url = self.crl_url;
+        config.p12_encryption = self.p12_encryption;
+        config.p12_include_ca = self.p12_include_ca;
+        config.combined_order = self.combined_order;
+        config.signature_algorithm = self.signature_algorithm;
+        config.hash = self.hash;
+        config.omit_san = self.omit_san;
 
         // Call internal generation function
-        generate_certificate_internal(&config, &self.ca_cert_pem, &self.ca_key_pem)
+        let serial = crate::ca::next_leaf_serial(&self.caroot)?;
+        let result =
+            generate_certificate_internal(&config, &self.ca_cert_pem, &self.ca_key_pem, serial)?;
+        crate::audit::record(&self.caroot, &result)?;
+        Ok(result)
     }
 }
 
@@ -261,9 +1001,11 @@ impl HostType {
     /// Parse a host string into the appropriate HostType.
     ///
     /// Automatically detects the type based on the string format:
-    /// - IP addresses are parsed as `IpAddress`
-    /// - Strings with '@' are parsed as `Email`
-    /// - Strings with '://' are parsed as `Uri`
+    /// - IP addresses are parsed as `IpAddress`; an IPv6 zone id
+    ///   (`fe80::1%eth0`) is stripped since it has no meaning in a certificate
+    /// - Strings with '://' are parsed as `Uri`, even with user info (`@`)
+    /// - Other strings with '@' are parsed as `Email`; see
+    ///   [`validate_email_address`] for which ones are accepted
     /// - Everything else defaults to `DnsName`
     ///
     /// # Arguments
@@ -276,7 +1018,9 @@ impl HostType {
     ///
     /// # Errors
     ///
-    /// Returns an error if the host string is invalid for its detected type.
+    /// Returns an error if the host string is invalid for its detected type,
+    /// or if it is a CIDR range: a SAN holds a single address, so each
+    /// address must be listed individually.
     pub fn parse(host: &str) -> Result<Self> {
         // Try IP address
         if let Ok(ip) = host.parse::<IpAddr>() {
@@ -284,33 +1028,197 @@ impl HostType {
             return Ok(HostType::IpAddress(ip));
         }
 
-        // Try email (simple check)
-        if host.contains('@') {
-            validate_email_address(host)?;
-            return Ok(HostType::Email(host.to_string()));
+        // Try IPv6 with a zone id (e.g. fe80::1%eth0)
+        if let Some((addr, _zone)) = host.split_once('%')
+            && let Ok(ipv6) = addr.parse::<std::net::Ipv6Addr>()
+        {
+            let ip = IpAddr::V6(ipv6);
+            validate_ip_address(&ip)?;
+            return Ok(HostType::IpAddress(ip));
+        }
+
+        // Reject CIDR ranges, which can't be expressed as a single SAN
+        if let Some((addr, _prefix)) = host.split_once('/')
+            && addr
+                .split_once('%')
+                .map_or(addr, |(a, _)| a)
+                .parse::<IpAddr>()
+                .is_ok()
+        {
+            return Err(Error::InvalidHostname(format!(
+                "CIDR ranges are not supported: {}. List each IP address individually or use {}START-END.",
+                host, IP_RANGE_PREFIX
+            )));
+        }
+
+        // Ranges are expanded by expand_hosts, never parsed as one host
+        if host.starts_with(IP_RANGE_PREFIX) {
+            return Err(Error::InvalidHostname(format!(
+                "IP range {} stands for several hosts; expand it with expand_hosts",
+                host
+            )));
         }
 
-        // Try URI (has scheme)
+        // Try URI (has scheme), before email since user info contains '@'
         if host.contains("://") {
             validate_uri(host)?;
             return Ok(HostType::Uri(host.to_string()));
         }
 
+        // Try email
+        if host.contains('@') {
+            validate_email_address(host)?;
+            return Ok(HostType::Email(host.to_string()));
+        }
+
         // Default to DNS name
         Ok(HostType::DnsName(host.to_string()))
     }
 }
 
-/// Validate IP address (comprehensive checks for IPv4 and IPv6)
-pub fn validate_ip_address(ip: &IpAddr) -> Result<()> {
-    match ip {
-        IpAddr::V4(ipv4) => {
-            // Allow all valid IPv4 addresses for development
-            // Just ensure it's not unspecified
-            if ipv4.is_unspecified() {
-                return Err(Error::InvalidHostname(format!(
-                    "Unspecified IP address not allowed: {}",
-                    ip
+impl std::fmt::Display for HostType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HostType::DnsName(name) => f.write_str(name),
+            HostType::IpAddress(ip) => write!(f, "{}", ip),
+            HostType::Email(email) => f.write_str(email),
+            HostType::Uri(uri) => f.write_str(uri),
+        }
+    }
+}
+
+/// Whether `host` is a wildcard DNS name such as `*.example.com`.
+///
+/// This and the other host predicates classify hosts the same way as
+/// [`HostType::parse`], and are `false` for hosts it rejects.
+pub fn is_wildcard(host: &str) -> bool {
+    matches!(HostType::parse(host), Ok(HostType::DnsName(name)) if name.starts_with("*."))
+}
+
+/// Whether `host` is an IP address, including IPv6 with a zone id.
+pub fn is_ip(host: &str) -> bool {
+    matches!(HostType::parse(host), Ok(HostType::IpAddress(_)))
+}
+
+/// Whether `host` is an email address.
+pub fn is_email(host: &str) -> bool {
+    matches!(HostType::parse(host), Ok(HostType::Email(_)))
+}
+
+/// Whether `host` is a URI such as `spiffe://cluster.local/ns/default`.
+pub fn is_uri(host: &str) -> bool {
+    matches!(HostType::parse(host), Ok(HostType::Uri(_)))
+}
+
+/// Host list prefix for a range of IP addresses, as in
+/// `ip-range:10.0.0.1-10.0.0.10`
+pub const IP_RANGE_PREFIX: &str = "ip-range:";
+
+/// Most SANs a single certificate may carry
+pub const MAX_SAN_COUNT: usize = 1024;
+
+/// List every IP address from `start` to `end`, inclusive.
+///
+/// # Errors
+///
+/// Returns an error if the addresses are of different families, `end`
+/// comes before `start`, the range holds more than [`MAX_SAN_COUNT`]
+/// addresses, or it includes the unspecified address.
+pub fn expand_ip_range(start: IpAddr, end: IpAddr) -> Result<Vec<IpAddr>> {
+    let (first, last) = match (start, end) {
+        (IpAddr::V4(start), IpAddr::V4(end)) => {
+            (u128::from(u32::from(start)), u128::from(u32::from(end)))
+        }
+        (IpAddr::V6(start), IpAddr::V6(end)) => (u128::from(start), u128::from(end)),
+        _ => {
+            return Err(Error::InvalidHostname(format!(
+                "IP range mixes IPv4 and IPv6: {}-{}",
+                start, end
+            )));
+        }
+    };
+    if last < first {
+        return Err(Error::InvalidHostname(format!(
+            "IP range ends before it starts: {}-{}",
+            start, end
+        )));
+    }
+    if last - first >= MAX_SAN_COUNT as u128 {
+        return Err(Error::InvalidHostname(format!(
+            "IP range {}-{} has more than {} addresses",
+            start, end, MAX_SAN_COUNT
+        )));
+    }
+
+    (first..=last)
+        .map(|n| {
+            let ip = match start {
+                IpAddr::V4(_) => IpAddr::V4(std::net::Ipv4Addr::from(n as u32)),
+                IpAddr::V6(_) => IpAddr::V6(std::net::Ipv6Addr::from(n)),
+            };
+            validate_ip_address(&ip)?;
+            Ok(ip)
+        })
+        .collect()
+}
+
+/// Parse a host entry, expanding an [`IP_RANGE_PREFIX`] range into one
+/// host per address.
+fn expand_host(host: &str) -> Result<Vec<HostType>> {
+    let Some(range) = host.strip_prefix(IP_RANGE_PREFIX) else {
+        return Ok(vec![HostType::parse(host)?]);
+    };
+
+    let invalid = || {
+        Error::InvalidHostname(format!(
+            "Invalid IP range: {}. Expected {}START-END",
+            host, IP_RANGE_PREFIX
+        ))
+    };
+    let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+    let start = start.trim().parse().map_err(|_| invalid())?;
+    let end = end.trim().parse().map_err(|_| invalid())?;
+    Ok(expand_ip_range(start, end)?
+        .into_iter()
+        .map(HostType::IpAddress)
+        .collect())
+}
+
+/// Parse a host list, expanding IP ranges such as
+/// `ip-range:10.0.0.1-10.0.0.10` into individual addresses.
+///
+/// # Errors
+///
+/// Returns an error for the first entry that is not a valid host or range.
+pub fn expand_hosts(hosts: &[String]) -> Result<Vec<HostType>> {
+    let mut host_types = Vec::with_capacity(hosts.len());
+    for host in hosts {
+        host_types.extend(expand_host(host)?);
+    }
+    Ok(host_types)
+}
+
+/// Check a SAN list against [`MAX_SAN_COUNT`]
+fn check_san_count(count: usize) -> Result<()> {
+    if count > MAX_SAN_COUNT {
+        return Err(Error::Certificate(format!(
+            "Too many subject alternative names: {} (at most {})",
+            count, MAX_SAN_COUNT
+        )));
+    }
+    Ok(())
+}
+
+/// Validate IP address (comprehensive checks for IPv4 and IPv6)
+pub fn validate_ip_address(ip: &IpAddr) -> Result<()> {
+    match ip {
+        IpAddr::V4(ipv4) => {
+            // Allow all valid IPv4 addresses for development
+            // Just ensure it's not unspecified
+            if ipv4.is_unspecified() {
+                return Err(Error::InvalidHostname(format!(
+                    "Unspecified IP address not allowed: {}",
+                    ip
                 )));
             }
 
@@ -331,6 +1239,13 @@ pub fn validate_ip_address(ip: &IpAddr) -> Result<()> {
 }
 
 /// Validate email address using regex
+///
+/// Beyond the syntax check, the domain must have at least two labels and a
+/// non-numeric top-level label, so `1@2` and `1.2.3.4@5.6.7.8` are rejected
+/// rather than treated as mailboxes. `localhost` is the one single-label
+/// domain accepted (`user@localhost`), for local development; other bare
+/// host names such as `user@host` are rejected. A local part that is itself
+/// an IP address is rejected too.
 pub fn validate_email_address(email: &str) -> Result<()> {
     // RFC 5322 compliant email validation (simplified)
     let email_regex = Regex::new(
@@ -338,10 +1253,16 @@ pub fn validate_email_address(email: &str) -> Result<()> {
     ).unwrap();
 
     if !email_regex.is_match(email) {
-        return Err(Error::InvalidHostname(format!(
-            "Invalid email address: {}",
-            email
-        )));
+        return Err(Error::InvalidEmail(email.to_string()));
+    }
+
+    let (local, domain) = email
+        .rsplit_once('@')
+        .ok_or_else(|| Error::InvalidEmail(email.to_string()))?;
+    let tld = domain.rsplit('.').next().unwrap_or(domain);
+    let single_label = !domain.contains('.') && !domain.eq_ignore_ascii_case("localhost");
+    if single_label || tld.bytes().all(|b| b.is_ascii_digit()) || local.parse::<IpAddr>().is_ok() {
+        return Err(Error::InvalidEmail(email.to_string()));
     }
 
     Ok(())
@@ -353,28 +1274,32 @@ pub fn validate_uri(uri: &str) -> Result<()> {
     let uri_regex = Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*://[^\s]+$").unwrap();
 
     if !uri_regex.is_match(uri) {
-        return Err(Error::InvalidHostname(format!(
-            "Invalid URI format: {}",
-            uri
-        )));
+        return Err(Error::InvalidUri(
+            uri.to_string(),
+            "expected scheme://location with no whitespace".to_string(),
+        ));
     }
 
     // Ensure scheme is valid
     if let Some(scheme_end) = uri.find("://") {
         let scheme = &uri[..scheme_end];
         if scheme.is_empty() {
-            return Err(Error::InvalidHostname(format!(
-                "URI must have a scheme: {}",
-                uri
-            )));
+            return Err(Error::InvalidUri(
+                uri.to_string(),
+                "missing scheme".to_string(),
+            ));
         }
     }
 
     Ok(())
 }
 
+/// Validate hostname format
+///
+/// Every label must be non-empty, so leading, trailing and consecutive dots
+/// are rejected. Underscores and hyphens are allowed within labels.
 pub fn validate_hostname(hostname: &str) -> Result<()> {
-    let hostname_regex = Regex::new(r"(?i)^(\*\.)?[0-9a-z_-]([0-9a-z._-]*[0-9a-z_-])?$").unwrap();
+    let hostname_regex = Regex::new(r"(?i)^(\*\.)?[0-9a-z_-]+(\.[0-9a-z_-]+)*$").unwrap();
 
     if !hostname_regex.is_match(hostname) {
         return Err(Error::InvalidHostname(hostname.to_string()));
@@ -418,9 +1343,44 @@ pub fn format_expiration_date(expiration: OffsetDateTime) -> String {
         .unwrap_or_else(|_| format!("{}", expiration))
 }
 
+/// Format certificate expiration relative to now, followed by the date.
+///
+/// Gives the two largest units, e.g. `expires in 2 years, 3 months
+/// (2027-01-15)` or `expired 5 days ago (2026-10-12)`. Months count as 30
+/// days and years as 365.
+pub fn format_expiry_relative(expiration: OffsetDateTime) -> String {
+    let remaining = expiration - OffsetDateTime::now_utc();
+    let days = remaining.whole_days().unsigned_abs();
+    let units = [
+        (days / 365, "year"),
+        ((days % 365) / 30, "month"),
+        ((days % 365) % 30, "day"),
+    ];
+    let parts: Vec<String> = units
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .take(2)
+        .map(|(count, unit)| format!("{} {}{}", count, unit, if *count == 1 { "" } else { "s" }))
+        .collect();
+    let span = if parts.is_empty() {
+        "less than a day".to_string()
+    } else {
+        parts.join(", ")
+    };
+
+    let date = expiration
+        .format(time::macros::format_description!("[year]-[month]-[day]"))
+        .unwrap_or_else(|_| format_expiration_date(expiration));
+    if remaining.is_negative() {
+        format!("expired {} ago ({})", span, date)
+    } else {
+        format!("expires in {} ({})", span, date)
+    }
+}
+
 /// Calculate certificate expiration date (2 years and 3 months from now)
 pub fn calculate_cert_expiration() -> OffsetDateTime {
-    OffsetDateTime::now_utc() + Duration::days(730 + 90)
+    OffsetDateTime::now_utc() + Duration::days(i64::from(DEFAULT_VALIDITY_DAYS))
 }
 
 /// Check if certificate is expiring soon (within 30 days)
@@ -430,6 +1390,442 @@ pub fn is_cert_expiring_soon(expiration: OffsetDateTime) -> bool {
     (0..=30).contains(&days_until_expiry)
 }
 
+/// Inspect a PEM-encoded certificate
+///
+/// # Arguments
+///
+/// * `pem` - The certificate in PEM format
+///
+/// # Returns
+///
+/// A `CertInfo` describing the certificate.
+///
+/// # Errors
+///
+/// Returns an error if the PEM or the certificate cannot be parsed.
+pub fn inspect_certificate(pem: &[u8]) -> Result<CertInfo> {
+    use x509_parser::extensions::ParsedExtension;
+
+    let cert_der = parse_pem_cert(pem)?;
+    let (_, cert) = x509_parser::parse_x509_certificate(&cert_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?;
+
+    let mut info = CertInfo {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        serial: format_serial(cert.raw_serial()),
+        not_before: cert.validity().not_before.to_datetime(),
+        not_after: cert.validity().not_after.to_datetime(),
+        dns_names: Vec::new(),
+        ip_addresses: Vec::new(),
+        emails: Vec::new(),
+        uris: Vec::new(),
+        is_ca: false,
+        subject_key_id: None,
+        authority_key_id: None,
+    };
+
+    for san in cert_sans(&cert_der)? {
+        match san {
+            HostType::DnsName(dns) => info.dns_names.push(dns),
+            HostType::IpAddress(ip) => info.ip_addresses.push(ip),
+            HostType::Email(email) => info.emails.push(email),
+            HostType::Uri(uri) => info.uris.push(uri),
+        }
+    }
+
+    for ext in cert.extensions() {
+        match ext.parsed_extension() {
+            ParsedExtension::BasicConstraints(bc) => info.is_ca = bc.ca,
+            ParsedExtension::SubjectKeyIdentifier(ski) => {
+                info.subject_key_id = Some(hex::encode(ski.0));
+            }
+            ParsedExtension::AuthorityKeyIdentifier(aki) => {
+                info.authority_key_id = aki.key_identifier.as_ref().map(|id| hex::encode(id.0));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(info)
+}
+
+/// Inspect a PEM-encoded certificate and render the result as JSON
+///
+/// # Arguments
+///
+/// * `pem` - The certificate in PEM format
+///
+/// # Returns
+///
+/// The serialized [`CertInfo`] as a pretty-printed JSON object.
+///
+/// # Errors
+///
+/// Returns an error if the certificate cannot be parsed.
+pub fn inspect_certificate_json(pem: &[u8]) -> Result<String> {
+    let info = inspect_certificate(pem)?;
+    serde_json::to_string_pretty(&info)
+        .map_err(|e| Error::Certificate(format!("Failed to serialize certificate info: {}", e)))
+}
+
+/// Format a PEM certificate as human-readable text.
+///
+/// The layout follows `openssl x509 -noout -text` (subject, issuer,
+/// validity, public key, key usage, basic constraints and SANs), but needs
+/// no openssl binary.
+///
+/// # Errors
+///
+/// Returns an error if the PEM or certificate cannot be parsed.
+pub fn certificate_text(pem: &[u8]) -> Result<String> {
+    use std::fmt::Write;
+    use x509_parser::objects::{oid_registry, oid2sn};
+    use x509_parser::public_key::PublicKey;
+
+    let date_format = time::macros::format_description!(
+        "[month repr:short] [day padding:space] [hour]:[minute]:[second] [year] GMT"
+    );
+    let format_date = |date: OffsetDateTime| date.format(&date_format).unwrap_or_default();
+    let oid_name = |oid| {
+        oid2sn(oid, oid_registry())
+            .map(str::to_string)
+            .unwrap_or_else(|_| oid.to_id_string())
+    };
+
+    let cert_der = parse_pem_cert(pem)?;
+    let (_, cert) = x509_parser::parse_x509_certificate(&cert_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?;
+    let parse_err = |e| Error::Certificate(format!("Failed to parse certificate: {}", e));
+
+    let serial = cert
+        .raw_serial()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(":");
+
+    // Writing to a String cannot fail
+    let mut out = String::new();
+    let _ = writeln!(out, "Certificate:");
+    let _ = writeln!(out, "    Data:");
+    let _ = writeln!(
+        out,
+        "        Version: {} (0x{:x})",
+        cert.version().0 + 1,
+        cert.version().0
+    );
+    let _ = writeln!(out, "        Serial Number:");
+    let _ = writeln!(out, "            {}", serial);
+    let _ = writeln!(
+        out,
+        "        Signature Algorithm: {}",
+        oid_name(&cert.signature_algorithm.algorithm)
+    );
+    let _ = writeln!(out, "        Issuer: {}", cert.issuer());
+    let _ = writeln!(out, "        Validity");
+    let _ = writeln!(
+        out,
+        "            Not Before: {}",
+        format_date(cert.validity().not_before.to_datetime())
+    );
+    let _ = writeln!(
+        out,
+        "            Not After : {}",
+        format_date(cert.validity().not_after.to_datetime())
+    );
+    let _ = writeln!(out, "        Subject: {}", cert.subject());
+    let _ = writeln!(out, "        Subject Public Key Info:");
+    let _ = writeln!(
+        out,
+        "            Public Key Algorithm: {}",
+        oid_name(&cert.public_key().algorithm.algorithm)
+    );
+    match cert.public_key().parsed() {
+        Ok(PublicKey::RSA(rsa)) => {
+            let _ = writeln!(out, "                Public-Key: ({} bit)", rsa.key_size());
+        }
+        Ok(PublicKey::EC(ec)) => {
+            let _ = writeln!(out, "                Public-Key: ({} bit)", ec.key_size());
+        }
+        _ => {}
+    }
+
+    let _ = writeln!(out, "        X509v3 extensions:");
+    if let Some(ku) = cert.key_usage().map_err(parse_err)? {
+        let critical = if ku.critical { " critical" } else { "" };
+        let _ = writeln!(out, "            X509v3 Key Usage:{}", critical);
+        let _ = writeln!(out, "                {}", ku.value);
+    }
+    if let Some(eku) = cert.extended_key_usage().map_err(parse_err)? {
+        let usages: Vec<&str> = [
+            (eku.value.server_auth, "TLS Web Server Authentication"),
+            (eku.value.client_auth, "TLS Web Client Authentication"),
+            (eku.value.code_signing, "Code Signing"),
+            (eku.value.email_protection, "E-mail Protection"),
+            (eku.value.time_stamping, "Time Stamping"),
+            (eku.value.ocsp_signing, "OCSP Signing"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect();
+        let _ = writeln!(out, "            X509v3 Extended Key Usage:");
+        let _ = writeln!(out, "                {}", usages.join(", "));
+    }
+    if let Some(bc) = cert.basic_constraints().map_err(parse_err)? {
+        let critical = if bc.critical { " critical" } else { "" };
+        let _ = writeln!(out, "            X509v3 Basic Constraints:{}", critical);
+        let ca = if bc.value.ca { "TRUE" } else { "FALSE" };
+        match bc.value.path_len_constraint {
+            Some(len) => {
+                let _ = writeln!(out, "                CA:{}, pathlen:{}", ca, len);
+            }
+            None => {
+                let _ = writeln!(out, "                CA:{}", ca);
+            }
+        }
+    }
+
+    let sans: Vec<String> = cert_sans(&cert_der)?
+        .into_iter()
+        .map(|san| match san {
+            HostType::DnsName(dns) => format!("DNS:{}", dns),
+            HostType::IpAddress(ip) => format!("IP Address:{}", ip),
+            HostType::Email(email) => format!("email:{}", email),
+            HostType::Uri(uri) => format!("URI:{}", uri),
+        })
+        .collect();
+    if !sans.is_empty() {
+        let _ = writeln!(out, "            X509v3 Subject Alternative Name:");
+        let _ = writeln!(out, "                {}", sans.join(", "));
+    }
+
+    Ok(out)
+}
+
+/// Parse every PEM block in the data.
+///
+/// Text outside the blocks (such as the human-readable dump OpenSSL puts
+/// before a certificate) is ignored, so files holding a certificate and its
+/// key, or a whole chain, can be read in one go.
+///
+/// # Returns
+///
+/// The blocks in file order.
+///
+/// # Errors
+///
+/// Returns an error if a block is malformed or there are no blocks at all.
+pub fn parse_pem_blocks(data: &[u8]) -> Result<Vec<::pem::Pem>> {
+    let blocks = ::pem::parse_many(data)
+        .map_err(|e| Error::Certificate(format!("Failed to parse PEM: {}", e)))?;
+    if blocks.is_empty() {
+        return Err(Error::Certificate("No PEM data found".to_string()));
+    }
+    Ok(blocks)
+}
+
+/// Find the first PEM block with the given tag, e.g. `"CERTIFICATE"`.
+pub fn first_of_tag<'a>(blocks: &'a [::pem::Pem], tag: &str) -> Option<&'a ::pem::Pem> {
+    blocks.iter().find(|block| block.tag() == tag)
+}
+
+/// Decode the first certificate from PEM data.
+///
+/// Other blocks, such as a private key stored in the same file, are skipped.
+///
+/// # Returns
+///
+/// The DER bytes of the certificate.
+///
+/// # Errors
+///
+/// Returns an error if the data is not PEM or contains no `CERTIFICATE`
+/// block.
+pub fn parse_pem_cert(pem: &[u8]) -> Result<Vec<u8>> {
+    let blocks = parse_pem_blocks(pem)?;
+    let cert = first_of_tag(&blocks, "CERTIFICATE").ok_or_else(|| {
+        Error::Certificate(format!("Expected CERTIFICATE, got {}", blocks[0].tag()))
+    })?;
+    Ok(cert.contents().to_vec())
+}
+
+/// Check whether a private key belongs to a certificate.
+///
+/// Compares the public key derived from the private key with the
+/// certificate's SubjectPublicKeyInfo. Either input may contain other PEM
+/// blocks; the first private key and the first certificate are used. The
+/// key may be PKCS#8, PKCS#1 or SEC1, as accepted by [`convert_key`].
+///
+/// # Errors
+///
+/// Returns an error if there is no unencrypted private key or no
+/// certificate, or either cannot be parsed.
+pub fn key_matches_cert(key_pem: &[u8], cert_pem: &[u8]) -> Result<bool> {
+    let pkcs8_der = convert_key(key_pem, KeyFormat::Pkcs8Der)?;
+    let key_pair = KeyPair::try_from(pkcs8_der.as_slice())
+        .map_err(|e| Error::Certificate(format!("Failed to parse private key: {}", e)))?;
+
+    key_pair_matches_cert(&key_pair, &parse_pem_cert(cert_pem)?)
+}
+
+/// Check whether a key pair's public key is the one in a DER certificate.
+pub(crate) fn key_pair_matches_cert(key_pair: &KeyPair, cert_der: &[u8]) -> Result<bool> {
+    use rcgen::PublicKeyData;
+
+    let (_, cert) = x509_parser::parse_x509_certificate(cert_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?;
+    Ok(cert.public_key().subject_public_key.data.as_ref() == key_pair.der_bytes())
+}
+
+/// Get the Subject Alternative Names of a DER certificate.
+///
+/// Names are returned in certificate order. SAN types other than DNS, IP,
+/// email and URI are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the certificate cannot be parsed.
+pub fn cert_sans(cert_der: &[u8]) -> Result<Vec<HostType>> {
+    use x509_parser::extensions::GeneralName;
+
+    let (_, cert) = x509_parser::parse_x509_certificate(cert_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?;
+    let Some(san) = cert
+        .subject_alternative_name()
+        .map_err(|e| Error::Certificate(format!("Invalid SAN extension: {}", e)))?
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut sans = Vec::new();
+    for name in &san.value.general_names {
+        match name {
+            GeneralName::DNSName(dns) => sans.push(HostType::DnsName(dns.to_string())),
+            GeneralName::RFC822Name(email) => sans.push(HostType::Email(email.to_string())),
+            GeneralName::URI(uri) => sans.push(HostType::Uri(uri.to_string())),
+            GeneralName::IPAddress(bytes) => {
+                let ip = match bytes.len() {
+                    4 => <[u8; 4]>::try_from(*bytes).ok().map(IpAddr::from),
+                    16 => <[u8; 16]>::try_from(*bytes).ok().map(IpAddr::from),
+                    _ => None,
+                };
+                sans.extend(ip.map(HostType::IpAddress));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(sans)
+}
+
+/// Get the Subject Alternative Names of a PEM certificate.
+///
+/// The PEM form of [`cert_sans`]. The result can be passed straight to
+/// [`CertificateConfig::from_host_types`] to re-issue for the same names.
+///
+/// # Errors
+///
+/// Returns an error if the PEM or the certificate cannot be parsed.
+pub fn certificate_sans(pem: &[u8]) -> Result<Vec<HostType>> {
+    cert_sans(&parse_pem_cert(pem)?)
+}
+
+/// Get the Subject Alternative Names of a PEM certificate as host strings.
+///
+/// The strings are in the form [`generate_certificate`] takes, so a
+/// certificate can be renewed without its original configuration: IP
+/// addresses are formatted, emails and URIs are returned as-is, and DNS
+/// names are given in their ASCII (A-label) form, lowercased. A wildcard
+/// keeps its `*.` prefix.
+///
+/// # Errors
+///
+/// Returns an error if the PEM or the certificate cannot be parsed.
+pub fn hosts_from_existing(cert_pem: &[u8]) -> Result<Vec<String>> {
+    Ok(certificate_sans(cert_pem)?
+        .into_iter()
+        .map(|san| match san {
+            HostType::DnsName(name) => {
+                let (wildcard, domain) = match name.strip_prefix("*.") {
+                    Some(domain) => ("*.", domain),
+                    None => ("", name.as_str()),
+                };
+                // Names idna cannot map (e.g. with underscores) are kept as written
+                let ascii = domain_to_ascii(domain).unwrap_or_else(|_| domain.to_string());
+                format!("{}{}", wildcard, ascii)
+            }
+            other => other.to_string(),
+        })
+        .collect())
+}
+
+/// Get the serial number of a DER certificate, formatted by [`format_serial`].
+///
+/// # Errors
+///
+/// Returns an error if the certificate cannot be parsed.
+pub fn cert_serial_hex(cert_der: &[u8]) -> Result<String> {
+    let (_, cert) = x509_parser::parse_x509_certificate(cert_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?;
+    Ok(format_serial(cert.raw_serial()))
+}
+
+/// Format a big-endian serial number the way `openssl x509 -serial` does.
+///
+/// The result is uppercase hex of even length, without the sign-padding
+/// zero byte DER adds when the high bit is set (e.g. `0A`, `7C8B...`).
+pub fn format_serial(serial: &[u8]) -> String {
+    let start = serial
+        .iter()
+        .position(|&b| b != 0)
+        .unwrap_or(serial.len().saturating_sub(1));
+    match &serial[start..] {
+        [] => "00".to_string(),
+        digits => hex::encode_upper(digits),
+    }
+}
+
+/// Get the validity period of a DER certificate.
+///
+/// # Returns
+///
+/// A `(not_before, not_after)` tuple.
+///
+/// # Errors
+///
+/// Returns an error if the certificate cannot be parsed.
+pub fn cert_validity(cert_der: &[u8]) -> Result<(OffsetDateTime, OffsetDateTime)> {
+    let (_, cert) = x509_parser::parse_x509_certificate(cert_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?;
+    let validity = cert.validity();
+    Ok((
+        validity.not_before.to_datetime(),
+        validity.not_after.to_datetime(),
+    ))
+}
+
+/// Encode a leaf certificate as PEM, followed by its issuer if that is an
+/// intermediate CA.
+///
+/// Servers need to present the intermediate alongside the leaf, so the chain
+/// is written into the certificate file. A self-signed issuer (the root) is
+/// never included.
+fn cert_chain_pem(cert_der: &[u8], issuer_cert_pem: &str) -> Result<String> {
+    use x509_parser::prelude::*;
+
+    let mut chain = cert_to_pem(cert_der);
+
+    let issuer_der = parse_pem_cert(issuer_cert_pem.as_bytes())?;
+    let (_, issuer_cert) = X509Certificate::from_der(&issuer_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse CA certificate: {}", e)))?;
+    if issuer_cert.subject() != issuer_cert.issuer() {
+        chain.push_str(&cert_to_pem(&issuer_der));
+    }
+
+    Ok(chain)
+}
+
 /// Validate certificate chain (cert must be signed by CA)
 pub fn validate_cert_chain(cert_der: &[u8], ca_cert_der: &[u8]) -> Result<()> {
     use x509_parser::prelude::*;
@@ -456,6 +1852,214 @@ pub fn validate_cert_chain(cert_der: &[u8], ca_cert_der: &[u8]) -> Result<()> {
     Ok(())
 }
 
+/// Validate a chain of certificates up to a root CA.
+///
+/// `chain` holds DER certificates ordered from the leaf upwards, e.g.
+/// `[leaf, intermediate]`. Each certificate must be issued and signed by the
+/// next one, and the last by `ca_cert_der`.
+///
+/// # Errors
+///
+/// Returns an error if the chain is empty, a certificate cannot be parsed,
+/// or any link has a mismatched issuer or an invalid signature.
+pub fn validate_chain(chain: &[Vec<u8>], ca_cert_der: &[u8]) -> Result<()> {
+    use x509_parser::prelude::*;
+
+    if chain.is_empty() {
+        return Err(Error::Certificate("Certificate chain is empty".to_string()));
+    }
+
+    let issuers = chain.iter().skip(1).map(Vec::as_slice);
+    for (cert_der, issuer_der) in chain.iter().zip(issuers.chain([ca_cert_der])) {
+        validate_cert_chain(cert_der, issuer_der)?;
+
+        let (_, cert) = X509Certificate::from_der(cert_der)
+            .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?;
+        let (_, issuer) = X509Certificate::from_der(issuer_der)
+            .map_err(|e| Error::Certificate(format!("Failed to parse CA certificate: {}", e)))?;
+        verify_cert_signature(&cert, &issuer)?;
+    }
+
+    Ok(())
+}
+
+/// DER of the RSASSA-PSS AlgorithmIdentifier fastcert signs with: SHA-256,
+/// MGF1 with SHA-256 and a 32-byte salt, as RFC 4055 recommends
+fn rsa_pss_algorithm_der() -> Vec<u8> {
+    use yasna::models::ObjectIdentifier;
+
+    let sha256 = ObjectIdentifier::from_slice(&[2, 16, 840, 1, 101, 3, 4, 2, 1]);
+    yasna::construct_der(|w| {
+        w.write_sequence(|w| {
+            w.next().write_oid(&ObjectIdentifier::from_slice(&[
+                1, 2, 840, 113549, 1, 1, 10,
+            ]));
+            w.next().write_sequence(|w| {
+                w.next().write_tagged(yasna::Tag::context(0), |w| {
+                    w.write_sequence(|w| w.next().write_oid(&sha256))
+                });
+                w.next().write_tagged(yasna::Tag::context(1), |w| {
+                    w.write_sequence(|w| {
+                        w.next().write_oid(&ObjectIdentifier::from_slice(&[
+                            1, 2, 840, 113549, 1, 1, 8,
+                        ]));
+                        w.next().write_sequence(|w| w.next().write_oid(&sha256));
+                    })
+                });
+                w.next()
+                    .write_tagged(yasna::Tag::context(2), |w| w.write_u8(32));
+            });
+        })
+    })
+}
+
+/// Re-sign a certificate with RSASSA-PSS.
+///
+/// rcgen only signs with PKCS#1 v1.5 for RSA keys, so the certificate is
+/// first signed as usual and then its TBSCertificate is given the PSS
+/// algorithm identifier and signed again with `signer`.
+///
+/// # Errors
+///
+/// Returns an error if `signer` is not an RSA key or the certificate
+/// cannot be parsed.
+pub(crate) fn resign_rsa_pss(cert_der: &[u8], signer: &KeyPair) -> Result<Vec<u8>> {
+    use ring::rand::SystemRandom;
+    use ring::signature::{RSA_PSS_SHA256, RsaKeyPair};
+
+    let key = RsaKeyPair::from_pkcs8(&signer.serialize_der()).map_err(|_| {
+        Error::Certificate(
+            "RSA-PSS signatures need an RSA CA key. Use the standard signature algorithm with this CA"
+                .to_string(),
+        )
+    })?;
+    let algorithm = rsa_pss_algorithm_der();
+
+    let invalid = |e: yasna::ASN1Error| {
+        Error::Certificate(format!("Failed to parse certificate for re-signing: {}", e))
+    };
+    let tbs = yasna::parse_der(cert_der, |r| {
+        r.read_sequence(|r| {
+            let tbs = r.next().read_der()?;
+            r.next().read_der()?;
+            r.next().read_bitvec_bytes()?;
+            Ok(tbs)
+        })
+    })
+    .map_err(invalid)?;
+    // TBSCertificate starts with [0] version, serialNumber, signature
+    let fields = yasna::parse_der(&tbs, |r| {
+        r.read_sequence(|r| {
+            let mut fields = Vec::new();
+            while let Some(field) = r.read_optional(|r| r.read_der())? {
+                fields.push(field);
+            }
+            Ok(fields)
+        })
+    })
+    .map_err(invalid)?;
+    if fields.len() < 3 {
+        return Err(Error::Certificate(
+            "Failed to parse certificate for re-signing: truncated TBSCertificate".to_string(),
+        ));
+    }
+
+    let tbs = yasna::construct_der(|w| {
+        w.write_sequence(|w| {
+            for (i, field) in fields.iter().enumerate() {
+                w.next().write_der(if i == 2 { &algorithm } else { field });
+            }
+        })
+    });
+    let mut signature = vec![0; key.public().modulus_len()];
+    key.sign(&RSA_PSS_SHA256, &SystemRandom::new(), &tbs, &mut signature)
+        .map_err(|_| Error::Certificate("Failed to sign certificate with RSA-PSS".to_string()))?;
+
+    Ok(yasna::construct_der(|w| {
+        w.write_sequence(|w| {
+            w.next().write_der(&tbs);
+            w.next().write_der(&algorithm);
+            w.next().write_bitvec_bytes(&signature, signature.len() * 8);
+        })
+    }))
+}
+
+/// Verify a certificate's signature with its issuer's public key.
+///
+/// x509-parser only verifies PKCS#1 v1.5, ECDSA and Ed25519 signatures, so
+/// RSASSA-PSS (as written by [`resign_rsa_pss`]) is checked here directly.
+fn verify_cert_signature(
+    cert: &x509_parser::certificate::X509Certificate,
+    issuer: &x509_parser::certificate::X509Certificate,
+) -> Result<()> {
+    use x509_parser::oid_registry::{OID_NIST_HASH_SHA256, OID_PKCS1_RSASSAPSS};
+    use x509_parser::signature_algorithm::SignatureAlgorithm as X509SignatureAlgorithm;
+
+    if cert.signature_algorithm.algorithm != OID_PKCS1_RSASSAPSS {
+        return cert
+            .verify_signature(Some(issuer.public_key()))
+            .map_err(|e| Error::Certificate(format!("Invalid certificate signature: {}", e)));
+    }
+
+    let supported = match X509SignatureAlgorithm::try_from(&cert.signature_algorithm) {
+        Ok(X509SignatureAlgorithm::RSASSA_PSS(params)) => {
+            *params.hash_algorithm_oid() == OID_NIST_HASH_SHA256
+                && params
+                    .mask_gen_algorithm()
+                    .is_ok_and(|mgf| mgf.hash == OID_NIST_HASH_SHA256)
+                && params.salt_length() == 32
+        }
+        _ => false,
+    };
+    if !supported {
+        return Err(Error::Certificate(
+            "Unsupported RSA-PSS parameters; only SHA-256 with a 32-byte salt is verified"
+                .to_string(),
+        ));
+    }
+    ring::signature::UnparsedPublicKey::new(
+        &ring::signature::RSA_PSS_2048_8192_SHA256,
+        &issuer.public_key().subject_public_key.data,
+    )
+    .verify(cert.tbs_certificate.as_ref(), &cert.signature_value.data)
+    .map_err(|_| Error::Certificate("Invalid certificate signature: RSA-PSS".to_string()))
+}
+
+/// Verify a PEM certificate against a PEM CA certificate without OpenSSL.
+///
+/// `leaf` may hold the leaf followed by intermediates, as fastcert writes
+/// it; every link is checked with [`validate_chain`] up to the first
+/// certificate in `ca`. Each certificate, including the CA, must also be
+/// within its validity window.
+///
+/// # Errors
+///
+/// Returns an error if either input has no certificate, a certificate is
+/// expired or not yet valid, or the chain does not verify.
+pub fn verify_chain_pem(leaf: &[u8], ca: &[u8]) -> Result<()> {
+    let chain: Vec<Vec<u8>> = parse_pem_blocks(leaf)?
+        .into_iter()
+        .filter(|block| block.tag() == "CERTIFICATE")
+        .map(|block| block.into_contents())
+        .collect();
+    let ca_der = parse_pem_cert(ca)?;
+
+    for der in chain.iter().chain([&ca_der]) {
+        let (_, cert) = x509_parser::parse_x509_certificate(der)
+            .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?;
+        if !cert.validity().is_valid() {
+            return Err(Error::Certificate(format!(
+                "Certificate \"{}\" is not valid now (valid from {} to {})",
+                cert.subject(),
+                cert.validity().not_before,
+                cert.validity().not_after
+            )));
+        }
+    }
+
+    validate_chain(&chain, &ca_der)
+}
+
 /// Print expiry warning if certificate is expiring soon
 pub fn check_cert_expiry_warning(expiration: OffsetDateTime) {
     if is_cert_expiring_soon(expiration) {
@@ -468,14 +2072,12 @@ pub fn check_cert_expiry_warning(expiration: OffsetDateTime) {
     }
 }
 
-/// Process a single host and convert to SanType
-fn process_host_to_san(host: &str) -> Result<SanType> {
-    let host_type = HostType::parse(host)?;
+/// Validate a typed host and convert it to a SanType
+fn host_type_to_san(host_type: HostType) -> Result<SanType> {
     match host_type {
         HostType::DnsName(name) => {
             validate_hostname(&name)?;
             validate_wildcard_depth(&name)?;
-            check_wildcard_warning(&name);
             let ia5 = Ia5String::try_from(name)
                 .map_err(|e| Error::Certificate(format!("Invalid DNS name: {}", e)))?;
             Ok(SanType::DnsName(ia5))
@@ -495,8 +2097,223 @@ fn process_host_to_san(host: &str) -> Result<SanType> {
 }
 
 /// Build Subject Alternative Names from a list of host strings
+///
+/// Produces exactly one SAN per host, in order, except that an IP range
+/// (see [`expand_hosts`]) produces one SAN per address. Nothing is added
+/// implicitly (no `localhost` or loopback IPs), so the SAN set is
+/// deterministic.
+///
+/// In strict mode (`FASTCERT_STRICT`), also warns about wildcards listed
+/// without their apex domain and names under public TLDs; see
+/// [`lint_wildcard_apex`] and [`lint_public_tlds`].
 pub fn build_san_list(hosts: &[String]) -> Result<Vec<SanType>> {
-    hosts.iter().map(|host| process_host_to_san(host)).collect()
+    let host_types = expand_hosts(hosts)?;
+    check_san_count(host_types.len())?;
+    let sans = host_types
+        .into_iter()
+        .map(host_type_to_san)
+        .collect::<Result<Vec<_>>>()?;
+    warn_wildcards(&sans);
+
+    for warning in lint_wildcard_apex(hosts) {
+        crate::info_print(&format!("{} {}", "Warning:".yellow().bold(), warning));
+    }
+    for advisory in lint_public_tlds(hosts) {
+        crate::verbose_print(&advisory);
+    }
+
+    Ok(sans)
+}
+
+/// Build Subject Alternative Names from explicitly typed hosts
+///
+/// Each entry keeps its given type; nothing is re-classified. Values are
+/// still validated for their type.
+pub fn build_san_list_from_types(host_types: &[HostType]) -> Result<Vec<SanType>> {
+    check_san_count(host_types.len())?;
+    let sans = host_types
+        .iter()
+        .cloned()
+        .map(host_type_to_san)
+        .collect::<Result<Vec<_>>>()?;
+    warn_wildcards(&sans);
+    Ok(sans)
+}
+
+/// Validate a list of hosts without loading the CA or writing anything.
+///
+/// Runs the same checks as certificate generation (IP, email, URI and
+/// hostname syntax, wildcard placement) and returns the parsed hosts in
+/// order, so a form can report errors before anything is issued. IP ranges
+/// are expanded, so the result may be longer than `hosts`.
+///
+/// # Errors
+///
+/// Returns `Error::InvalidHost` for the first invalid entry, carrying its
+/// index in `hosts` and the underlying validation error.
+pub fn validate_hosts(hosts: &[String]) -> Result<Vec<HostType>> {
+    let mut host_types = Vec::with_capacity(hosts.len());
+    for (index, host) in hosts.iter().enumerate() {
+        let expanded = expand_host(host)
+            .and_then(|expanded| {
+                for host_type in &expanded {
+                    host_type_to_san(host_type.clone())?;
+                }
+                Ok(expanded)
+            })
+            .map_err(|e| Error::InvalidHost {
+                index,
+                host: host.clone(),
+                source: Box::new(e),
+            })?;
+        host_types.extend(expanded);
+    }
+    check_san_count(host_types.len())?;
+    Ok(host_types)
+}
+
+/// Read a host list with one host per line, e.g. from stdin.
+///
+/// Surrounding whitespace is trimmed, and blank lines and lines starting
+/// with `#` are skipped. The remaining hosts are checked with
+/// [`validate_hosts`].
+///
+/// # Errors
+///
+/// Returns an error if reading fails or a host is invalid.
+pub fn read_hosts_from_reader(reader: impl std::io::BufRead) -> Result<Vec<String>> {
+    let mut hosts = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let host = line.trim();
+        if !host.is_empty() && !host.starts_with('#') {
+            hosts.push(host.to_string());
+        }
+    }
+    validate_hosts(&hosts)?;
+    Ok(hosts)
+}
+
+/// Print wildcard warnings for the DNS names in a SAN list
+fn warn_wildcards(sans: &[SanType]) {
+    for san in sans {
+        if let SanType::DnsName(name) = san {
+            check_wildcard_warning(name.as_str());
+        }
+    }
+}
+
+/// Find wildcard names whose apex domain is missing from the host list.
+///
+/// `*.example.com` matches `www.example.com` but not `example.com` itself,
+/// which is easy to forget. Only runs when `FASTCERT_STRICT` is set.
+///
+/// # Returns
+///
+/// One warning message per wildcard without its apex; empty when strict
+/// mode is off.
+pub fn lint_wildcard_apex(hosts: &[String]) -> Vec<String> {
+    if !crate::is_strict() {
+        return Vec::new();
+    }
+
+    hosts
+        .iter()
+        .filter_map(|host| host.strip_prefix("*."))
+        .filter(|apex| !hosts.iter().any(|h| h.eq_ignore_ascii_case(apex)))
+        .map(|apex| {
+            format!(
+                "\"*.{0}\" does not cover \"{0}\"; add it to the host list if needed",
+                apex
+            )
+        })
+        .collect()
+}
+
+/// Common public top-level domains that real sites live under.
+///
+/// Not a full public suffix list; it only needs to catch the usual suspects.
+const PUBLIC_TLDS: &[&str] = &[
+    "app", "biz", "ca", "cloud", "co", "com", "de", "dev", "edu", "eu", "fr", "gov", "info", "io",
+    "jp", "me", "net", "nl", "org", "page", "ru", "site", "tech", "uk", "us", "xyz",
+];
+
+/// Find DNS names under a public TLD, where a dev certificate is unexpected.
+///
+/// Names such as `example.com` belong to real sites, so a development
+/// certificate for them is usually a typo or a copy-paste leftover;
+/// reserved names such as `.local`, `.test` or `.localhost` are the safe
+/// choice. Only runs when `FASTCERT_STRICT` is set.
+///
+/// # Returns
+///
+/// One advisory message per DNS name with a public TLD; empty when strict
+/// mode is off.
+pub fn lint_public_tlds(hosts: &[String]) -> Vec<String> {
+    if !crate::is_strict() {
+        return Vec::new();
+    }
+
+    hosts
+        .iter()
+        .filter(|host| matches!(HostType::parse(host), Ok(HostType::DnsName(_))))
+        .filter(|host| {
+            let tld = host.trim_end_matches('.').rsplit('.').next().unwrap_or("");
+            host.contains('.') && PUBLIC_TLDS.contains(&tld.to_ascii_lowercase().as_str())
+        })
+        .map(|host| {
+            format!(
+                "\"{}\" uses a public TLD; consider a reserved name such as .local or .test for development",
+                host
+            )
+        })
+        .collect()
+}
+
+/// Check that a CN-only certificate has exactly one DNS host.
+fn validate_omit_san(host_types: &[HostType]) -> Result<()> {
+    match host_types {
+        [HostType::DnsName(_)] => Ok(()),
+        [_] => Err(Error::Certificate(
+            "Omitting the subjectAltName requires a DNS name; IP addresses, emails and URIs can only be carried in a SAN".to_string(),
+        )),
+        _ => Err(Error::Certificate(format!(
+            "Omitting the subjectAltName requires exactly one host, got {}",
+            host_types.len()
+        ))),
+    }
+}
+
+/// OID of the Microsoft User Principal Name `otherName` SAN
+const UPN_OID: &[u64] = &[1, 3, 6, 1, 4, 1, 311, 20, 2, 3];
+
+/// Validate that a User Principal Name has the form `user@domain`
+pub fn validate_upn(upn: &str) -> Result<()> {
+    validate_email_address(upn).map_err(|_| {
+        Error::Certificate(format!(
+            "Invalid UPN '{}'. Expected the form user@domain",
+            upn
+        ))
+    })
+}
+
+/// Check that a configured UPN is valid and can be carried in a SAN.
+fn validate_upn_config(config: &CertificateConfig) -> Result<()> {
+    let Some(upn) = &config.upn else {
+        return Ok(());
+    };
+    validate_upn(upn)?;
+    if !config.client_cert {
+        return Err(Error::Certificate(
+            "A UPN can only be added to a client certificate".to_string(),
+        ));
+    }
+    if config.omit_san {
+        return Err(Error::Certificate(
+            "A UPN is carried in the subjectAltName, which omit_san removes".to_string(),
+        ));
+    }
+    Ok(())
 }
 
 /// Validate wildcard depth (only one level deep is allowed)
@@ -505,29 +2322,78 @@ pub fn validate_wildcard_depth(name: &str) -> Result<()> {
         // Count the number of wildcard components
         let wildcard_count = name.matches("*").count();
         if wildcard_count > 1 {
-            return Err(Error::InvalidHostname(format!(
-                "Multiple wildcards not allowed: {}",
-                name
-            )));
+            return Err(Error::InvalidWildcard(
+                name.to_string(),
+                "only one wildcard is allowed".to_string(),
+            ));
         }
 
         // Ensure wildcard is only at the beginning
         if stripped.contains('*') {
-            return Err(Error::InvalidHostname(format!(
-                "Wildcard must be at the beginning: {}",
-                name
-            )));
+            return Err(Error::InvalidWildcard(
+                name.to_string(),
+                "the wildcard must be the whole leftmost label".to_string(),
+            ));
         }
     } else if name.contains('*') {
-        return Err(Error::InvalidHostname(format!(
-            "Wildcard must be at the beginning: {}",
-            name
-        )));
+        return Err(Error::InvalidWildcard(
+            name.to_string(),
+            "the wildcard must be the whole leftmost label".to_string(),
+        ));
     }
 
     Ok(())
 }
 
+/// Build the wildcard SANs covering several levels below a base domain.
+///
+/// A wildcard only matches one label, so `*.example.com` does not cover
+/// `a.sub.example.com`; that needs `*.sub.example.com` as well. Given the
+/// base domain and the subdomains whose children should be covered, this
+/// returns `*.<base>` followed by `*.<subdomain>.<base>` for each one, in
+/// order and without duplicates. The apex itself is not included.
+///
+/// ```
+/// let sans = fastcert::cert::wildcard_levels("example.com", &["sub", "api.sub"]).unwrap();
+/// assert_eq!(sans, ["*.example.com", "*.sub.example.com", "*.api.sub.example.com"]);
+/// ```
+///
+/// # Errors
+///
+/// Returns `Error::InvalidWildcard` if the base domain or a subdomain
+/// contains a wildcard or is not a valid hostname.
+pub fn wildcard_levels(base: &str, subdomains: &[&str]) -> Result<Vec<String>> {
+    let base = base.trim().trim_end_matches('.');
+    if base.contains('*') || validate_hostname(base).is_err() {
+        return Err(Error::InvalidWildcard(
+            base.to_string(),
+            "the base must be a plain domain name".to_string(),
+        ));
+    }
+
+    let mut wildcards = vec![format!("*.{}", base)];
+    for subdomain in subdomains {
+        let subdomain = subdomain.trim().trim_matches('.');
+        let wildcard = format!("*.{}.{}", subdomain, base);
+        if subdomain.is_empty() || subdomain.contains('*') || validate_hostname(subdomain).is_err()
+        {
+            return Err(Error::InvalidWildcard(
+                wildcard,
+                "each level must be a plain subdomain; write *.a.example.com, not *.*.example.com"
+                    .to_string(),
+            ));
+        }
+        if !wildcards.contains(&wildcard) {
+            wildcards.push(wildcard);
+        }
+    }
+
+    for wildcard in &wildcards {
+        validate_wildcard_depth(wildcard)?;
+    }
+    Ok(wildcards)
+}
+
 /// Check for wildcard certificates and log warnings
 fn check_wildcard_warning(name: &str) {
     // Check for second-level wildcards (e.g., *.com, *.net)
@@ -555,17 +2421,141 @@ fn check_wildcard_warning(name: &str) {
 /// the limit that macOS/iOS apply to all certificates, including custom roots.
 /// See https://support.apple.com/en-us/HT210176
 pub fn create_cert_params(hosts: &[String]) -> Result<CertificateParams> {
+    let mut params = leaf_cert_params();
+    params.subject_alt_names = build_san_list(hosts)?;
+    Ok(params)
+}
+
+/// Create certificate parameters from explicitly typed SANs
+///
+/// Same as [`create_cert_params`], but the SAN types are taken as given
+/// instead of being detected from each string.
+pub fn create_cert_params_from_types(host_types: &[HostType]) -> Result<CertificateParams> {
+    let mut params = leaf_cert_params();
+    params.subject_alt_names = build_san_list_from_types(host_types)?;
+    Ok(params)
+}
+
+/// Default notBefore backdating in minutes
+const DEFAULT_BACKDATE_MINUTES: u32 = 60;
+
+/// Default leaf lifetime: 2 years and 3 months, always less than the 825
+/// days Apple accepts (2 years = 730 days, 3 months ≈ 90 days)
+pub(crate) const DEFAULT_VALIDITY_DAYS: u32 = 730 + 90;
+
+/// Default notBefore backdating for new certificates.
+///
+/// Reads `FASTCERT_BACKDATE_MINUTES` and defaults to one hour, so a
+/// certificate is not rejected as "not yet valid" by a device whose clock
+/// is a few minutes behind. `0` disables backdating.
+pub fn default_backdate() -> Duration {
+    let minutes = std::env::var("FASTCERT_BACKDATE_MINUTES")
+        .ok()
+        .and_then(|value| value.trim().parse::<u32>().ok())
+        .unwrap_or(DEFAULT_BACKDATE_MINUTES);
+    Duration::minutes(i64::from(minutes))
+}
+
+/// Keep a certificate from outliving the CA certificate that signs it.
+///
+/// Only matters for a CA created with a short `FASTCERT_CA_VALIDITY_DAYS`.
+pub(crate) fn cap_to_issuer(params: &mut CertificateParams, issuer_cert_pem: &str) -> Result<()> {
+    let issuer_der = parse_pem_cert(issuer_cert_pem.as_bytes())?;
+    let (_, issuer) = x509_parser::parse_x509_certificate(&issuer_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse CA certificate: {}", e)))?;
+    let issuer_not_after = issuer.validity().not_after.to_datetime();
+    if params.not_after > issuer_not_after {
+        params.not_after = issuer_not_after;
+    }
+    Ok(())
+}
+
+/// Key algorithm of an issuer's certificate, as used for an inheriting leaf.
+///
+/// RSA issuers map to RSA-2048 whatever their own key size, matching the
+/// regular leaf default.
+fn issuer_key_algorithm(issuer_cert_pem: &str) -> Result<KeyAlgorithm> {
+    use x509_parser::oid_registry::OID_SIG_ED25519;
+    use x509_parser::public_key::PublicKey;
+
+    let issuer_der = parse_pem_cert(issuer_cert_pem.as_bytes())?;
+    let (_, issuer) = x509_parser::parse_x509_certificate(&issuer_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse CA certificate: {}", e)))?;
+    match issuer.public_key().parsed() {
+        Ok(PublicKey::RSA(_)) => Ok(KeyAlgorithm::Rsa2048),
+        Ok(PublicKey::EC(ec)) if ec.key_size() > 256 => Ok(KeyAlgorithm::EcdsaP384),
+        Ok(PublicKey::EC(_)) => Ok(KeyAlgorithm::EcdsaP256),
+        _ if issuer.public_key().algorithm.algorithm == OID_SIG_ED25519 => {
+            Ok(KeyAlgorithm::Ed25519)
+        }
+        _ => Err(Error::Certificate(
+            "Unsupported CA key algorithm for the certificate key".to_string(),
+        )),
+    }
+}
+
+/// Load the CA key again so that it signs with `hash`.
+///
+/// # Errors
+///
+/// Returns an error if the CA key cannot sign with that hash: ECDSA keys
+/// are tied to the hash of their curve and Ed25519 has none to choose.
+fn signing_key_with_hash(key: &KeyPair, hash: HashAlgorithm) -> Result<KeyPair> {
+    let current = key.algorithm();
+    let algorithm = if [&PKCS_RSA_SHA256, &PKCS_RSA_SHA384, &PKCS_RSA_SHA512].contains(&current) {
+        match hash {
+            HashAlgorithm::Sha256 => &PKCS_RSA_SHA256,
+            HashAlgorithm::Sha384 => &PKCS_RSA_SHA384,
+            HashAlgorithm::Sha512 => &PKCS_RSA_SHA512,
+        }
+    } else {
+        let (name, fixed) = if current == &PKCS_ECDSA_P256_SHA256 {
+            ("ECDSA P-256", Some(HashAlgorithm::Sha256))
+        } else if current == &PKCS_ECDSA_P384_SHA384 {
+            ("ECDSA P-384", Some(HashAlgorithm::Sha384))
+        } else {
+            ("Ed25519", None)
+        };
+        if fixed != Some(hash) {
+            return Err(Error::Certificate(format!(
+                "A {} CA key cannot sign with {}{}",
+                name,
+                hash,
+                fixed.map_or(String::new(), |fixed| format!("; it signs with {}", fixed))
+            )));
+        }
+        current
+    };
+
+    KeyPair::from_pkcs8_der_and_sign_algo(&key.serialize_der().into(), algorithm)
+        .map_err(|e| Error::Certificate(format!("Failed to load CA key for signing: {}", e)))
+}
+
+/// Reject a signed leaf whose subject is the same DN as its issuer.
+///
+/// Such a certificate looks self-issued, which some verifiers treat as a
+/// CA certificate or reject outright, so it is never written out.
+fn ensure_subject_differs_from_issuer(cert_der: &[u8]) -> Result<()> {
+    let (_, cert) = x509_parser::parse_x509_certificate(cert_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?;
+    if cert.subject().as_raw() == cert.issuer().as_raw() {
+        return Err(Error::Certificate(format!(
+            "The certificate subject \"{}\" is the same as the CA subject. Use a different name",
+            cert.subject()
+        )));
+    }
+    Ok(())
+}
+
+/// Leaf certificate parameters without SANs
+fn leaf_cert_params() -> CertificateParams {
     let mut params = CertificateParams::default();
 
-    // Set validity period: 2 years and 3 months (always less than 825 days)
+    // Set validity period, starting a little in the past for clients with a
+    // slow clock
     let now = OffsetDateTime::now_utc();
-    params.not_before = now;
-    // 2 years = 730 days, 3 months ≈ 90 days = 820 days total (< 825 days)
-    params.not_after = now + Duration::days(730 + 90);
-
-    // Build and set SANs
-    let san_list = build_san_list(hosts)?;
-    params.subject_alt_names = san_list;
+    params.not_before = now - default_backdate();
+    params.not_after = now + Duration::days(i64::from(DEFAULT_VALIDITY_DAYS));
 
     // Set key usage for leaf certificates
     params.key_usages = vec![
@@ -573,7 +2563,58 @@ pub fn create_cert_params(hosts: &[String]) -> Result<CertificateParams> {
         KeyUsagePurpose::KeyEncipherment,
     ];
 
-    Ok(params)
+    // Mark as a leaf (CA:FALSE, with a Subject Key Identifier) and link it to
+    // the issuer's Subject Key Identifier for strict path builders
+    params.is_ca = rcgen::IsCa::ExplicitNoCa;
+    params.use_authority_key_identifier_extension = true;
+
+    params
+}
+
+/// Build an Authority Information Access extension from OCSP and CA Issuers URLs
+///
+/// Returns `None` when neither URL is set.
+pub fn build_authority_info_access(
+    ocsp_url: Option<&str>,
+    issuer_url: Option<&str>,
+) -> Result<Option<CustomExtension>> {
+    const OID_AIA: &[u64] = &[1, 3, 6, 1, 5, 5, 7, 1, 1];
+    const OID_OCSP: &[u64] = &[1, 3, 6, 1, 5, 5, 7, 48, 1];
+    const OID_CA_ISSUERS: &[u64] = &[1, 3, 6, 1, 5, 5, 7, 48, 2];
+
+    let mut descriptions = Vec::new();
+    if let Some(url) = ocsp_url {
+        validate_uri(url)?;
+        descriptions.push((OID_OCSP, url));
+    }
+    if let Some(url) = issuer_url {
+        validate_uri(url)?;
+        descriptions.push((OID_CA_ISSUERS, url));
+    }
+    if descriptions.is_empty() {
+        return Ok(None);
+    }
+
+    // AuthorityInfoAccessSyntax ::= SEQUENCE OF AccessDescription
+    let content = yasna::construct_der(|writer| {
+        writer.write_sequence_of(|writer| {
+            for (method, url) in &descriptions {
+                writer.next().write_sequence(|writer| {
+                    writer
+                        .next()
+                        .write_oid(&yasna::models::ObjectIdentifier::from_slice(method));
+                    // accessLocation is a uniformResourceIdentifier GeneralName: [6] IA5String
+                    writer
+                        .next()
+                        .write_tagged_implicit(yasna::Tag::context(6), |writer| {
+                            writer.write_ia5_string(url)
+                        });
+                });
+            }
+        })
+    });
+
+    Ok(Some(CustomExtension::from_oid_content(OID_AIA, content)))
 }
 
 /// Add server authentication extended key usage
@@ -623,8 +2664,261 @@ pub fn key_to_pem(key: &KeyPair) -> Result<String> {
     Ok(pem::encode(&pem::Pem::new("PRIVATE KEY", key_der)))
 }
 
+/// Generate a key pair without issuing a certificate for it.
+///
+/// For workflows that create the key first, build a CSR from it elsewhere
+/// and then have it signed with [`sign_csr`].
+///
+/// # Errors
+///
+/// Returns an error if key generation or encoding fails.
+pub fn generate_key_pair(algo: KeyAlgorithm) -> Result<KeyPairPem> {
+    let key = algo.generate()?;
+    Ok(KeyPairPem {
+        private_pem: key_to_pem(&key)?,
+        public_pem: key.public_key_pem(),
+    })
+}
+
+/// PBKDF2 iteration count for passphrase-protected private keys
+const ENCRYPTED_KEY_ITERATIONS: u32 = 100_000;
+
+/// Serialize a private key as an encrypted PKCS#8 `ENCRYPTED PRIVATE KEY`
+/// PEM, using PBES2 with PBKDF2-HMAC-SHA256 and AES-256-CBC
+pub fn key_to_encrypted_pem(key: &KeyPair, passphrase: &str) -> Result<String> {
+    use pkcs8::der::pem::LineEnding;
+    use pkcs8::pkcs5::pbes2;
+    use ring::rand::{SecureRandom, SystemRandom};
+
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; 16];
+    let mut iv = [0u8; 16];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut iv))
+        .map_err(|_| Error::Certificate("Failed to generate random bytes".to_string()))?;
+
+    let params =
+        pbes2::Parameters::pbkdf2_sha256_aes256cbc(ENCRYPTED_KEY_ITERATIONS, &salt, &iv)
+            .map_err(|e| Error::Certificate(format!("Invalid key encryption parameters: {}", e)))?;
+    let key_der = key.serialize_der();
+    let key_info = pkcs8::PrivateKeyInfo::try_from(key_der.as_slice())
+        .map_err(|e| Error::Certificate(format!("Failed to parse private key: {}", e)))?;
+    let encrypted = key_info
+        .encrypt_with_params(params, passphrase.as_bytes())
+        .map_err(|e| Error::Certificate(format!("Failed to encrypt private key: {}", e)))?;
+
+    let pem = encrypted
+        .to_pem("ENCRYPTED PRIVATE KEY", LineEnding::LF)
+        .map_err(|e| Error::Certificate(format!("Failed to encode private key: {}", e)))?;
+    Ok(pem.to_string())
+}
+
+/// Algorithm OID of RSA keys in PKCS#8
+const RSA_ENCRYPTION_OID: &str = "1.2.840.113549.1.1.1";
+/// Algorithm OID of elliptic curve keys in PKCS#8
+const EC_PUBLIC_KEY_OID: &str = "1.2.840.10045.2.1";
+
+/// An `ECPrivateKey` structure from SEC1
+struct Sec1Key {
+    private_key: Vec<u8>,
+    curve: Option<yasna::models::ObjectIdentifier>,
+    public_key: Option<(Vec<u8>, usize)>,
+}
+
+impl Sec1Key {
+    fn from_der(der: &[u8]) -> Result<Self> {
+        yasna::parse_der(der, |r| {
+            r.read_sequence(|r| {
+                r.next().read_u8()?;
+                let private_key = r.next().read_bytes()?;
+                let curve =
+                    r.read_optional(|r| r.read_tagged(yasna::Tag::context(0), |r| r.read_oid()))?;
+                let public_key = r.read_optional(|r| {
+                    r.read_tagged(yasna::Tag::context(1), |r| r.read_bitvec_bytes())
+                })?;
+                Ok(Self {
+                    private_key,
+                    curve,
+                    public_key,
+                })
+            })
+        })
+        .map_err(|e| Error::Certificate(format!("Failed to parse EC private key: {}", e)))
+    }
+
+    fn to_der(&self) -> Vec<u8> {
+        yasna::construct_der(|w| {
+            w.write_sequence(|w| {
+                w.next().write_u8(1);
+                w.next().write_bytes(&self.private_key);
+                if let Some(curve) = &self.curve {
+                    w.next()
+                        .write_tagged(yasna::Tag::context(0), |w| w.write_oid(curve));
+                }
+                if let Some((bytes, len)) = &self.public_key {
+                    w.next().write_tagged(yasna::Tag::context(1), |w| {
+                        w.write_bitvec_bytes(bytes, *len)
+                    });
+                }
+            })
+        })
+    }
+}
+
+/// Wrap a PKCS#1 or SEC1 key in a PKCS#8 `PrivateKeyInfo`
+fn wrap_pkcs8(
+    algorithm: &str,
+    curve: Option<&yasna::models::ObjectIdentifier>,
+    key: &[u8],
+) -> Vec<u8> {
+    let algorithm: yasna::models::ObjectIdentifier =
+        algorithm.parse().expect("algorithm OID is valid");
+    yasna::construct_der(|w| {
+        w.write_sequence(|w| {
+            w.next().write_u8(0);
+            w.next().write_sequence(|w| {
+                w.next().write_oid(&algorithm);
+                match curve {
+                    Some(curve) => w.next().write_oid(curve),
+                    None => w.next().write_null(),
+                }
+            });
+            w.next().write_bytes(key);
+        })
+    })
+}
+
+/// Re-encode a private key in another format.
+///
+/// Accepts an unencrypted PKCS#8 (`PRIVATE KEY`), PKCS#1 (`RSA PRIVATE
+/// KEY`) or SEC1 (`EC PRIVATE KEY`) PEM and returns the same key material
+/// in `to`, as PEM text or raw DER bytes. Nothing is re-issued, so the key
+/// still matches its certificate.
+///
+/// # Errors
+///
+/// Returns an error if no usable private key is found, the key is
+/// encrypted, or a traditional format is requested for a key that has
+/// none (such as Ed25519).
+pub fn convert_key(key_pem: &[u8], to: KeyFormat) -> Result<Vec<u8>> {
+    let blocks = parse_pem_blocks(key_pem)?;
+    let Some(block) = blocks
+        .iter()
+        .find(|block| block.tag().ends_with("PRIVATE KEY"))
+    else {
+        return Err(Error::Certificate(
+            "No private key found in PEM data".to_string(),
+        ));
+    };
+
+    let pkcs8_der = match block.tag() {
+        "PRIVATE KEY" => block.contents().to_vec(),
+        "RSA PRIVATE KEY" => wrap_pkcs8(RSA_ENCRYPTION_OID, None, block.contents()),
+        "EC PRIVATE KEY" => {
+            let mut key = Sec1Key::from_der(block.contents())?;
+            let curve = key.curve.take().ok_or_else(|| {
+                Error::Certificate("EC private key does not name its curve".to_string())
+            })?;
+            wrap_pkcs8(EC_PUBLIC_KEY_OID, Some(&curve), &key.to_der())
+        }
+        "ENCRYPTED PRIVATE KEY" => {
+            return Err(Error::Certificate(
+                "Private key is encrypted, decrypt it before converting".to_string(),
+            ));
+        }
+        tag => {
+            return Err(Error::Certificate(format!(
+                "Unsupported private key type: {}",
+                tag
+            )));
+        }
+    };
+    // Make sure the key actually loads before handing it back
+    KeyPair::try_from(pkcs8_der.as_slice())
+        .map_err(|e| Error::Certificate(format!("Invalid private key: {}", e)))?;
+
+    match to {
+        KeyFormat::Pkcs8Der => Ok(pkcs8_der),
+        KeyFormat::Pkcs8Pem => {
+            Ok(pem::encode(&pem::Pem::new("PRIVATE KEY", pkcs8_der)).into_bytes())
+        }
+        KeyFormat::TraditionalPem | KeyFormat::TraditionalDer => {
+            let info = pkcs8::PrivateKeyInfo::try_from(pkcs8_der.as_slice())
+                .map_err(|e| Error::Certificate(format!("Failed to parse private key: {}", e)))?;
+            let (tag, der) =
+                match info.algorithm.oid.to_string().as_str() {
+                    RSA_ENCRYPTION_OID => ("RSA PRIVATE KEY", info.private_key.to_vec()),
+                    EC_PUBLIC_KEY_OID => {
+                        let curve = info.algorithm.parameters_oid().map_err(|e| {
+                            Error::Certificate(format!("EC private key has no curve: {}", e))
+                        })?;
+                        let mut key = Sec1Key::from_der(info.private_key)?;
+                        key.curve =
+                            Some(curve.to_string().parse().map_err(|_| {
+                                Error::Certificate(format!("Invalid curve {}", curve))
+                            })?);
+                        ("EC PRIVATE KEY", key.to_der())
+                    }
+                    oid => {
+                        return Err(Error::Certificate(format!(
+                            "Keys with algorithm {} only have a PKCS#8 encoding",
+                            oid
+                        )));
+                    }
+                };
+            Ok(if to == KeyFormat::TraditionalDer {
+                der
+            } else {
+                pem::encode(&pem::Pem::new(tag, der)).into_bytes()
+            })
+        }
+    }
+}
+
+/// Turn a host into a file name component that is valid on every platform
+///
+/// This is how [`generate_file_names`] names files after the first host.
+/// `*` becomes `_wildcard` and `@` becomes `_at_`. Characters Windows does
+/// not allow in file names (including `:` from ports and IPv6 addresses and
+/// `/` from URIs) become `_`. Trailing dots and spaces are dropped, and
+/// reserved device names such as `CON` get a leading `_`.
+pub fn sanitize_filename(host: &str) -> String {
+    let mut name = String::with_capacity(host.len());
+    for c in host.chars() {
+        match c {
+            '*' => name.push_str("_wildcard"),
+            '@' => name.push_str("_at_"),
+            ':' | '/' | '\\' | '<' | '>' | '"' | '|' | '?' => name.push('_'),
+            c if c.is_control() => name.push('_'),
+            c => name.push(c),
+        }
+    }
+
+    let trimmed_len = name.trim_end_matches(['.', ' ']).len();
+    name.truncate(trimmed_len);
+
+    const RESERVED: [&str; 4] = ["CON", "PRN", "AUX", "NUL"];
+    let stem = name.split('.').next().unwrap_or("").to_ascii_uppercase();
+    let is_port = |prefix: &str| {
+        stem.strip_prefix(prefix)
+            .is_some_and(|n| n.len() == 1 && n.as_bytes()[0].is_ascii_digit() && n != "0")
+    };
+    if RESERVED.contains(&stem.as_str()) || is_port("COM") || is_port("LPT") {
+        name.insert(0, '_');
+    }
+
+    if name.is_empty() {
+        "cert".to_string()
+    } else {
+        name
+    }
+}
+
 /// Generate file names for certificate, key, and PKCS#12 files
 /// File naming convention: example.com+4.pem, example.com+4-key.pem, example.com+4.p12
+///
+/// The first host is made filesystem-safe, e.g. `::1` becomes `__1.pem` and
+/// `user@example.com` becomes `user_at_example.com.pem`.
 pub fn generate_file_names(config: &CertificateConfig) -> (PathBuf, PathBuf, PathBuf) {
     // Use provided file names if available
     if let (Some(cert), Some(key), Some(p12)) =
@@ -637,7 +2931,7 @@ pub fn generate_file_names(config: &CertificateConfig) -> (PathBuf, PathBuf, Pat
     let default_name = if config.hosts.is_empty() {
         "cert".to_string()
     } else {
-        let mut name = config.hosts[0].replace(':', "_").replace('*', "_wildcard");
+        let mut name = sanitize_filename(&config.hosts[0]);
 
         // Add count suffix if more than one host
         if config.hosts.len() > 1 {
@@ -668,6 +2962,24 @@ pub fn generate_file_names(config: &CertificateConfig) -> (PathBuf, PathBuf, Pat
     (cert_file, key_file, p12_file)
 }
 
+/// Get the default certificate and key paths for a list of hosts.
+///
+/// Returns what [`generate_file_names`] would pick for a server certificate
+/// without custom paths, for previewing output before generating.
+///
+/// # Errors
+///
+/// Returns an error if `hosts` is empty or contains an invalid host.
+pub fn default_filenames(hosts: &[String]) -> Result<(PathBuf, PathBuf)> {
+    if hosts.is_empty() {
+        return Err(Error::Certificate("No hosts specified".to_string()));
+    }
+    validate_hosts(hosts)?;
+
+    let (cert_file, key_file, _) = generate_file_names(&CertificateConfig::new(hosts.to_vec()));
+    Ok((cert_file, key_file))
+}
+
 /// Write PEM files with appropriate permissions using buffered I/O
 /// Certificate files: 0644 (readable by all)
 /// Key files: 0600 (readable only by owner)
@@ -677,6 +2989,23 @@ pub fn write_pem_files(
     key_path: &PathBuf,
     cert_pem: &str,
     key_pem: &str,
+) -> Result<()> {
+    write_pem_files_ordered(
+        cert_path,
+        key_path,
+        cert_pem,
+        key_pem,
+        CombinedOrder::CertFirst,
+    )
+}
+
+/// Like [`write_pem_files`], with the block order of a combined file
+pub fn write_pem_files_ordered(
+    cert_path: &PathBuf,
+    key_path: &PathBuf,
+    cert_pem: &str,
+    key_pem: &str,
+    order: CombinedOrder,
 ) -> Result<()> {
     use std::io::BufWriter;
 
@@ -685,8 +3014,13 @@ pub fn write_pem_files(
         let file = std::fs::File::create(cert_path).map_err(Error::Io)?;
         let mut writer = BufWriter::new(file);
         use std::io::Write;
-        writer.write_all(cert_pem.as_bytes()).map_err(Error::Io)?;
-        writer.write_all(key_pem.as_bytes()).map_err(Error::Io)?;
+        let blocks = match order {
+            CombinedOrder::CertFirst => [cert_pem, key_pem],
+            CombinedOrder::KeyFirst => [key_pem, cert_pem],
+        };
+        for block in blocks {
+            writer.write_all(block.as_bytes()).map_err(Error::Io)?;
+        }
         writer.flush().map_err(Error::Io)?;
         set_file_permissions(cert_path, 0o600)?;
     } else {
@@ -712,6 +3046,46 @@ pub fn write_pem_files(
     Ok(())
 }
 
+/// Check that a validity period starts before it ends
+fn validate_validity_window(not_before: OffsetDateTime, not_after: OffsetDateTime) -> Result<()> {
+    if not_before >= not_after {
+        return Err(Error::Certificate(format!(
+            "Certificate notBefore ({}) must be earlier than notAfter ({})",
+            format_expiration_date(not_before),
+            format_expiration_date(not_after)
+        )));
+    }
+    Ok(())
+}
+
+/// Validate the custom file modes in a certificate configuration
+///
+/// Modes must fit in the permission bits (0o777), and the key mode must not
+/// grant write access to everyone.
+pub fn validate_file_modes(config: &CertificateConfig) -> Result<()> {
+    for (what, mode) in [("key", config.key_mode), ("certificate", config.cert_mode)] {
+        if let Some(mode) = mode
+            && mode & !0o777 != 0
+        {
+            return Err(Error::Certificate(format!(
+                "Invalid {} file mode {:o}: only permission bits (0777) are allowed",
+                what, mode
+            )));
+        }
+    }
+
+    if let Some(mode) = config.key_mode
+        && mode & 0o002 != 0
+    {
+        return Err(Error::Certificate(format!(
+            "Key file mode {:o} would make the private key world-writable",
+            mode
+        )));
+    }
+
+    Ok(())
+}
+
 /// Set file permissions (Unix: actual permissions, Windows: no-op for now)
 #[cfg(unix)]
 pub(crate) fn set_file_permissions(path: &PathBuf, mode: u32) -> Result<()> {
@@ -741,27 +3115,30 @@ pub fn verify_file_permissions(_path: &PathBuf, _expected_mode: u32) -> Result<b
     Ok(true)
 }
 
-/// Write PKCS#12 file with certificate, key, and CA cert
-/// Uses the default password "changeit"
+/// Write a PKCS#12 bundle containing the certificate, key and, if given,
+/// the CA certificate
+///
+/// The bundle is protected with the password "changeit" using the given
+/// encryption scheme.
 pub fn write_pkcs12_file(
     p12_path: &PathBuf,
     cert_der: &[u8],
     key: &KeyPair,
-    ca_cert_der: &[u8],
+    ca_cert_der: Option<&[u8]>,
+    encryption: P12Encryption,
 ) -> Result<()> {
-    use p12::PFX;
-
     // Get the private key DER (PKCS#8 format)
     let key_der = key.serialize_der();
 
-    // Create PKCS#12 bundle with password "changeit"
-    // The p12 crate's PFX::new takes: cert_der, key_der, ca_chain, password, friendly_name
-    // It returns Option<PFX>
-    let pfx = PFX::new(cert_der, &key_der, Some(ca_cert_der), "changeit", "")
-        .ok_or_else(|| Error::Certificate("Failed to create PKCS#12".to_string()))?;
-
-    // Encode to DER (returns Vec<u8>)
-    let pfx_data = pfx.to_der();
+    let pfx_data = match encryption {
+        P12Encryption::Legacy => {
+            // The p12 crate's PFX::new takes: cert_der, key_der, ca_chain, password, friendly_name
+            p12::PFX::new(cert_der, &key_der, ca_cert_der, PKCS12_PASSWORD, "")
+                .ok_or_else(|| Error::Certificate("Failed to create PKCS#12".to_string()))?
+                .to_der()
+        }
+        P12Encryption::Modern => build_modern_pkcs12(cert_der, &key_der, ca_cert_der)?,
+    };
 
     // Write to file with 0644 permissions
     fs::write(p12_path, &pfx_data).map_err(Error::Io)?;
@@ -770,6 +3147,154 @@ pub fn write_pkcs12_file(
     Ok(())
 }
 
+/// Password protecting generated PKCS#12 bundles
+const PKCS12_PASSWORD: &str = "changeit";
+
+/// PBKDF2 iteration count for modern PKCS#12 bundles
+const PKCS12_ITERATIONS: u32 = 2048;
+
+/// Build a PKCS#12 bundle encrypted with PBES2 (PBKDF2-HMAC-SHA256, AES-256-CBC)
+///
+/// Uses the p12 crate's ASN.1 structures but swaps in PBES2 for the legacy
+/// PBE algorithms, matching what OpenSSL 3 produces by default.
+fn build_modern_pkcs12(
+    cert_der: &[u8],
+    key_der: &[u8],
+    ca_cert_der: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+    use p12::{
+        CertBag, ContentInfo, EncryptedContentInfo, EncryptedData, EncryptedPrivateKeyInfo,
+        MacData, PFX, PKCS12Attribute, SafeBag, SafeBagKind,
+    };
+    use sha1::{Digest, Sha1};
+
+    let password = PKCS12_PASSWORD.as_bytes();
+
+    let local_key_id = PKCS12Attribute::LocalKeyId(Sha1::digest(cert_der).to_vec());
+    let (algorithm, encrypted_data) = pbes2_encrypt(key_der, password)?;
+    let key_bag = SafeBag {
+        bag: SafeBagKind::Pkcs8ShroudedKeyBag(EncryptedPrivateKeyInfo {
+            encryption_algorithm: algorithm,
+            encrypted_data,
+        }),
+        attributes: vec![local_key_id.clone()],
+    };
+
+    let mut cert_bags = vec![SafeBag {
+        bag: SafeBagKind::CertBag(CertBag::X509(cert_der.to_vec())),
+        attributes: vec![local_key_id],
+    }];
+    if let Some(ca_cert_der) = ca_cert_der {
+        cert_bags.push(SafeBag {
+            bag: SafeBagKind::CertBag(CertBag::X509(ca_cert_der.to_vec())),
+            attributes: vec![],
+        });
+    }
+    let cert_safe_contents = yasna::construct_der(|w| {
+        w.write_sequence_of(|w| {
+            for bag in &cert_bags {
+                bag.write(w.next());
+            }
+        })
+    });
+    let (algorithm, encrypted_content) = pbes2_encrypt(&cert_safe_contents, password)?;
+    let cert_content = ContentInfo::EncryptedData(EncryptedData {
+        encrypted_content_info: EncryptedContentInfo {
+            content_encryption_algorithm: algorithm,
+            encrypted_content,
+        },
+    });
+
+    let key_content = ContentInfo::Data(yasna::construct_der(|w| {
+        w.write_sequence_of(|w| key_bag.write(w.next()))
+    }));
+
+    let auth_safe = yasna::construct_der(|w| {
+        w.write_sequence_of(|w| {
+            cert_content.write(w.next());
+            key_content.write(w.next());
+        })
+    });
+
+    // The MAC still uses the PKCS#12 KDF, which expects a BMPString password
+    let mut bmp_password: Vec<u8> = PKCS12_PASSWORD
+        .encode_utf16()
+        .flat_map(|c| c.to_be_bytes())
+        .collect();
+    bmp_password.extend_from_slice(&[0, 0]);
+    let mac_data = MacData::new(&auth_safe, &bmp_password);
+
+    let pfx = PFX {
+        version: 3,
+        auth_safe: ContentInfo::Data(auth_safe),
+        mac_data: Some(mac_data),
+    };
+
+    Ok(pfx.to_der())
+}
+
+/// Encrypt data with PBES2 and return the algorithm identifier and ciphertext
+fn pbes2_encrypt(data: &[u8], password: &[u8]) -> Result<(p12::AlgorithmIdentifier, Vec<u8>)> {
+    use aes::cipher::{BlockEncryptMut, KeyIvInit, block_padding::Pkcs7};
+    use ring::rand::{SecureRandom, SystemRandom};
+    use yasna::models::ObjectIdentifier;
+
+    const OID_PBES2: &[u64] = &[1, 2, 840, 113549, 1, 5, 13];
+    const OID_PBKDF2: &[u64] = &[1, 2, 840, 113549, 1, 5, 12];
+    const OID_HMAC_SHA256: &[u64] = &[1, 2, 840, 113549, 2, 9];
+    const OID_AES256_CBC: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 1, 42];
+
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; 16];
+    let mut iv = [0u8; 16];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut iv))
+        .map_err(|_| Error::Certificate("Failed to generate random bytes".to_string()))?;
+
+    let mut key = [0u8; 32];
+    ring::pbkdf2::derive(
+        ring::pbkdf2::PBKDF2_HMAC_SHA256,
+        std::num::NonZeroU32::new(PKCS12_ITERATIONS).unwrap(),
+        &salt,
+        password,
+        &mut key,
+    );
+
+    let ciphertext = cbc::Encryptor::<aes::Aes256>::new(&key.into(), &iv.into())
+        .encrypt_padded_vec_mut::<Pkcs7>(data);
+
+    // PBES2-params ::= SEQUENCE { keyDerivationFunc, encryptionScheme }
+    let params = yasna::construct_der(|w| {
+        w.write_sequence(|w| {
+            w.next().write_sequence(|w| {
+                w.next()
+                    .write_oid(&ObjectIdentifier::from_slice(OID_PBKDF2));
+                w.next().write_sequence(|w| {
+                    w.next().write_bytes(&salt);
+                    w.next().write_u32(PKCS12_ITERATIONS);
+                    w.next().write_sequence(|w| {
+                        w.next()
+                            .write_oid(&ObjectIdentifier::from_slice(OID_HMAC_SHA256));
+                        w.next().write_null();
+                    });
+                });
+            });
+            w.next().write_sequence(|w| {
+                w.next()
+                    .write_oid(&ObjectIdentifier::from_slice(OID_AES256_CBC));
+                w.next().write_bytes(&iv);
+            });
+        })
+    });
+
+    let algorithm = p12::AlgorithmIdentifier::OtherAlg(p12::OtherAlgorithmIdentifier {
+        algorithm_type: ObjectIdentifier::from_slice(OID_PBES2),
+        params: Some(params),
+    });
+
+    Ok((algorithm, ciphertext))
+}
+
 /// Print certificate hosts with warnings
 pub fn print_hosts(hosts: &[String]) {
     let second_level_wildcard_regex = Regex::new(r"(?i)^\*\.[0-9a-z_-]+$").unwrap();
@@ -804,60 +3329,305 @@ pub fn print_hosts(hosts: &[String]) {
     }
 }
 
-/// Generate a certificate from command line arguments.
-///
-/// This is the main entry point for certificate generation. It loads the CA,
-/// creates a certificate configuration, and generates the certificate with
-/// the specified parameters.
-///
-/// # Arguments
-///
-/// * `domains` - List of domains, IPs, emails, or URIs for the certificate
-/// * `cert_file` - Optional custom path for the certificate file
-/// * `key_file` - Optional custom path for the private key file
-/// * `p12_file` - Optional custom path for the PKCS#12 bundle
-/// * `client` - Generate a client authentication certificate
-/// * `ecdsa` - Use ECDSA instead of RSA for the key
-/// * `pkcs12` - Generate PKCS#12 bundle instead of PEM files
-///
-/// # Returns
+/// Generate a certificate from command line arguments.
+///
+/// This is the main entry point for certificate generation. It loads the CA,
+/// creates a certificate configuration, and generates the certificate with
+/// the specified parameters.
+///
+/// # Arguments
+///
+/// * `domains` - List of domains, IPs, emails, or URIs for the certificate
+/// * `cert_file` - Optional custom path for the certificate file
+/// * `key_file` - Optional custom path for the private key file
+/// * `p12_file` - Optional custom path for the PKCS#12 bundle
+/// * `client` - Generate a client authentication certificate
+/// * `ecdsa` - Use ECDSA instead of RSA for the key
+/// * `pkcs12` - Generate PKCS#12 bundle instead of PEM files
+///
+/// # Returns
+///
+/// A `GenerationResult` describing the issued certificate and written files.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The CA cannot be loaded or created
+/// - Domain validation fails
+/// - Certificate generation fails
+/// - File writing fails
+pub fn generate_certificate(
+    domains: &[String],
+    cert_file: Option<&str>,
+    key_file: Option<&str>,
+    p12_file: Option<&str>,
+    client: bool,
+    ecdsa: bool,
+    pkcs12: bool,
+) -> Result<GenerationResult> {
+    // Build config
+    let mut config = CertificateConfig::new(domains.to_vec());
+    config.client_cert = client;
+    config.use_ecdsa = ecdsa;
+    config.pkcs12 = pkcs12;
+    config.cert_file = cert_file.map(PathBuf::from);
+    config.key_file = key_file.map(PathBuf::from);
+    config.p12_file = p12_file.map(PathBuf::from);
+
+    generate_from_config(&config)
+}
+
+/// Generate a certificate from a [`CertificateConfig`] using the default CA.
+///
+/// Loads (or creates) the CA from CAROOT and writes the certificate files
+/// described by the configuration.
+///
+/// # Example
+///
+/// ```no_run
+/// use fastcert::cert::{CertificateConfig, generate_from_config};
+///
+/// let config = CertificateConfig::builder()
+///     .host("example.com")
+///     .client()
+///     .build()?;
+/// let result = generate_from_config(&config)?;
+/// println!("Certificate written to {:?}", result.cert_path);
+/// # Ok::<(), fastcert::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns `Error::InvalidHost` if a host is invalid, checked before the CA
+/// is touched, or an error if the CA cannot be loaded or certificate
+/// generation fails.
+pub fn generate_from_config(config: &CertificateConfig) -> Result<GenerationResult> {
+    // Reject bad hosts before the CA is loaded or created
+    if config.host_types.is_none() {
+        validate_hosts(&config.hosts)?;
+    }
+
+    // Load CA
+    let started = std::time::Instant::now();
+    let ca = crate::ca::CA::load_or_create()?;
+
+    // Sign with the intermediate CA if there is one, otherwise the root
+    let (ca_cert_pem, ca_key_pem) = ca.signing_pems()?;
+    let ca_load = started.elapsed();
+
+    // Generate the certificate
+    let mut result =
+        generate_certificate_internal(config, &ca_cert_pem, &ca_key_pem, ca.next_serial()?)?;
+    if let Some(timings) = &mut result.timings {
+        timings.ca_load = ca_load;
+    }
+    crate::audit::record(ca.root_path(), &result)?;
+    Ok(result)
+}
+
+/// Rebuild the configuration a leaf certificate was issued with.
+///
+/// Covers the hosts, key type and client authentication; output paths and
+/// validity are left at their defaults.
+pub(crate) fn config_from_cert(cert_der: &[u8]) -> Result<CertificateConfig> {
+    use x509_parser::oid_registry::OID_KEY_TYPE_EC_PUBLIC_KEY;
+
+    let (_, parsed) = x509_parser::parse_x509_certificate(cert_der)
+        .map_err(|e| Error::Certificate(format!("Failed to parse certificate: {}", e)))?;
+    let mut config = CertificateConfig::from_host_types(cert_sans(cert_der)?);
+    config.use_ecdsa = parsed.public_key().algorithm.algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY;
+    config.client_cert = parsed
+        .extended_key_usage()
+        .ok()
+        .flatten()
+        .is_some_and(|eku| eku.value.client_auth);
+    Ok(config)
+}
+
+/// Re-issue an existing certificate from the default CA.
+///
+/// The new certificate keeps the hosts, key type and client authentication
+/// of the old one, gets a fresh key and the default validity, and
+/// overwrites `cert_path` and `key_path`. Passing the same path for both
+/// writes a combined file.
+///
+/// # Errors
+///
+/// Returns an error if the certificate cannot be read or parsed, has no
+/// subject alternative names, or generation fails.
+pub fn renew_certificate(
+    cert_path: &std::path::Path,
+    key_path: &std::path::Path,
+) -> Result<GenerationResult> {
+    let cert_der = parse_pem_cert(&fs::read(cert_path)?)?;
+    let mut config = config_from_cert(&cert_der)?;
+    if config.hosts.is_empty() {
+        return Err(Error::Certificate(format!(
+            "{} has no subject alternative names to renew",
+            cert_path.display()
+        )));
+    }
+    config.cert_file = Some(cert_path.to_path_buf());
+    config.key_file = Some(key_path.to_path_buf());
+    generate_from_config(&config)
+}
+
+/// Renew a certificate only if it expires within `threshold_days`.
+///
+/// Meant for cron jobs: run it regularly and the certificate is re-issued
+/// with [`renew_certificate`] once it gets close to expiry. A certificate
+/// that has already expired is renewed as well.
+///
+/// # Errors
+///
+/// Returns an error if the certificate cannot be read or parsed, or
+/// renewal fails.
+pub fn renew_if_expiring(
+    cert_path: &std::path::Path,
+    key_path: &std::path::Path,
+    threshold_days: u32,
+) -> Result<RenewOutcome> {
+    let info = inspect_certificate(&fs::read(cert_path)?)?;
+    let days_remaining = (info.not_after - OffsetDateTime::now_utc()).whole_days();
+    if days_remaining > i64::from(threshold_days) {
+        return Ok(RenewOutcome::Skipped { days_remaining });
+    }
+
+    crate::verbose_print(&format!(
+        "{} expires in {} days, renewing",
+        cert_path.display(),
+        days_remaining
+    ));
+    renew_certificate(cert_path, key_path).map(|result| RenewOutcome::Renewed(Box::new(result)))
+}
+
+/// Remove an issued certificate together with its key and other formats.
+///
+/// Besides `cert_path` itself, the siblings named after it by fastcert's
+/// convention are removed if present: `<name>-key.pem`, `<name>.pem`,
+/// `<name>.p12` and `<name>.der`. Nothing is removed if any of the
+/// certificates among them is a CA certificate, so the CA files cannot be
+/// deleted this way.
+///
+/// # Errors
+///
+/// Returns an error if `cert_path` does not exist, one of the certificates
+/// is a CA certificate, or a file cannot be removed.
+pub fn remove_cert(cert_path: &std::path::Path) -> Result<RemovedFiles> {
+    if !cert_path.is_file() {
+        return Err(Error::Certificate(format!(
+            "Certificate {} does not exist",
+            cert_path.display()
+        )));
+    }
+
+    let stem = cert_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| Error::Certificate(format!("Invalid file path: {:?}", cert_path)))?;
+    let sibling = |suffix: &str| cert_path.with_file_name(format!("{}{}", stem, suffix));
+
+    let mut paths = vec![cert_path.to_path_buf()];
+    for path in [
+        sibling("-key.pem"),
+        sibling(".pem"),
+        sibling(".p12"),
+        sibling(".der"),
+    ] {
+        if path.is_file() && !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    // Refuse outright rather than remove only part of a CA's files
+    for path in &paths {
+        let is_cert = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("pem" | "der" | "crt")
+        );
+        if is_cert && is_ca_certificate_file(path)? {
+            return Err(Error::Certificate(format!(
+                "{} is a CA certificate; remove_cert only removes issued certificates",
+                path.display()
+            )));
+        }
+    }
+
+    for path in &paths {
+        fs::remove_file(path)?;
+    }
+    Ok(RemovedFiles { removed: paths })
+}
+
+/// Whether a PEM or DER file holds a CA certificate. Files without a
+/// certificate, such as keys, are not CA certificates.
+fn is_ca_certificate_file(path: &std::path::Path) -> Result<bool> {
+    let bytes = fs::read(path)?;
+    let der = parse_pem_cert(&bytes).unwrap_or(bytes);
+    Ok(x509_parser::parse_x509_certificate(&der)
+        .ok()
+        .and_then(|(_, cert)| {
+            cert.basic_constraints()
+                .ok()
+                .flatten()
+                .map(|bc| bc.value.ca)
+        })
+        .unwrap_or(false))
+}
+
+/// Pack the files of a generated certificate into a zip archive to share.
 ///
-/// `Ok(())` on success, or an error if generation fails.
+/// The archive holds the certificate, its key and PKCS#12 bundle, each of
+/// them if it was written, under their file names. With `include_ca` the
+/// root CA certificate from CAROOT is added too, so the recipient can trust
+/// it. The private key is included, so the archive is written with the same
+/// restrictive permissions as key files.
 ///
 /// # Errors
 ///
-/// Returns an error if:
-/// - The CA cannot be loaded or created
-/// - Domain validation fails
-/// - Certificate generation fails
-/// - File writing fails
-pub fn generate_certificate(
-    domains: &[String],
-    cert_file: Option<&str>,
-    key_file: Option<&str>,
-    p12_file: Option<&str>,
-    client: bool,
-    ecdsa: bool,
-    pkcs12: bool,
+/// Returns an error if a file cannot be read, the CA certificate does not
+/// exist, or the archive cannot be written.
+pub fn bundle_outputs(
+    result: &GenerationResult,
+    zip_path: &std::path::Path,
+    include_ca: bool,
 ) -> Result<()> {
-    // Load CA
-    let ca = crate::ca::CA::load_or_create()?;
-
-    // Get CA certificate and key PEMs
-    let ca_cert_pem = std::fs::read_to_string(ca.cert_path())?;
-    let ca_key_pem = std::fs::read_to_string(ca.key_path())?;
+    use std::io::Write;
+
+    let mut files = vec![result.cert_path.clone()];
+    files.extend(result.key_path.clone());
+    files.extend(result.p12_path.clone());
+    if include_ca {
+        let ca = crate::ca::CA::new(crate::fileutil::get_ca_root()?);
+        if !ca.cert_exists() {
+            return Err(Error::Certificate(format!(
+                "CA certificate {} does not exist",
+                ca.cert_path().display()
+            )));
+        }
+        files.push(ca.cert_path());
+    }
+    // In PKCS#12 mode the certificate path is the bundle itself
+    files.dedup();
 
-    // Build config
-    let mut config = CertificateConfig::new(domains.to_vec());
-    config.client_cert = client;
-    config.use_ecdsa = ecdsa;
-    config.pkcs12 = pkcs12;
-    config.cert_file = cert_file.map(PathBuf::from);
-    config.key_file = key_file.map(PathBuf::from);
-    config.p12_file = p12_file.map(PathBuf::from);
+    let zip_error = |e: zip::result::ZipError| {
+        Error::Certificate(format!("Failed to write {}: {}", zip_path.display(), e))
+    };
+    let mut zip = zip::ZipWriter::new(fs::File::create(zip_path)?);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .unix_permissions(0o600);
+    for file in &files {
+        let name = file
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| Error::Certificate(format!("Invalid file path: {:?}", file)))?;
+        zip.start_file(name, options).map_err(zip_error)?;
+        zip.write_all(&fs::read(file)?)?;
+    }
+    zip.finish().map_err(zip_error)?;
 
-    // Generate the certificate
-    generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem)
+    set_file_permissions(&zip_path.to_path_buf(), 0o600)
 }
 
 /// Read CSR file from disk
@@ -866,96 +3636,180 @@ pub fn read_csr_file(csr_path: &str) -> Result<Vec<u8>> {
 }
 
 /// Parse CSR from PEM format and return the DER bytes
+///
+/// The first `CERTIFICATE REQUEST` (or legacy `NEW CERTIFICATE REQUEST`)
+/// block is used; any other blocks in the file are ignored.
 pub fn parse_csr_pem(csr_bytes: &[u8]) -> Result<Vec<u8>> {
-    // Try to parse as PEM first
-    let pem_str = std::str::from_utf8(csr_bytes)
-        .map_err(|e| Error::Certificate(format!("Invalid UTF-8 in CSR file: {}", e)))?;
-
-    // Find the PEM block boundaries
-    let begin_marker = "-----BEGIN";
-    let end_marker = "-----END";
-
-    let begin_pos = pem_str
-        .find(begin_marker)
-        .ok_or_else(|| Error::Certificate("No PEM data found in CSR file".to_string()))?;
-    let end_pos = pem_str
-        .find(end_marker)
-        .ok_or_else(|| Error::Certificate("Invalid PEM format in CSR file".to_string()))?;
-
-    // Find the end of the final line (after END marker)
-    let mut final_pos = end_pos + end_marker.len();
-    while final_pos < pem_str.len() {
-        let ch = pem_str.as_bytes()[final_pos];
-        if ch == b'\n' || ch == b'\r' {
-            final_pos += 1;
-            break;
-        }
-        final_pos += 1;
-    }
-    let pem_block = &pem_str[begin_pos..final_pos];
-
-    // Parse using pem crate
-    let pem_data = ::pem::parse(pem_block.as_bytes())
-        .map_err(|e| Error::Certificate(format!("Failed to parse CSR PEM: {}", e)))?;
-
-    // Validate PEM type
-    if pem_data.tag() != "CERTIFICATE REQUEST" && pem_data.tag() != "NEW CERTIFICATE REQUEST" {
-        return Err(Error::Certificate(format!(
-            "Expected CERTIFICATE REQUEST, got {}",
-            pem_data.tag()
-        )));
-    }
-
-    // Return the DER bytes
-    Ok(pem_data.into_contents())
+    let blocks = parse_pem_blocks(csr_bytes)
+        .map_err(|e| Error::Certificate(format!("Invalid CSR file: {}", e)))?;
+
+    let csr = first_of_tag(&blocks, "CERTIFICATE REQUEST")
+        .or_else(|| first_of_tag(&blocks, "NEW CERTIFICATE REQUEST"))
+        .ok_or_else(|| {
+            Error::Certificate(format!(
+                "Expected CERTIFICATE REQUEST, got {}",
+                blocks[0].tag()
+            ))
+        })?;
+
+    Ok(csr.contents().to_vec())
 }
 
 /// Validate CSR signature
+///
+/// Verifies the CSR's self-signature, which proves the requester holds the
+/// private key matching the public key in the request.
 pub fn validate_csr_signature(
     csr: &x509_parser::certification_request::X509CertificationRequest,
 ) -> Result<()> {
-    // x509-parser 0.16 doesn't have verify_signature for CSR
-    // We'll do basic validation by checking that the CSR was parsed successfully
-    // The signature verification happens during parsing in x509-parser
-    // For now, we trust that the CSR is valid if it parsed correctly
-
     // Check that we have a valid public key
     if csr.certification_request_info.subject_pki.parsed().is_err() {
         return Err(Error::Certificate("Invalid public key in CSR".to_string()));
     }
 
-    Ok(())
+    csr.verify_signature()
+        .map_err(|e| Error::Certificate(format!("Invalid CSR signature: {}", e)))
 }
 
 /// Extract subject alternative names from CSR
+///
+/// Reads the SAN extension from the CSR's requested extensions. Falls back
+/// to the subject Common Name when the CSR requests no SANs.
 pub fn extract_san_from_csr(
     csr: &x509_parser::certification_request::X509CertificationRequest,
 ) -> Result<Vec<String>> {
+    use x509_parser::extensions::{GeneralName, ParsedExtension};
+
     let mut hosts = Vec::new();
     let req_info = &csr.certification_request_info;
 
-    // For now, just extract the Common Name from the subject
-    // Full SAN extraction from CSR extensions is complex and can be added later
-    if let Some(cn) = req_info.subject.iter_common_name().next()
+    for ext in csr.requested_extensions().into_iter().flatten() {
+        if let ParsedExtension::SubjectAlternativeName(san) = ext {
+            for name in &san.general_names {
+                match name {
+                    GeneralName::DNSName(dns) => hosts.push(dns.to_string()),
+                    GeneralName::RFC822Name(email) => hosts.push(email.to_string()),
+                    GeneralName::URI(uri) => hosts.push(uri.to_string()),
+                    GeneralName::IPAddress(bytes) => {
+                        let ip = match bytes.len() {
+                            4 => <[u8; 4]>::try_from(*bytes).map(IpAddr::from).ok(),
+                            16 => <[u8; 16]>::try_from(*bytes).map(IpAddr::from).ok(),
+                            _ => None,
+                        };
+                        let ip = ip.ok_or_else(|| {
+                            Error::Certificate("Invalid IP address in CSR".to_string())
+                        })?;
+                        hosts.push(ip.to_string());
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // Without SANs, fall back to the Common Name from the subject
+    if hosts.is_empty()
+        && let Some(cn) = req_info.subject.iter_common_name().next()
         && let Ok(cn_str) = cn.as_str()
     {
         hosts.push(cn_str.to_string());
     }
 
-    // If no CN found, return an error
+    // If no names found, return an error
     if hosts.is_empty() {
         return Err(Error::Certificate(
-            "No Common Name found in CSR subject".to_string(),
+            "No subject alternative names or Common Name found in CSR".to_string(),
         ));
     }
 
     Ok(hosts)
 }
 
+/// Generate a Certificate Signing Request and its private key.
+///
+/// The CSR requests one SAN per host, classified like hosts on the command
+/// line, and can be sent to an external CA or signed with [`sign_csr`].
+/// Without `subject`, the Common Name is set to the first host.
+///
+/// # Returns
+///
+/// The CSR as a `CERTIFICATE REQUEST` PEM and the private key as a PKCS#8
+/// PEM, in that order.
+///
+/// # Errors
+///
+/// Returns an error if no hosts are given, a host is invalid, or key
+/// generation or signing the request fails.
+pub fn generate_csr(
+    hosts: &[String],
+    subject: Option<SubjectFields>,
+    algo: KeyAlgorithm,
+) -> Result<(String, String)> {
+    if hosts.is_empty() {
+        return Err(Error::Certificate("No hosts specified".to_string()));
+    }
+
+    let mut params = CertificateParams::default();
+    params.subject_alt_names = build_san_list(hosts)?;
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    let subject = subject.unwrap_or_else(|| SubjectFields {
+        common_name: Some(hosts[0].clone()),
+        ..Default::default()
+    });
+    for (dn_type, value) in [
+        (rcgen::DnType::CommonName, subject.common_name),
+        (rcgen::DnType::OrganizationName, subject.organization),
+        (
+            rcgen::DnType::OrganizationalUnitName,
+            subject.organizational_unit,
+        ),
+    ] {
+        if let Some(value) = value {
+            params.distinguished_name.push(dn_type, value);
+        }
+    }
+
+    let key = algo.generate()?;
+    let csr = params
+        .serialize_request(&key)
+        .map_err(|e| Error::Certificate(format!("Failed to create CSR: {}", e)))?;
+    let csr_pem = csr
+        .pem()
+        .map_err(|e| Error::Certificate(format!("Failed to encode CSR: {}", e)))?;
+    Ok((csr_pem, key_to_pem(&key)?))
+}
+
+/// Sign a Certificate Signing Request (CSR) with the local CA.
+///
+/// Parses the CSR, verifies its signature (proof of possession of the
+/// private key), and issues a certificate for the CSR's public key and
+/// requested SANs. The certificate is written to the current directory using
+/// the usual naming convention (e.g. `example.com+1.pem`).
+///
+/// # Arguments
+///
+/// * `csr_pem` - The CSR in PEM format
+/// * `validity_days` - Certificate lifetime in days (default: 820)
+///
+/// # Returns
+///
+/// A `GenerationResult` for the issued certificate. No key is written since
+/// the requester keeps their own.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The CSR cannot be parsed or its signature is invalid
+/// - No subject names are found in the CSR
+/// - `validity_days` is zero
+/// - Certificate signing or writing fails
+pub fn sign_csr(csr_pem: &[u8], validity_days: Option<u32>) -> Result<GenerationResult> {
+    issue_from_csr(csr_pem, validity_days, None)
+}
+
 /// Generate a certificate from a Certificate Signing Request (CSR).
 ///
-/// Reads a CSR file, extracts the subject names and public key,
-/// and generates a signed certificate using the local CA.
+/// Reads a CSR file and signs it with the local CA (see [`sign_csr`]).
 ///
 /// # Arguments
 ///
@@ -974,8 +3828,25 @@ pub fn extract_san_from_csr(
 /// - No subject names are found in the CSR
 /// - Certificate generation or signing fails
 pub fn generate_from_csr(csr_path: &str, cert_file: Option<&str>) -> Result<()> {
+    let csr_bytes = read_csr_file(csr_path)?;
+    issue_from_csr(&csr_bytes, None, cert_file.map(PathBuf::from))?;
+    Ok(())
+}
+
+/// Issue a certificate for a CSR and write it to disk
+fn issue_from_csr(
+    csr_bytes: &[u8],
+    validity_days: Option<u32>,
+    cert_file: Option<PathBuf>,
+) -> Result<GenerationResult> {
     use x509_parser::prelude::*;
 
+    if validity_days == Some(0) {
+        return Err(Error::Certificate(
+            "Certificate validity must be at least one day".to_string(),
+        ));
+    }
+
     // Load CA
     let ca = crate::ca::CA::load_or_create()?;
 
@@ -984,49 +3855,41 @@ pub fn generate_from_csr(csr_path: &str, cert_file: Option<&str>) -> Result<()>
         return Err(Error::CAKeyMissing);
     }
 
-    // Read and parse CSR
-    let csr_bytes = read_csr_file(csr_path)?;
-    let csr_der = parse_csr_pem(&csr_bytes)?;
-
-    // Parse the CSR DER
+    // Parse the CSR
+    let csr_der = parse_csr_pem(csr_bytes)?;
     let (_, csr) = X509CertificationRequest::from_der(&csr_der)
         .map_err(|e| Error::Certificate(format!("Failed to parse CSR: {}", e)))?;
 
     // Validate CSR signature
+    crate::log_event("csr", "Verifying CSR signature");
     validate_csr_signature(&csr)?;
 
     // Extract hosts from CSR
     let hosts = extract_san_from_csr(&csr)?;
 
-    if hosts.is_empty() {
-        return Err(Error::Certificate(
-            "No subject names found in CSR".to_string(),
-        ));
-    }
-
-    // Get CA cert and key for signing
-    let ca_cert_pem = std::fs::read_to_string(ca.cert_path())?;
-    let ca_key_pem = std::fs::read_to_string(ca.key_path())?;
-
-    // TODO: CSR handling needs proper public key extraction
-    // For now, generate a new key pair (this is a workaround)
-    let cert_key_pair = KeyPair::generate_rsa_for(&PKCS_RSA_SHA256, RsaKeySize::_2048)
-        .map_err(|e| Error::Certificate(format!("Failed to generate key pair: {}", e)))?;
+    // Use the public key from the CSR so the requester's private key matches
+    let public_key =
+        rcgen::SubjectPublicKeyInfo::from_der(csr.certification_request_info.subject_pki.raw)
+            .map_err(|e| Error::Certificate(format!("Unsupported CSR public key: {}", e)))?;
 
-    let ca_key_pair = KeyPair::from_pem(&ca_key_pem)
-        .map_err(|e| Error::Certificate(format!("Failed to parse CA key: {}", e)))?;
+    // Get CA cert and key for signing (the intermediate, if present)
+    let (ca_cert_pem, ca_key_pem) = ca.signing_pems()?;
 
-    let issuer = Issuer::from_ca_cert_pem(&ca_cert_pem, ca_key_pair)
-        .map_err(|e| Error::Certificate(format!("Failed to create issuer: {}", e)))?;
+    let issuer = crate::ca::load_issuer(&ca_cert_pem, &ca_key_pem)?;
 
     // Create certificate parameters from CSR
     let mut params = create_cert_params(&hosts)?;
+    params.serial_number = Some(SerialNumber::from_slice(&ca.next_serial()?));
+    if let Some(days) = validity_days {
+        params.not_after = params.not_before + Duration::days(i64::from(days));
+    }
+    cap_to_issuer(&mut params, &ca_cert_pem)?;
 
     // Set extended key usage based on what's in the CSR
     // Always add ServerAuth for TLS compatibility
     add_server_auth(&mut params);
 
-    // Add ClientAuth if requested via extension or if email addresses present
+    // Add EmailProtection if email addresses present
     let has_email = hosts.iter().any(|h| h.contains('@'));
     if has_email {
         add_email_protection(&mut params);
@@ -1038,25 +3901,24 @@ pub fn generate_from_csr(csr_path: &str, cert_file: Option<&str>) -> Result<()>
 
     // Create signed certificate
     let cert = params
-        .signed_by(&cert_key_pair, &issuer)
+        .signed_by(&public_key, &issuer)
         .map_err(|e| Error::Certificate(format!("Failed to create signed certificate: {}", e)))?;
 
     let cert_der = cert.der().to_vec();
+    ensure_subject_differs_from_issuer(&cert_der)?;
 
     // Determine output file name
     let output_file = if let Some(file) = cert_file {
-        PathBuf::from(file)
+        file
     } else {
         // Generate filename from hosts
-        let mut config = CertificateConfig::new(hosts.clone());
-        config.cert_file = None;
-        config.key_file = None;
+        let config = CertificateConfig::new(hosts.clone());
         let (cert_path, _, _) = generate_file_names(&config);
         cert_path
     };
 
     // Write certificate (PEM format)
-    let cert_pem = cert_to_pem(&cert_der);
+    let cert_pem = cert_chain_pem(&cert_der, &ca_cert_pem)?;
     fs::write(&output_file, cert_pem.as_bytes()).map_err(Error::Io)?;
     set_file_permissions(&output_file, 0o644)?;
 
@@ -1065,11 +3927,22 @@ pub fn generate_from_csr(csr_path: &str, cert_file: Option<&str>) -> Result<()>
     println!("\nThe certificate is at {:?}\n", output_file);
 
     // Print expiration date
-    let expiration = calculate_cert_expiration();
+    let expiration = params.not_after;
     check_cert_expiry_warning(expiration);
-    println!("It will expire on {}\n", format_expiration_date(expiration));
-
-    Ok(())
+    println!("It {}\n", format_expiry_relative(expiration));
+
+    let result = GenerationResult {
+        cert_path: output_file,
+        key_path: None,
+        p12_path: None,
+        serial: cert_serial_hex(&cert_der)?,
+        hosts,
+        not_after: expiration,
+        fingerprint: crate::ca::sha256_fingerprint(&cert_der),
+        timings: None,
+    };
+    crate::audit::record(ca.root_path(), &result)?;
+    Ok(result)
 }
 
 /// Copy subject from X509Name to rcgen DistinguishedName
@@ -1108,46 +3981,105 @@ fn copy_subject_to_params(
 
 /// Generate and save a new certificate signed by the CA
 /// This is the main certificate generation function that orchestrates everything
-fn generate_certificate_internal(
+pub(crate) fn generate_certificate_internal(
     config: &CertificateConfig,
     ca_cert_pem: &str,
     ca_key_pem: &str,
-) -> Result<()> {
+    serial: [u8; 16],
+) -> Result<GenerationResult> {
     if config.hosts.is_empty() {
         return Err(Error::Certificate("No hosts specified".to_string()));
     }
+    validate_file_modes(config)?;
+
+    crate::log_event(
+        "generate",
+        &format!("Generating certificate for {}", config.hosts.join(", ")),
+    );
+
+    let mut timings = Timings::default();
 
-    // Generate key pair based on config (RSA-2048 or ECDSA P-256)
+    // Generate key pair based on config (RSA-2048 or ECDSA P-256, or
+    // whatever the CA uses when inheriting)
+    let started = std::time::Instant::now();
     let cert_key_pair = if config.use_ecdsa {
         KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256)
             .map_err(|e| Error::Certificate(format!("Failed to generate ECDSA key pair: {}", e)))?
+    } else if config.inherit_ca_algorithm {
+        issuer_key_algorithm(ca_cert_pem)?.generate()?
     } else {
         KeyPair::generate_rsa_for(&PKCS_RSA_SHA256, RsaKeySize::_2048)
             .map_err(|e| Error::Certificate(format!("Failed to generate RSA key pair: {}", e)))?
     };
-
-    // Parse CA key pair
-    let ca_key_pair = KeyPair::from_pem(ca_key_pem)
-        .map_err(|e| Error::Certificate(format!("Failed to parse CA key: {}", e)))?;
+    timings.key_gen = started.elapsed();
 
     // Create issuer from CA certificate and key
-    let issuer = Issuer::from_ca_cert_pem(ca_cert_pem, ca_key_pair)
+    let started = std::time::Instant::now();
+    let mut issuer = crate::ca::load_issuer(ca_cert_pem, ca_key_pem)?;
+    if let Some(hash) = config.hash {
+        if config.signature_algorithm == SignatureAlgorithm::RsaPss && hash != HashAlgorithm::Sha256
+        {
+            return Err(Error::Certificate(
+                "RSA-PSS signatures always use SHA-256".to_string(),
+            ));
+        }
+        let key_pair = signing_key_with_hash(issuer.key(), hash)?;
+        issuer = rcgen::Issuer::from_ca_cert_der(
+            &parse_pem_cert(ca_cert_pem.as_bytes())?.into(),
+            key_pair,
+        )
         .map_err(|e| Error::Certificate(format!("Failed to create issuer from CA cert: {}", e)))?;
+    }
 
     // Create certificate parameters
-    let mut params = create_cert_params(&config.hosts)?;
+    let host_types = config.resolved_host_types()?;
+    if config.omit_san {
+        validate_omit_san(&host_types)?;
+    }
+    validate_upn_config(config)?;
+    if config.pkcs12 && config.key_passphrase.is_some() {
+        return Err(Error::Certificate(
+            "A key passphrase only applies to PEM output; PKCS#12 bundles have their own password"
+                .to_string(),
+        ));
+    }
+    let mut params = match &config.host_types {
+        Some(host_types) => create_cert_params_from_types(host_types)?,
+        None => create_cert_params(&config.hosts)?,
+    };
+    params.serial_number = Some(SerialNumber::from_slice(&serial));
+    if let Some(backdate) = config.backdate {
+        params.not_before = OffsetDateTime::now_utc() - backdate;
+    }
+    if let Some(not_before) = config.not_before {
+        params.not_before = not_before;
+    }
+    if let Some(days) = config.validity_days {
+        params.not_after = params.not_before + Duration::days(i64::from(days));
+    }
+    if let Some(not_after) = config.not_after {
+        params.not_after = not_after;
+    }
+    cap_to_issuer(&mut params, ca_cert_pem)?;
+    validate_validity_window(params.not_before, params.not_after)?;
 
     // Set extended key usage based on certificate type
     if config.client_cert {
         add_client_auth(&mut params);
     }
 
+    // Smartcard logon style clients look the user up by UPN
+    if let Some(upn) = &config.upn {
+        params
+            .subject_alt_names
+            .push(SanType::OtherName((UPN_OID.to_vec(), upn.as_str().into())));
+    }
+
     // Check if we have IP addresses, DNS names, or URIs for server auth
-    let has_server_names = config.hosts.iter().any(|h| {
-        let host_type = HostType::parse(h).ok();
+    let has_server_names = host_types.iter().any(|h| {
         matches!(
-            host_type,
-            Some(HostType::DnsName(_)) | Some(HostType::IpAddress(_)) | Some(HostType::Uri(_))
+            h,
+            HostType::DnsName(_) | HostType::IpAddress(_) | HostType::Uri(_)
         )
     });
 
@@ -1156,29 +4088,53 @@ fn generate_certificate_internal(
     }
 
     // Check if we have email addresses for email protection
-    let has_email = config
-        .hosts
-        .iter()
-        .any(|h| matches!(HostType::parse(h).ok(), Some(HostType::Email(_))));
+    let has_email = host_types.iter().any(|h| matches!(h, HostType::Email(_)));
 
     if has_email {
         add_email_protection(&mut params);
     }
 
-    // If generating PKCS#12, set the CommonName to the first host (for IIS compatibility)
-    if config.pkcs12 {
+    // Point clients at the OCSP responder and CA issuer if requested
+    if let Some(aia) =
+        build_authority_info_access(config.ocsp_url.as_deref(), config.issuer_url.as_deref())?
+    {
+        params.custom_extensions.push(aia);
+    }
+
+    // Advertise where clients can fetch the CRL
+    if let Some(url) = &config.crl_url {
+        validate_uri(url)?;
+        params.crl_distribution_points = vec![CrlDistributionPoint {
+            uris: vec![url.clone()],
+        }];
+    }
+
+    // Legacy clients read the name from the CN alone
+    if config.omit_san {
+        params.subject_alt_names.clear();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, config.hosts[0].clone());
+    } else if config.pkcs12 {
+        // If generating PKCS#12, set the CommonName to the first host (for IIS compatibility)
         params
             .distinguished_name
             .push(rcgen::DnType::CommonName, config.hosts[0].clone());
     }
 
     // Create the certificate signed by the CA
+    crate::log_event("generate", "Signing certificate");
     let cert = params
         .signed_by(&cert_key_pair, &issuer)
         .map_err(|e| Error::Certificate(format!("Failed to create signed certificate: {}", e)))?;
 
     // Get certificate DER
-    let cert_der = cert.der().to_vec();
+    let cert_der = match config.signature_algorithm {
+        SignatureAlgorithm::Standard => cert.der().to_vec(),
+        SignatureAlgorithm::RsaPss => resign_rsa_pss(cert.der(), issuer.key())?,
+    };
+    ensure_subject_differs_from_issuer(&cert_der)?;
+    timings.sign = started.elapsed();
 
     // Get CA cert DER for PKCS#12 - parse from PEM
     let ca_cert_pem_parsed = pem::parse(ca_cert_pem)
@@ -1187,17 +4143,50 @@ fn generate_certificate_internal(
 
     // Get file names
     let (cert_file, key_file, p12_file) = generate_file_names(config);
+    crate::log_event("generate", "Writing certificate files");
+    let started = std::time::Instant::now();
 
     // Write files based on mode
     if !config.pkcs12 {
         // PEM mode
-        let cert_pem = cert_to_pem(&cert_der);
-        let key_pem = key_to_pem(&cert_key_pair)?;
-        write_pem_files(&cert_file, &key_file, &cert_pem, &key_pem)?;
+        let cert_pem = cert_chain_pem(&cert_der, ca_cert_pem)?;
+        let key_pem = match &config.key_passphrase {
+            Some(passphrase) => key_to_encrypted_pem(&cert_key_pair, passphrase)?,
+            None => key_to_pem(&cert_key_pair)?,
+        };
+        write_pem_files_ordered(
+            &cert_file,
+            &key_file,
+            &cert_pem,
+            &key_pem,
+            config.combined_order,
+        )?;
+
+        // Apply custom permissions; a combined file holds the key so uses the key mode
+        if let Some(mode) = config.cert_mode
+            && cert_file != key_file
+        {
+            set_file_permissions(&cert_file, mode)?;
+        }
+        if let Some(mode) = config.key_mode {
+            set_file_permissions(&key_file, mode)?;
+        }
     } else {
         // PKCS#12 mode
-        write_pkcs12_file(&p12_file, &cert_der, &cert_key_pair, &ca_cert_der)?;
+        write_pkcs12_file(
+            &p12_file,
+            &cert_der,
+            &cert_key_pair,
+            config.p12_include_ca.then_some(ca_cert_der.as_slice()),
+            config.p12_encryption,
+        )?;
+
+        // The bundle contains the private key, so it follows the key mode
+        if let Some(mode) = config.key_mode {
+            set_file_permissions(&p12_file, mode)?;
+        }
     }
+    timings.write = started.elapsed();
 
     // Print certificate information
     print_hosts(&config.hosts);
@@ -1221,22 +4210,43 @@ fn generate_certificate_internal(
         }
     } else {
         println!("\n{} {:?}", "The PKCS#12 bundle is at".green(), p12_file);
+        let scheme = match config.p12_encryption {
+            P12Encryption::Legacy => "legacy",
+            P12Encryption::Modern => "AES-256",
+        };
         println!(
-            "\n{} The legacy PKCS#12 encryption password is the often hardcoded default \"changeit\"\n",
-            "Info:".cyan()
+            "\n{} The {} PKCS#12 encryption password is the often hardcoded default \"{}\"\n",
+            "Info:".cyan(),
+            scheme,
+            PKCS12_PASSWORD
         );
     }
 
     // Print expiration date
-    let expiration = calculate_cert_expiration();
+    let expiration = params.not_after;
     check_cert_expiry_warning(expiration);
     println!(
         "{} {}\n",
-        "It will expire on".bright_white(),
-        format_expiration_date(expiration)
+        "It".bright_white(),
+        format_expiry_relative(expiration)
     );
 
-    Ok(())
+    let (cert_path, key_path, p12_path) = if config.pkcs12 {
+        (p12_file.clone(), None, Some(p12_file))
+    } else {
+        (cert_file, Some(key_file), None)
+    };
+
+    Ok(GenerationResult {
+        cert_path,
+        key_path,
+        p12_path,
+        hosts: config.hosts.clone(),
+        serial: cert_serial_hex(&cert_der)?,
+        not_after: expiration,
+        fingerprint: crate::ca::sha256_fingerprint(&cert_der),
+        timings: timings_enabled().then_some(timings),
+    })
 }
 
 #[cfg(test)]
@@ -1297,6 +4307,16 @@ mod tests {
     fn test_invalid_hostname() {
         assert!(validate_hostname("").is_err());
         assert!(validate_hostname("..").is_err());
+        assert!(validate_hostname("example..com").is_err());
+        assert!(validate_hostname(".example.com").is_err());
+        assert!(validate_hostname("example.com.").is_err());
+        assert!(validate_hostname("*..example.com").is_err());
+    }
+
+    #[test]
+    fn test_validate_hostname_underscore_and_hyphen() {
+        assert!(validate_hostname("my-host_1.example.com").is_ok());
+        assert!(validate_hostname("_service._tcp.example.com").is_ok());
     }
 
     #[test]
@@ -1341,6 +4361,60 @@ mod tests {
         assert_eq!(p12, PathBuf::from("./localhost_8080.p12"));
     }
 
+    #[test]
+    fn test_file_naming_ip_and_email() {
+        let config = CertificateConfig::new(vec!["127.0.0.1".to_string()]);
+        let (cert, _, _) = generate_file_names(&config);
+        assert_eq!(cert, PathBuf::from("./127.0.0.1.pem"));
+
+        let config = CertificateConfig::new(vec!["::1".to_string()]);
+        let (cert, key, p12) = generate_file_names(&config);
+        assert_eq!(cert, PathBuf::from("./__1.pem"));
+        assert_eq!(key, PathBuf::from("./__1-key.pem"));
+        assert_eq!(p12, PathBuf::from("./__1.p12"));
+
+        let config = CertificateConfig::new(vec!["user@example.com".to_string()]);
+        let (cert, key, _) = generate_file_names(&config);
+        assert_eq!(cert, PathBuf::from("./user_at_example.com.pem"));
+        assert_eq!(key, PathBuf::from("./user_at_example.com-key.pem"));
+    }
+
+    #[test]
+    fn test_sanitize_filename_wildcard() {
+        assert_eq!(sanitize_filename("*.example.com"), "_wildcard.example.com");
+    }
+
+    #[test]
+    fn test_sanitize_filename_ipv6() {
+        assert_eq!(sanitize_filename("::1"), "__1");
+        assert_eq!(sanitize_filename("fe80::1"), "fe80__1");
+    }
+
+    #[test]
+    fn test_sanitize_filename_email() {
+        assert_eq!(sanitize_filename("user@example.com"), "user_at_example.com");
+    }
+
+    #[test]
+    fn test_sanitize_filename_uri() {
+        assert_eq!(
+            sanitize_filename("https://example.com/a?b"),
+            "https___example.com_a_b"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_filename_windows_reserved() {
+        assert_eq!(sanitize_filename("a<b>c|d\"e\\f"), "a_b_c_d_e_f");
+        assert_eq!(sanitize_filename("tab\there"), "tab_here");
+        assert_eq!(sanitize_filename("con"), "_con");
+        assert_eq!(sanitize_filename("COM1.local"), "_COM1.local");
+        assert_eq!(sanitize_filename("lpt9"), "_lpt9");
+        assert_eq!(sanitize_filename("com10"), "com10");
+        assert_eq!(sanitize_filename("example.com. "), "example.com");
+        assert_eq!(sanitize_filename("..."), "cert");
+    }
+
     #[test]
     fn test_file_naming_client_cert() {
         let mut config = CertificateConfig::new(vec!["example.com".to_string()]);
@@ -1390,7 +4464,12 @@ mod tests {
         config.key_file = Some(key_path.clone());
 
         // Generate the certificate
-        let result = generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem);
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        );
         assert!(
             result.is_ok(),
             "Certificate generation failed: {:?}",
@@ -1448,7 +4527,12 @@ mod tests {
         config.cert_file = Some(combined_path.clone());
         config.key_file = Some(combined_path.clone());
 
-        let result = generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem);
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        );
         assert!(
             result.is_ok(),
             "Certificate generation failed: {:?}",
@@ -1479,6 +4563,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_certificate_generation_combined_key_first() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+
+        let combined_path = temp_dir.path().join("haproxy.pem");
+        let mut config = CertificateConfig::new(vec!["localhost".to_string()]);
+        config.use_ecdsa = true;
+        config.cert_file = Some(combined_path.clone());
+        config.key_file = Some(combined_path.clone());
+        config.combined_order = CombinedOrder::KeyFirst;
+
+        generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem, generate_serial_number())
+            .unwrap();
+
+        let combined_pem = fs::read_to_string(&combined_path).unwrap();
+        let key_at = combined_pem.find("BEGIN PRIVATE KEY").unwrap();
+        let cert_at = combined_pem.find("BEGIN CERTIFICATE").unwrap();
+        assert!(
+            key_at < cert_at,
+            "Private key should precede the certificate"
+        );
+    }
+
     #[test]
     fn test_csr_file_reading() {
         use std::io::Write;
@@ -1587,7 +4698,12 @@ Hdy3K7d4rELyODVkKr06Q+NjLKWrNWWUlWCsFfh/xeU=
         config.cert_file = Some(cert_path.clone());
         config.key_file = Some(key_path.clone());
 
-        let result = generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem);
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        );
         assert!(
             result.is_ok(),
             "End-to-end certificate generation failed: {:?}",
@@ -1639,23 +4755,134 @@ Hdy3K7d4rELyODVkKr06Q+NjLKWrNWWUlWCsFfh/xeU=
     }
 
     #[test]
-    fn test_calculate_cert_expiration() {
-        let expiration = calculate_cert_expiration();
-        let now = OffsetDateTime::now_utc();
-        let diff = expiration - now;
+    fn test_format_serial() {
+        assert_eq!(format_serial(&[0x0a]), "0A");
+        assert_eq!(format_serial(&[0x00, 0x80, 0x01]), "8001");
+        assert_eq!(format_serial(&[0x00, 0x00]), "00");
+        assert_eq!(format_serial(&[0x7c, 0x8b, 0x2f]), "7C8B2F");
+    }
+
+    #[test]
+    fn test_calculate_cert_expiration() {
+        let expiration = calculate_cert_expiration();
+        let now = OffsetDateTime::now_utc();
+        let diff = expiration - now;
+
+        // Should be approximately 820 days (730 + 90)
+        assert!(diff.whole_days() >= 819 && diff.whole_days() <= 821);
+    }
+
+    #[test]
+    fn test_format_expiration_date() {
+        let now = OffsetDateTime::now_utc();
+        let formatted = format_expiration_date(now);
+
+        // Should contain common date elements
+        assert!(!formatted.is_empty());
+        assert!(formatted.len() > 10);
+    }
+
+    #[test]
+    fn test_format_expiry_relative_future() {
+        let expiration = OffsetDateTime::now_utc() + Duration::days(820) + Duration::hours(1);
+        let formatted = format_expiry_relative(expiration);
+        assert!(
+            formatted.starts_with("expires in 2 years, 3 months ("),
+            "{}",
+            formatted
+        );
+        assert!(formatted.contains(&expiration.year().to_string()));
+
+        let formatted = format_expiry_relative(OffsetDateTime::now_utc() + Duration::hours(2));
+        assert!(
+            formatted.starts_with("expires in less than a day"),
+            "{}",
+            formatted
+        );
+    }
+
+    #[test]
+    fn test_format_expiry_relative_past() {
+        let expiration = OffsetDateTime::now_utc() - Duration::days(5) - Duration::hours(1);
+        let formatted = format_expiry_relative(expiration);
+        assert!(
+            formatted.starts_with("expired 5 days ago ("),
+            "{}",
+            formatted
+        );
+
+        let expiration = OffsetDateTime::now_utc() - Duration::days(366) - Duration::hours(1);
+        let formatted = format_expiry_relative(expiration);
+        assert!(
+            formatted.starts_with("expired 1 year, 1 day ago"),
+            "{}",
+            formatted
+        );
+    }
+
+    #[test]
+    fn test_is_wildcard() {
+        assert!(is_wildcard("*.example.com"));
+        assert!(is_wildcard("*.sub.example.com"));
+        assert!(!is_wildcard("example.com"));
+        assert!(!is_wildcard("a*.example.com"));
+        assert!(!is_wildcard("::1"));
+        assert!(!is_wildcard("spiffe://x/y"));
+    }
+
+    #[test]
+    fn test_is_ip() {
+        assert!(is_ip("127.0.0.1"));
+        assert!(is_ip("::1"));
+        assert!(is_ip("fe80::1%eth0"));
+        assert!(!is_ip("10.0.0.0/8"));
+        assert!(!is_ip("0.0.0.0"));
+        assert!(!is_ip("example.com"));
+        assert!(!is_ip("*.example.com"));
+    }
 
-        // Should be approximately 820 days (730 + 90)
-        assert!(diff.whole_days() >= 819 && diff.whole_days() <= 821);
+    #[test]
+    fn test_is_email() {
+        assert!(is_email("user@example.com"));
+        assert!(is_email("user@localhost"));
+        assert!(!is_email("1@2"));
+        assert!(!is_email("example.com"));
+        assert!(!is_email("https://user@example.com"));
+        assert!(!is_email("::1"));
     }
 
     #[test]
-    fn test_format_expiration_date() {
-        let now = OffsetDateTime::now_utc();
-        let formatted = format_expiration_date(now);
+    fn test_is_uri() {
+        assert!(is_uri("spiffe://x/y"));
+        assert!(is_uri("https://user@example.com/path"));
+        assert!(!is_uri("example.com"));
+        assert!(!is_uri("user@example.com"));
+        assert!(!is_uri("*.example.com"));
+    }
 
-        // Should contain common date elements
-        assert!(!formatted.is_empty());
-        assert!(formatted.len() > 10);
+    #[test]
+    fn test_wildcard_levels() {
+        let sans = wildcard_levels("example.com", &["sub", "api.sub", "sub"]).unwrap();
+        assert_eq!(
+            sans,
+            [
+                "*.example.com",
+                "*.sub.example.com",
+                "*.api.sub.example.com"
+            ]
+        );
+        assert!(build_san_list(&sans).is_ok());
+        assert_eq!(
+            wildcard_levels("example.com", &[]).unwrap(),
+            ["*.example.com"]
+        );
+
+        // Double wildcards and wildcard bases are rejected
+        assert!(wildcard_levels("example.com", &["*"]).is_err());
+        assert!(wildcard_levels("example.com", &["*.sub"]).is_err());
+        assert!(wildcard_levels("*.example.com", &["sub"]).is_err());
+        assert!(wildcard_levels("example.com", &[""]).is_err());
+        assert!(wildcard_levels("bad host", &["sub"]).is_err());
     }
 
     #[test]
@@ -1703,6 +4930,40 @@ Hdy3K7d4rELyODVkKr06Q+NjLKWrNWWUlWCsFfh/xeU=
         assert!(validate_email_address("test @example.com").is_err());
     }
 
+    #[test]
+    fn test_email_versus_ip_classification() {
+        // Neither side of `1@2` is a mailbox: the domain is a bare number
+        assert!(matches!(
+            HostType::parse("1@2"),
+            Err(Error::InvalidEmail(_))
+        ));
+        assert!(HostType::parse("1.2.3.4@5.6.7.8").is_err());
+        assert!(HostType::parse("1.2.3.4@example.com").is_err());
+        assert!(HostType::parse("::1@example.com").is_err());
+
+        // localhost is the only single-label domain accepted
+        assert_eq!(
+            HostType::parse("user@localhost").unwrap(),
+            HostType::Email("user@localhost".to_string())
+        );
+        assert!(HostType::parse("user@host").is_err());
+
+        assert_eq!(
+            HostType::parse("a@b.c").unwrap(),
+            HostType::Email("a@b.c".to_string())
+        );
+        assert_eq!(
+            HostType::parse("1@example.com").unwrap(),
+            HostType::Email("1@example.com".to_string())
+        );
+
+        // A URI with user info is still a URI
+        assert!(matches!(
+            HostType::parse("https://user@example.com/"),
+            Ok(HostType::Uri(_))
+        ));
+    }
+
     #[test]
     fn test_uri_validation() {
         // Valid URIs
@@ -1853,7 +5114,12 @@ Hdy3K7d4rELyODVkKr06Q+NjLKWrNWWUlWCsFfh/xeU=
                 config.cert_file = Some(cert_path.clone());
                 config.key_file = Some(key_path.clone());
 
-                let result = generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem);
+                let result = generate_certificate_internal(
+                    &config,
+                    &ca_cert_pem,
+                    &ca_key_pem,
+                    generate_serial_number(),
+                );
                 assert!(result.is_ok(), "Concurrent certificate generation failed");
 
                 // Verify files exist
@@ -1893,7 +5159,8 @@ Hdy3K7d4rELyODVkKr06Q+NjLKWrNWWUlWCsFfh/xeU=
         config.cert_file = Some(cert_path.clone());
         config.key_file = Some(key_path.clone());
 
-        generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem).unwrap();
+        generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem, generate_serial_number())
+            .unwrap();
 
         // Read the generated certificate
         let cert_pem = fs::read_to_string(&cert_path).unwrap();
@@ -1926,7 +5193,12 @@ Hdy3K7d4rELyODVkKr06Q+NjLKWrNWWUlWCsFfh/xeU=
         config.cert_file = Some(temp_dir.path().join("multi.pem"));
         config.key_file = Some(temp_dir.path().join("multi-key.pem"));
 
-        let result = generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem);
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        );
         assert!(result.is_ok(), "Multi-domain certificate generation failed");
     }
 
@@ -1949,7 +5221,12 @@ Hdy3K7d4rELyODVkKr06Q+NjLKWrNWWUlWCsFfh/xeU=
         config.cert_file = Some(temp_dir.path().join("ipv6.pem"));
         config.key_file = Some(temp_dir.path().join("ipv6-key.pem"));
 
-        let result = generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem);
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        );
         assert!(result.is_ok(), "IPv6 certificate generation failed");
     }
 
@@ -1968,7 +5245,12 @@ Hdy3K7d4rELyODVkKr06Q+NjLKWrNWWUlWCsFfh/xeU=
         config.cert_file = Some(temp_dir.path().join("wildcard.pem"));
         config.key_file = Some(temp_dir.path().join("wildcard-key.pem"));
 
-        let result = generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem);
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        );
         assert!(result.is_ok(), "Wildcard certificate generation failed");
     }
 
@@ -1988,7 +5270,12 @@ Hdy3K7d4rELyODVkKr06Q+NjLKWrNWWUlWCsFfh/xeU=
         config.cert_file = Some(temp_dir.path().join("client.pem"));
         config.key_file = Some(temp_dir.path().join("client-key.pem"));
 
-        let result = generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem);
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        );
         assert!(result.is_ok(), "Client certificate generation failed");
     }
 
@@ -2007,10 +5294,613 @@ Hdy3K7d4rELyODVkKr06Q+NjLKWrNWWUlWCsFfh/xeU=
         config.pkcs12 = true;
         config.p12_file = Some(temp_dir.path().join("example.p12"));
 
-        let result = generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem);
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        );
         assert!(result.is_ok(), "PKCS#12 export failed");
 
         let p12_path = temp_dir.path().join("example.p12");
         assert!(p12_path.exists(), "PKCS#12 file was not created");
     }
+
+    #[test]
+    fn test_build_authority_info_access() {
+        assert!(build_authority_info_access(None, None).unwrap().is_none());
+
+        let ext = build_authority_info_access(
+            Some("http://127.0.0.1:8888"),
+            Some("http://127.0.0.1:8888/ca.crt"),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            ext.oid_components().collect::<Vec<_>>(),
+            vec![1, 3, 6, 1, 5, 5, 7, 1, 1]
+        );
+        assert!(!ext.criticality());
+
+        assert!(build_authority_info_access(Some("not a url"), None).is_err());
+    }
+
+    #[test]
+    fn test_key_algorithm_from_str() {
+        assert_eq!(
+            "rsa".parse::<KeyAlgorithm>().unwrap(),
+            KeyAlgorithm::Rsa3072
+        );
+        assert_eq!(
+            "RSA2048".parse::<KeyAlgorithm>().unwrap(),
+            KeyAlgorithm::Rsa2048
+        );
+        assert_eq!(
+            "ecdsa-p384".parse::<KeyAlgorithm>().unwrap(),
+            KeyAlgorithm::EcdsaP384
+        );
+        assert_eq!(
+            "p256".parse::<KeyAlgorithm>().unwrap(),
+            KeyAlgorithm::EcdsaP256
+        );
+        assert_eq!(
+            "ed25519".parse::<KeyAlgorithm>().unwrap(),
+            KeyAlgorithm::Ed25519
+        );
+        assert!("dsa".parse::<KeyAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn test_inspect_certificate_key_identifiers() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+
+        let mut config =
+            CertificateConfig::new(vec!["example.com".to_string(), "127.0.0.1".to_string()]);
+        config.cert_file = Some(temp_dir.path().join("cert.pem"));
+        config.key_file = Some(temp_dir.path().join("key.pem"));
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        )
+        .unwrap();
+
+        let ca_info = inspect_certificate(ca_cert_pem.as_bytes()).unwrap();
+        let leaf_info = inspect_certificate(&fs::read(&result.cert_path).unwrap()).unwrap();
+
+        assert!(ca_info.is_ca);
+        assert!(!leaf_info.is_ca);
+        assert_eq!(leaf_info.dns_names, vec!["example.com"]);
+        assert_eq!(
+            leaf_info.ip_addresses,
+            vec!["127.0.0.1".parse::<IpAddr>().unwrap()]
+        );
+        assert_eq!(leaf_info.serial, result.serial);
+        assert!(leaf_info.subject_key_id.is_some());
+        assert!(ca_info.subject_key_id.is_some());
+        assert_eq!(leaf_info.authority_key_id, ca_info.subject_key_id);
+    }
+
+    #[test]
+    fn test_inspect_certificate_json() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+
+        let config = CertificateConfig::builder()
+            .hosts(["example.com", "::1"])
+            .cert_file(temp_dir.path().join("cert.pem"))
+            .key_file(temp_dir.path().join("key.pem"))
+            .build()
+            .unwrap();
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        )
+        .unwrap();
+
+        let json = inspect_certificate_json(&fs::read(&result.cert_path).unwrap()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let not_after = value["not_after"].as_str().unwrap();
+        assert!(
+            Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?Z$")
+                .unwrap()
+                .is_match(not_after),
+            "{}",
+            not_after
+        );
+        assert_eq!(value["san_dns"], serde_json::json!(["example.com"]));
+        assert_eq!(value["san_ip"], serde_json::json!(["::1"]));
+        assert_eq!(value["serial"], result.serial.as_str());
+        assert_eq!(value["is_ca"], false);
+    }
+
+    #[test]
+    fn test_not_before_is_backdated() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+
+        for (backdate, expected) in [
+            (None, Duration::minutes(60)),
+            (Some(Duration::minutes(10)), Duration::minutes(10)),
+        ] {
+            let mut builder = CertificateConfig::builder()
+                .host("example.local")
+                .validity_days(30)
+                .cert_file(temp_dir.path().join("cert.pem"))
+                .key_file(temp_dir.path().join("key.pem"));
+            if let Some(backdate) = backdate {
+                builder = builder.backdate(backdate);
+            }
+            let config = builder.build().unwrap();
+            let result = generate_certificate_internal(
+                &config,
+                &ca_cert_pem,
+                &ca_key_pem,
+                generate_serial_number(),
+            )
+            .unwrap();
+
+            let now = OffsetDateTime::now_utc();
+            let cert_der = parse_pem_cert(&fs::read(&result.cert_path).unwrap()).unwrap();
+            let (not_before, not_after) = cert_validity(&cert_der).unwrap();
+            let skew = (now - not_before) - expected;
+            assert!(skew.abs() < Duration::minutes(1), "{:?}", now - not_before);
+            assert_eq!(not_after - not_before, Duration::days(30));
+        }
+    }
+
+    #[test]
+    fn test_certificate_text() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+
+        let config = CertificateConfig::builder()
+            .hosts(["example.com", "127.0.0.1"])
+            .cert_file(temp_dir.path().join("cert.pem"))
+            .key_file(temp_dir.path().join("key.pem"))
+            .build()
+            .unwrap();
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        )
+        .unwrap();
+
+        let pem = fs::read(&result.cert_path).unwrap();
+        let text = certificate_text(&pem).unwrap();
+        let info = inspect_certificate(&pem).unwrap();
+        let date_format = time::macros::format_description!(
+            "[month repr:short] [day padding:space] [hour]:[minute]:[second] [year] GMT"
+        );
+
+        assert!(
+            text.contains("DNS:example.com, IP Address:127.0.0.1"),
+            "{}",
+            text
+        );
+        assert!(text.contains(&format!(
+            "Not Before: {}",
+            info.not_before.format(&date_format).unwrap()
+        )));
+        assert!(text.contains(&format!(
+            "Not After : {}",
+            info.not_after.format(&date_format).unwrap()
+        )));
+        assert!(
+            text.contains("Public Key Algorithm: rsaEncryption"),
+            "{}",
+            text
+        );
+        assert!(text.contains("Public-Key: (2048 bit)"), "{}", text);
+        assert!(text.contains("TLS Web Server Authentication"), "{}", text);
+        assert!(text.contains("CA:FALSE"), "{}", text);
+    }
+
+    #[test]
+    fn test_parse_pem_blocks_cert_and_key() {
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+        let combined = format!("{}{}", ca_cert_pem, ca_key_pem);
+
+        let blocks = parse_pem_blocks(combined.as_bytes()).unwrap();
+        let tags: Vec<&str> = blocks.iter().map(|b| b.tag()).collect();
+        assert_eq!(tags, ["CERTIFICATE", "PRIVATE KEY"]);
+        assert!(first_of_tag(&blocks, "PRIVATE KEY").is_some());
+        assert!(first_of_tag(&blocks, "CERTIFICATE REQUEST").is_none());
+
+        // Key-first files still yield the certificate
+        let reversed = format!("{}{}", ca_key_pem, ca_cert_pem);
+        assert_eq!(
+            parse_pem_cert(reversed.as_bytes()).unwrap(),
+            blocks[0].contents()
+        );
+
+        assert!(parse_pem_blocks(b"no pem here").is_err());
+        assert!(parse_csr_pem(combined.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_inherit_ca_algorithm() {
+        use tempfile::TempDir;
+        use x509_parser::oid_registry::OID_KEY_TYPE_EC_PUBLIC_KEY;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+        let leaf_is_ecdsa = |inherit: bool| {
+            let mut config = CertificateConfig::new(vec!["inherit.test".to_string()]);
+            config.inherit_ca_algorithm = inherit;
+            let cert_path = temp_dir.path().join(format!("inherit-{}.pem", inherit));
+            config.cert_file = Some(cert_path.clone());
+            config.key_file = Some(temp_dir.path().join(format!("inherit-{}-key.pem", inherit)));
+            generate_certificate_internal(
+                &config,
+                &ca_cert_pem,
+                &ca_key_pem,
+                generate_serial_number(),
+            )
+            .unwrap();
+
+            let der = parse_pem_cert(&fs::read(cert_path).unwrap()).unwrap();
+            let (_, cert) = x509_parser::parse_x509_certificate(&der).unwrap();
+            cert.public_key().algorithm.algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY
+        };
+
+        // The test CA has an ECDSA key
+        assert!(leaf_is_ecdsa(true));
+        assert!(!leaf_is_ecdsa(false));
+    }
+
+    #[test]
+    fn test_certificate_sans_round_trip() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+
+        let hosts = vec![
+            HostType::DnsName("example.test".to_string()),
+            HostType::DnsName("*.example.test".to_string()),
+            HostType::IpAddress("127.0.0.1".parse().unwrap()),
+            HostType::IpAddress("::1".parse().unwrap()),
+            HostType::Email("dev@example.test".to_string()),
+            HostType::Uri("spiffe://example.test/api".to_string()),
+        ];
+        let mut config = CertificateConfig::from_host_types(hosts.clone());
+        config.cert_file = Some(temp_dir.path().join("mixed.pem"));
+        config.key_file = Some(temp_dir.path().join("mixed-key.pem"));
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        )
+        .unwrap();
+
+        let pem = fs::read(&result.cert_path).unwrap();
+        assert_eq!(certificate_sans(&pem).unwrap(), hosts);
+        assert!(certificate_sans(b"not a certificate").is_err());
+    }
+
+    #[test]
+    fn test_typed_host_types_are_not_reclassified() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+
+        let mut config = CertificateConfig::from_host_types(vec![
+            HostType::DnsName("127.0.0.1".to_string()),
+            HostType::IpAddress("10.0.0.1".parse().unwrap()),
+        ]);
+        config.cert_file = Some(temp_dir.path().join("typed.pem"));
+        config.key_file = Some(temp_dir.path().join("typed-key.pem"));
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        )
+        .unwrap();
+
+        let der = parse_pem_cert(&fs::read(&result.cert_path).unwrap()).unwrap();
+        assert_eq!(
+            cert_sans(&der).unwrap(),
+            vec![
+                HostType::DnsName("127.0.0.1".to_string()),
+                HostType::IpAddress("10.0.0.1".parse().unwrap()),
+            ]
+        );
+        assert_eq!(result.hosts, vec!["127.0.0.1", "10.0.0.1"]);
+
+        // The string API still classifies by format
+        let sans = build_san_list(&["127.0.0.1".to_string()]).unwrap();
+        assert!(matches!(sans[0], SanType::IpAddress(_)));
+    }
+
+    #[test]
+    fn test_key_matches_cert() {
+        use tempfile::TempDir;
+
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+        let (_, other_key_pem) = create_test_ca();
+
+        assert!(key_matches_cert(ca_key_pem.as_bytes(), ca_cert_pem.as_bytes()).unwrap());
+        assert!(!key_matches_cert(other_key_pem.as_bytes(), ca_cert_pem.as_bytes()).unwrap());
+        assert!(key_matches_cert(ca_cert_pem.as_bytes(), ca_cert_pem.as_bytes()).is_err());
+
+        // Traditional encodings, as written by `openssl ecparam` and
+        // `openssl genrsa`, are accepted too
+        let sec1 = convert_key(ca_key_pem.as_bytes(), KeyFormat::TraditionalPem).unwrap();
+        assert!(String::from_utf8_lossy(&sec1).contains("EC PRIVATE KEY"));
+        assert!(key_matches_cert(&sec1, ca_cert_pem.as_bytes()).unwrap());
+
+        let rsa_key = KeyPair::generate_for(&rcgen::PKCS_RSA_SHA256).unwrap();
+        let rsa_cert = CertificateParams::default().self_signed(&rsa_key).unwrap();
+        let pkcs1 = convert_key(
+            rsa_key.serialize_pem().as_bytes(),
+            KeyFormat::TraditionalPem,
+        )
+        .unwrap();
+        assert!(String::from_utf8_lossy(&pkcs1).contains("RSA PRIVATE KEY"));
+        assert!(key_matches_cert(&pkcs1, rsa_cert.pem().as_bytes()).unwrap());
+        assert!(!key_matches_cert(&pkcs1, ca_cert_pem.as_bytes()).unwrap());
+
+        // Signing with a mismatched CA key is refused
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = CertificateConfig::new(vec!["example.com".to_string()]);
+        config.cert_file = Some(temp_dir.path().join("cert.pem"));
+        config.key_file = Some(temp_dir.path().join("key.pem"));
+        let err = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &other_key_pem,
+            generate_serial_number(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{}", err);
+        assert!(!temp_dir.path().join("cert.pem").exists());
+    }
+
+    #[test]
+    fn test_leaf_subject_must_differ_from_ca() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let key_pair = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let mut params = CertificateParams::default();
+        params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "ca.test");
+        let ca_cert_pem = params.self_signed(&key_pair).unwrap().pem();
+        let ca_key_pem = key_pair.serialize_pem();
+
+        // With omit_san the host becomes the only subject attribute
+        let mut config = CertificateConfig::new(vec!["ca.test".to_string()]);
+        config.omit_san = true;
+        config.cert_file = Some(temp_dir.path().join("cert.pem"));
+        config.key_file = Some(temp_dir.path().join("key.pem"));
+        let err = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("same as the CA subject"),
+            "{}",
+            err
+        );
+        assert!(!temp_dir.path().join("cert.pem").exists());
+
+        config.hosts = vec!["leaf.test".to_string()];
+        generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem, generate_serial_number())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify_chain_pem() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+        let (other_ca_pem, _) = create_test_ca();
+
+        let cert_path = temp_dir.path().join("leaf.pem");
+        let mut config = CertificateConfig::new(vec!["leaf.test".to_string()]);
+        config.use_ecdsa = true;
+        config.cert_file = Some(cert_path.clone());
+        config.key_file = Some(temp_dir.path().join("leaf-key.pem"));
+        generate_certificate_internal(&config, &ca_cert_pem, &ca_key_pem, generate_serial_number())
+            .unwrap();
+        let leaf_pem = fs::read(&cert_path).unwrap();
+
+        verify_chain_pem(&leaf_pem, ca_cert_pem.as_bytes()).unwrap();
+        // Same subject, different key
+        let err = verify_chain_pem(&leaf_pem, other_ca_pem.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("signature"), "{}", err);
+        assert!(verify_chain_pem(b"not a pem", ca_cert_pem.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_host_type_ipv6_zone_and_cidr() {
+        let parsed = HostType::parse("fe80::1%eth0").unwrap();
+        assert_eq!(
+            parsed,
+            HostType::IpAddress("fe80::1".parse::<IpAddr>().unwrap())
+        );
+
+        let err = HostType::parse("10.0.0.0/24").unwrap_err();
+        assert!(err.to_string().contains("individually"), "{}", err);
+        assert!(HostType::parse("fd00::/64").is_err());
+    }
+
+    #[test]
+    fn test_ip_range_expands_to_individual_sans() {
+        let hosts = vec![
+            "example.com".to_string(),
+            "ip-range:10.0.0.1-10.0.0.5".to_string(),
+        ];
+        let sans = build_san_list(&hosts).unwrap();
+        let ips: Vec<_> = sans
+            .iter()
+            .filter_map(|san| match san {
+                SanType::IpAddress(ip) => Some(ip.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            ips,
+            ["10.0.0.1", "10.0.0.2", "10.0.0.3", "10.0.0.4", "10.0.0.5"]
+        );
+        assert_eq!(sans.len(), 6);
+
+        let v6 = expand_ip_range("fd00::fe".parse().unwrap(), "fd00::101".parse().unwrap());
+        assert_eq!(v6.unwrap().len(), 4);
+
+        let err = validate_hosts(&["ip-range:10.0.0.9-10.0.0.1".to_string()]).unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidHost { index: 0, .. }),
+            "{}",
+            err
+        );
+        assert!(expand_hosts(&["ip-range:10.0.0.1-::1".to_string()]).is_err());
+        assert!(expand_hosts(&["ip-range:10.0.0.1".to_string()]).is_err());
+        // Larger than the SAN cap
+        assert!(expand_hosts(&["ip-range:10.0.0.0-10.0.255.255".to_string()]).is_err());
+        assert!(HostType::parse("ip-range:10.0.0.1-10.0.0.5").is_err());
+    }
+
+    #[test]
+    fn test_read_hosts_from_reader() {
+        let input = "# dev hosts\napp.local\n\n  api.local  \n\t# loopback\n127.0.0.1\n   \n";
+        let hosts = read_hosts_from_reader(input.as_bytes()).unwrap();
+        assert_eq!(hosts, ["app.local", "api.local", "127.0.0.1"]);
+
+        let err = read_hosts_from_reader("app.local\n0.0.0.0\n".as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidHost { index: 1, .. }),
+            "{}",
+            err
+        );
+        assert!(
+            read_hosts_from_reader("# nothing\n".as_bytes())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_san_list_has_no_implicit_entries() {
+        let hosts = vec!["example.com".to_string()];
+        assert_eq!(build_san_list(&hosts).unwrap().len(), 1);
+
+        let hosts = vec![
+            "example.com".to_string(),
+            "10.0.0.1".to_string(),
+            "dev@example.com".to_string(),
+        ];
+        assert_eq!(build_san_list(&hosts).unwrap().len(), hosts.len());
+    }
+
+    #[test]
+    fn test_generated_cert_has_exactly_requested_sans() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+
+        let config = CertificateConfig::builder()
+            .host("only.example.com")
+            .cert_file(temp_dir.path().join("only.pem"))
+            .key_file(temp_dir.path().join("only-key.pem"))
+            .build()
+            .unwrap();
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        )
+        .unwrap();
+
+        let der = parse_pem_cert(&fs::read(&result.cert_path).unwrap()).unwrap();
+        assert_eq!(
+            cert_sans(&der).unwrap(),
+            vec![HostType::DnsName("only.example.com".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_config_builder_server_cert() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let (ca_cert_pem, ca_key_pem) = create_test_ca();
+
+        let config = CertificateConfig::builder()
+            .host("example.com")
+            .host("127.0.0.1")
+            .ecdsa()
+            .validity_days(30)
+            .cert_file(temp_dir.path().join("server.pem"))
+            .key_file(temp_dir.path().join("server-key.pem"))
+            .build()
+            .unwrap();
+        assert_eq!(config.hosts, vec!["example.com", "127.0.0.1"]);
+        assert!(config.use_ecdsa);
+        assert!(!config.client_cert);
+
+        let result = generate_certificate_internal(
+            &config,
+            &ca_cert_pem,
+            &ca_key_pem,
+            generate_serial_number(),
+        )
+        .unwrap();
+        let info = inspect_certificate(&fs::read(&result.cert_path).unwrap()).unwrap();
+        assert_eq!(info.not_after - info.not_before, Duration::days(30));
+    }
+
+    #[test]
+    fn test_config_builder_client_cert() {
+        let config = CertificateConfig::builder()
+            .hosts(["client@example.com"])
+            .client()
+            .p12_file("client.p12")
+            .build()
+            .unwrap();
+        assert!(config.client_cert);
+        assert!(config.pkcs12);
+        assert_eq!(config.p12_file, Some(PathBuf::from("client.p12")));
+        assert_eq!(config.validity_days, None);
+    }
+
+    #[test]
+    fn test_config_builder_validation() {
+        assert!(CertificateConfig::builder().build().is_err());
+        assert!(
+            CertificateConfig::builder()
+                .host("example.com")
+                .validity_days(0)
+                .build()
+                .is_err()
+        );
+    }
 }
@@ -0,0 +1,152 @@
+//! Environment diagnostics
+//!
+//! Collects the information needed to debug a fastcert setup in a single
+//! report: where the CA lives, whether it exists, which trust stores and
+//! tools are available on this machine, and which crypto libraries this
+//! build of fastcert uses.
+
+use crate::truststore::{self, java::JavaTrustStore, nss::NssTrustStore};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use time::format_description::well_known::Rfc3339;
+
+/// Snapshot of the local fastcert environment.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticReport {
+    /// CAROOT directory, empty if it could not be determined
+    pub caroot: String,
+    /// Whether the CAROOT directory exists
+    pub caroot_exists: bool,
+    /// Whether files can be created in the CAROOT directory
+    pub caroot_writable: bool,
+    /// Whether the root CA certificate exists
+    pub ca_exists: bool,
+    /// Expiration of the root CA certificate (RFC 3339)
+    pub ca_expires: Option<String>,
+    /// Trust stores available on this system
+    pub available_stores: Vec<String>,
+    /// Trust stores enabled via `TRUST_STORES`
+    pub enabled_stores: Vec<String>,
+    /// Whether NSS `certutil` was found
+    pub certutil_found: bool,
+    /// Whether Java `keytool` was found
+    pub keytool_found: bool,
+    /// Configured output format
+    pub output_format: String,
+    /// Crypto library versions this build uses
+    pub crypto: BackendInfo,
+}
+
+/// Versions of the crypto libraries fastcert was built with.
+///
+/// Versions are the ones resolved in the `Cargo.lock` of the workspace
+/// fastcert was built in. If no lock file was found at build time, the
+/// requirements declared in fastcert's `Cargo.toml` (e.g. `0.14`) are
+/// reported instead, and `unknown` if those could not be read either.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackendInfo {
+    /// Version of rcgen, which builds and signs certificates
+    pub rcgen_version: String,
+    /// Crypto provider rcgen signs with, e.g. `aws-lc-rs 1.15.1`
+    pub signature_provider: String,
+    /// Version of ring, used for serial numbers, PKCS#12 bundles and
+    /// RSA-PSS signatures
+    pub ring_version: String,
+}
+
+impl DiagnosticReport {
+    /// Serialize the report as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("diagnostic report is always serializable")
+    }
+}
+
+/// Collect a diagnostic report for the current environment
+///
+/// Never fails: anything that cannot be determined is reported as missing.
+///
+/// # Example
+///
+/// ```no_run
+/// let report = fastcert::diagnose();
+/// println!("{}", report.to_json());
+/// ```
+pub fn diagnose() -> DiagnosticReport {
+    let caroot = crate::ca::get_caroot().unwrap_or_default();
+    let caroot_path = Path::new(&caroot);
+    let has_caroot = !caroot.is_empty();
+
+    let ca = crate::ca::get_ca().ok().filter(|_| has_caroot);
+    let ca_exists = ca.as_ref().is_some_and(|ca| ca.cert_exists());
+    let ca_expires = ca
+        .filter(|_| ca_exists)
+        .and_then(|ca| ca_expiration(&ca.cert_path()));
+
+    DiagnosticReport {
+        caroot_exists: has_caroot && caroot_path.is_dir(),
+        caroot_writable: has_caroot && is_writable(caroot_path),
+        caroot,
+        ca_exists,
+        ca_expires,
+        available_stores: truststore::enumerate_available_stores(),
+        enabled_stores: truststore::get_enabled_stores(),
+        certutil_found: NssTrustStore::has_certutil(),
+        keytool_found: JavaTrustStore::has_keytool(),
+        output_format: format!("{:?}", crate::get_output_format()).to_lowercase(),
+        crypto: crypto_versions(),
+    }
+}
+
+/// Report the crypto libraries and versions this build of fastcert uses.
+///
+/// Useful in bug reports, since signature algorithm support differs
+/// between providers and versions.
+pub fn crypto_versions() -> BackendInfo {
+    let version = |v: Option<&str>| v.unwrap_or("unknown").to_string();
+    BackendInfo {
+        rcgen_version: version(option_env!("FASTCERT_RCGEN_VERSION")),
+        signature_provider: version(option_env!("FASTCERT_SIGNATURE_PROVIDER")),
+        ring_version: version(option_env!("FASTCERT_RING_VERSION")),
+    }
+}
+
+/// Read the notAfter date of a PEM certificate
+fn ca_expiration(cert_path: &Path) -> Option<String> {
+    let cert_pem = fs::read(cert_path).ok()?;
+    let pem_data = pem::parse(cert_pem).ok()?;
+    let (_, cert) = x509_parser::parse_x509_certificate(pem_data.contents()).ok()?;
+    cert.validity()
+        .not_after
+        .to_datetime()
+        .format(&Rfc3339)
+        .ok()
+}
+
+/// Check whether a file can be created in a directory
+fn is_writable(dir: &Path) -> bool {
+    if !dir.is_dir() {
+        return false;
+    }
+
+    let probe = dir.join(format!(".fastcert-write-test-{}", std::process::id()));
+    match fs::File::create(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_writable() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        assert!(is_writable(temp_dir.path()));
+        assert!(!is_writable(&temp_dir.path().join("missing")));
+    }
+}
@@ -13,9 +13,20 @@ pub enum Error {
     )]
     CARootNotFound,
 
+    #[error(
+        "CA root directory {} is not writable: {1}. Fix its permissions or set CAROOT to a writable directory",
+        .0.display()
+    )]
+    CARootNotWritable(std::path::PathBuf, String),
+
     #[error("CA private key is missing. The CA may not have been properly initialized")]
     CAKeyMissing,
 
+    #[error(
+        "CA private key could not be decrypted: {0}. Set FASTCERT_CA_PASSPHRASE to the passphrase used when the CA was created"
+    )]
+    CAKeyPassphrase(String),
+
     #[error("Trust store operation failed: {0}")]
     TrustStore(String),
 
@@ -24,6 +35,27 @@ pub enum Error {
     )]
     InvalidHostname(String),
 
+    #[error("Invalid host #{} '{host}': {source}", .index + 1)]
+    InvalidHost {
+        /// Position of the host in the list (0-based)
+        index: usize,
+        host: String,
+        #[source]
+        source: Box<Error>,
+    },
+
+    #[error("Invalid email address '{0}'. Expected the form user@example.com")]
+    InvalidEmail(String),
+
+    #[error("Invalid URI '{0}': {1}")]
+    InvalidUri(String, String),
+
+    #[error("Invalid wildcard '{0}': {1}")]
+    InvalidWildcard(String, String),
+
+    #[error("Invalid config file {}: {1}", .0.display())]
+    InvalidConfig(std::path::PathBuf, String),
+
     #[error("Command execution failed: {0}")]
     CommandFailed(String),
 }
@@ -67,6 +99,17 @@ mod tests {
         assert!(msg.contains("CAROOT"));
     }
 
+    #[test]
+    fn test_ca_root_not_writable_error() {
+        let err = Error::CARootNotWritable(
+            std::path::PathBuf::from("/ro/caroot"),
+            "Permission denied".to_string(),
+        );
+        let msg = format!("{}", err);
+        assert!(msg.contains("/ro/caroot is not writable: Permission denied"));
+        assert!(msg.contains("CAROOT"));
+    }
+
     #[test]
     fn test_ca_key_missing_error() {
         let err = Error::CAKeyMissing;
@@ -75,6 +118,14 @@ mod tests {
         assert!(msg.contains("not have been properly initialized"));
     }
 
+    #[test]
+    fn test_ca_key_passphrase_error() {
+        let err = Error::CAKeyPassphrase("wrong passphrase".to_string());
+        let msg = format!("{}", err);
+        assert!(msg.contains("could not be decrypted: wrong passphrase"));
+        assert!(msg.contains("FASTCERT_CA_PASSPHRASE"));
+    }
+
     #[test]
     fn test_trust_store_error() {
         let err = Error::TrustStore("certutil command failed".to_string());
@@ -93,6 +144,59 @@ mod tests {
         assert!(msg.contains("alphanumeric"));
     }
 
+    #[test]
+    fn test_invalid_host_error() {
+        let err = Error::InvalidHost {
+            index: 1,
+            host: "bad host".to_string(),
+            source: Box::new(Error::InvalidHostname("bad host".to_string())),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.starts_with("Invalid host #2 'bad host': Invalid hostname"));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_invalid_email_error() {
+        let err = Error::InvalidEmail("nope".to_string());
+        assert_eq!(
+            format!("{}", err),
+            "Invalid email address 'nope'. Expected the form user@example.com"
+        );
+    }
+
+    #[test]
+    fn test_invalid_uri_error() {
+        let err = Error::InvalidUri("not-a-uri".to_string(), "missing scheme".to_string());
+        assert_eq!(
+            format!("{}", err),
+            "Invalid URI 'not-a-uri': missing scheme"
+        );
+    }
+
+    #[test]
+    fn test_invalid_wildcard_error() {
+        let err = Error::InvalidWildcard(
+            "*.*.example.com".to_string(),
+            "only one wildcard is allowed".to_string(),
+        );
+        let msg = format!("{}", err);
+        assert!(msg.contains("'*.*.example.com'"));
+        assert!(msg.contains("only one wildcard"));
+    }
+
+    #[test]
+    fn test_invalid_config_error() {
+        let err = Error::InvalidConfig(
+            std::path::PathBuf::from("fastcert.toml"),
+            "missing field `hosts`".to_string(),
+        );
+        assert_eq!(
+            format!("{}", err),
+            "Invalid config file fastcert.toml: missing field `hosts`"
+        );
+    }
+
     #[test]
     fn test_command_failed_error() {
         let err = Error::CommandFailed("openssl failed with exit code 1".to_string());
@@ -1,10 +1,19 @@
 //! File and path utilities
 
 use crate::{Error, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{Duration, Instant};
 
-/// Get the CAROOT directory path
+/// Default limit for external commands, in seconds
+const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 60;
+
+/// Get the CAROOT directory path, including the `FASTCERT_PROFILE` subdirectory
 pub fn get_ca_root() -> Result<PathBuf> {
+    crate::ca::profile_dir(get_base_ca_root()?)
+}
+
+fn get_base_ca_root() -> Result<PathBuf> {
     if let Ok(path) = std::env::var("CAROOT") {
         return Ok(PathBuf::from(path));
     }
@@ -64,6 +73,115 @@ pub fn command_exists(command: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Get the time limit for external trust store commands
+///
+/// Reads `FASTCERT_CMD_TIMEOUT` in seconds and defaults to 60.
+pub fn command_timeout() -> Duration {
+    let secs = std::env::var("FASTCERT_CMD_TIMEOUT")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|&secs| secs > 0)
+        .unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Run a command and collect its output, limited by [`command_timeout`]
+pub fn run_command(command: &mut Command) -> Result<Output> {
+    run_command_with_timeout(command, command_timeout())
+}
+
+/// Run a command and collect its output, killing it after `timeout`
+///
+/// Like `Command::output`, stdin is closed and stdout/stderr are captured.
+/// Returns `Error::CommandFailed` if the command cannot be started or does
+/// not finish in time.
+pub fn run_command_with_timeout(command: &mut Command, timeout: Duration) -> Result<Output> {
+    use std::process::Stdio;
+
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::CommandFailed(format!("Failed to execute {}: {}", program, e)))?;
+
+    // Drain both pipes while waiting so a chatty child cannot block on a full pipe
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(Error::CommandFailed(format!(
+                "{} timed out after {:?}",
+                program, timeout
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn read_pipe<R>(pipe: Option<R>) -> std::thread::JoinHandle<Vec<u8>>
+where
+    R: std::io::Read + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Move an existing file aside before it gets overwritten
+///
+/// The file is renamed to `<name>.bak`. If that backup already exists, a
+/// timestamped name such as `<name>.20240101T120000.bak` is used instead so
+/// earlier backups are never clobbered.
+///
+/// Returns the backup path, or `None` if there was no file to back up.
+pub fn backup_existing(path: &Path) -> Result<Option<PathBuf>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| Error::Certificate(format!("Invalid file path: {:?}", path)))?;
+
+    let mut backup = path.with_file_name(format!("{}.bak", file_name));
+    if backup.exists() {
+        let format = time::macros::format_description!("[year][month][day]T[hour][minute][second]");
+        let timestamp = time::OffsetDateTime::now_utc()
+            .format(&format)
+            .map_err(|e| Error::Certificate(format!("Failed to format timestamp: {}", e)))?;
+
+        backup = path.with_file_name(format!("{}.{}.bak", file_name, timestamp));
+        let mut counter = 1;
+        while backup.exists() {
+            backup = path.with_file_name(format!("{}.{}-{}.bak", file_name, timestamp, counter));
+            counter += 1;
+        }
+    }
+
+    std::fs::rename(path, &backup)?;
+    Ok(Some(backup))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,4 +213,40 @@ mod tests {
             "this_command_definitely_does_not_exist_12345"
         ));
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_command_with_timeout() {
+        let output =
+            run_command_with_timeout(Command::new("echo").arg("hello"), Duration::from_secs(5))
+                .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+
+        let start = Instant::now();
+        let err =
+            run_command_with_timeout(Command::new("sleep").arg("10"), Duration::from_millis(200))
+                .unwrap_err();
+        assert!(matches!(err, Error::CommandFailed(ref msg) if msg.contains("timed out")));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_backup_existing() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("rootCA.pem");
+
+        assert_eq!(backup_existing(&path).unwrap(), None);
+
+        std::fs::write(&path, "first").unwrap();
+        let first = backup_existing(&path).unwrap().unwrap();
+        assert_eq!(first, temp_dir.path().join("rootCA.pem.bak"));
+        assert!(!path.exists());
+
+        std::fs::write(&path, "second").unwrap();
+        let second = backup_existing(&path).unwrap().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "first");
+        assert_eq!(std::fs::read_to_string(&second).unwrap(), "second");
+    }
 }
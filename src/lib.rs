@@ -48,16 +48,24 @@
 //! # Ok::<(), fastcert::Error>(())
 //! ```
 
+pub mod audit;
 pub mod ca;
 pub mod cert;
+pub mod doctor;
 pub mod error;
 pub mod fileutil;
 pub mod truststore;
+pub mod watch;
 
 // Re-export main types at crate root
 pub use ca::CA;
-pub use cert::{CertificateBuilder, KeyType};
+pub use cert::{
+    CertInfo, CertificateBuilder, GenerationResult, KeyAlgorithm, KeyPairPem, KeyType,
+    RemovedFiles, RenewOutcome, SubjectFields, Timings,
+};
+pub use doctor::{BackendInfo, DiagnosticReport, crypto_versions, diagnose};
 pub use error::{Error, Result};
+pub use truststore::selftest::self_test_tls;
 
 // Convenience functions for simple use cases
 
@@ -67,6 +75,9 @@ pub use error::{Error, Result};
 /// a certificate with the specified domains. For more control, use the
 /// builder API via `CA::load_or_create()?.issue_certificate()`.
 ///
+/// Certificate generation never touches a trust store; only the install
+/// functions do.
+///
 /// # Example
 ///
 /// ```no_run
@@ -84,7 +95,36 @@ pub fn generate_cert(domains: &[String]) -> Result<()> {
     CA::load_or_create()?
         .issue_certificate()?
         .domains(domains.to_vec())
-        .build()
+        .build()?;
+    Ok(())
+}
+
+/// Install the CA and generate a certificate in one step
+///
+/// The same as `fastcert --install <domains>`. With `skip_trust` the trust
+/// store step is bypassed entirely and only the CA and certificate files are
+/// written, like [`generate_cert`].
+///
+/// # Example
+///
+/// ```no_run
+/// // In CI, where trust stores must not be modified
+/// fastcert::generate_and_install(&["localhost".to_string()], true)?;
+/// # Ok::<(), fastcert::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if CA operations, installation (unless skipped) or
+/// certificate generation fails.
+pub fn generate_and_install(domains: &[String], skip_trust: bool) -> Result<GenerationResult> {
+    let ca = CA::load_or_create()?;
+    if skip_trust {
+        log_event("install", "Skipping trust store installation");
+    } else {
+        ca.install()?;
+    }
+    ca.issue_certificate()?.domains(domains.to_vec()).build()
 }
 
 /// Install the CA to system trust stores
@@ -120,18 +160,44 @@ pub fn uninstall() -> Result<()> {
     ca.uninstall()
 }
 
+/// Check whether a boolean `FASTCERT_*` flag is set
+///
+/// Any value turns the flag on except an empty string or `0`, so
+/// `FASTCERT_X=0` can switch off a flag set elsewhere.
+pub(crate) fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
 /// Check if verbose mode is enabled
 pub fn is_verbose() -> bool {
     std::env::var("FASTCERT_VERBOSE").is_ok()
 }
 
 /// Print verbose message
+///
+/// Kept for compatibility; equivalent to `log_event("general", msg)`.
 pub fn verbose_print(msg: &str) {
+    log_event("general", msg);
+}
+
+/// Log a timestamped event for a phase of an operation in verbose mode
+///
+/// Prints `[VERBOSE] <RFC 3339 timestamp> <phase> <msg>` to stderr, which
+/// makes it easy to see where slow operations spend their time.
+pub fn log_event(phase: &str, msg: &str) {
     if is_verbose() {
-        eprintln!("[VERBOSE] {}", msg);
+        eprintln!("{}", format_log_event(phase, msg));
     }
 }
 
+/// Format a verbose log line for `log_event`
+fn format_log_event(phase: &str, msg: &str) -> String {
+    let timestamp = time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_default();
+    format!("[VERBOSE] {} {} {}", timestamp, phase, msg)
+}
+
 /// Check if debug mode is enabled
 pub fn is_debug() -> bool {
     std::env::var("FASTCERT_DEBUG").is_ok()
@@ -163,6 +229,18 @@ pub fn info_print(msg: &str) {
     }
 }
 
+/// Check if dry-run mode is enabled
+///
+/// In dry-run mode trust store operations only print what they would do.
+pub fn is_dry_run() -> bool {
+    env_flag("FASTCERT_DRY_RUN")
+}
+
+/// Check if strict mode is enabled
+pub fn is_strict() -> bool {
+    env_flag("FASTCERT_STRICT")
+}
+
 /// Output format options
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OutputFormat {
@@ -184,6 +262,26 @@ impl std::str::FromStr for OutputFormat {
     }
 }
 
+impl OutputFormat {
+    /// File extension for output written in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+        }
+    }
+
+    /// MIME type for serving output in this format
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Text => "text/plain",
+            Self::Json => "application/json",
+            Self::Yaml => "application/yaml",
+        }
+    }
+}
+
 /// Get the configured output format
 pub fn get_output_format() -> OutputFormat {
     std::env::var("FASTCERT_FORMAT")
@@ -244,6 +342,21 @@ mod tests {
         verbose_print("test message");
     }
 
+    #[test]
+    fn test_format_log_event() {
+        let line = format_log_event("truststore", "installing into nss");
+        let mut parts = line.splitn(4, ' ');
+        assert_eq!(parts.next(), Some("[VERBOSE]"));
+        let timestamp = parts.next().unwrap();
+        assert!(
+            timestamp.contains('T') && timestamp.ends_with('Z'),
+            "Timestamp should be RFC 3339 UTC: {}",
+            timestamp
+        );
+        assert_eq!(parts.next(), Some("truststore"));
+        assert_eq!(parts.next(), Some("installing into nss"));
+    }
+
     #[test]
     fn test_is_debug_when_env_var_set() {
         let _guard = TEST_MUTEX.lock().unwrap();
@@ -379,6 +492,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_output_format_text_extension_and_content_type() {
+        assert_eq!(OutputFormat::Text.extension(), "txt");
+        assert_eq!(OutputFormat::Text.content_type(), "text/plain");
+    }
+
+    #[test]
+    fn test_output_format_json_extension_and_content_type() {
+        assert_eq!(OutputFormat::Json.extension(), "json");
+        assert_eq!(OutputFormat::Json.content_type(), "application/json");
+    }
+
+    #[test]
+    fn test_output_format_yaml_extension_and_content_type() {
+        assert_eq!(OutputFormat::Yaml.extension(), "yaml");
+        assert_eq!(OutputFormat::Yaml.content_type(), "application/yaml");
+    }
+
     #[test]
     fn test_get_output_format_default() {
         let _guard = TEST_MUTEX.lock().unwrap();
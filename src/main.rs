@@ -9,6 +9,7 @@
 //! - PKCS#12 bundle creation
 
 use clap::Parser;
+use fastcert::cert::{CertificateConfig, CombinedOrder, P12Encryption, generate_file_names};
 use fastcert::{CA, KeyType, Result};
 
 const AFTER_HELP: &str = "\
@@ -28,6 +29,9 @@ EXAMPLES:
     $ fastcert --uninstall
     Uninstall the local CA (but do not delete it).
 
+    $ fastcert --renew-if-expiring 30 --cert-file app.pem
+    Re-issue \"app.pem\" and \"app-key.pem\" if it expires within 30 days.
+
 ENVIRONMENT:
     CAROOT
         Set the CA certificate and key storage location. (This allows
@@ -37,6 +41,64 @@ ENVIRONMENT:
         A comma-separated list of trust stores to install the local
         root CA into. Options are: \"system\", \"java\" and \"nss\" (includes
         Firefox). Autodetected by default.
+
+    FASTCERT_AUDIT
+        Append a JSON line for every issued certificate to issued.jsonl
+        in the CAROOT.
+
+    FASTCERT_CA_KEY
+        Key algorithm for a new local CA: \"rsa3072\" (default), \"rsa2048\",
+        \"ecdsa-p256\", \"ecdsa-p384\" or \"ed25519\".
+
+    FASTCERT_CA_VALIDITY_DAYS
+        Lifetime in days of a new local CA (default: 3650). Certificates
+        never outlive the CA that issues them.
+
+    FASTCERT_CA_PATH_LEN
+        Basic Constraints path length of a new local CA (default: no
+        limit). 0 allows no intermediate CA.
+
+    FASTCERT_EXTRA_ANCHORS
+        Directories, separated like PATH, that --install copies the CA
+        certificate into and --uninstall removes it from.
+
+    FASTCERT_BACKDATE_MINUTES
+        Minutes to backdate the start of certificate validity, to allow
+        for client clock skew (default: 60).
+
+    FASTCERT_CA_NAME_CONSTRAINTS
+        Comma-separated DNS suffixes a new local CA may issue for, such as
+        \"dev.local,test\". Clients reject certificates for other names.
+
+    FASTCERT_CA_PASSPHRASE
+        Encrypt a newly created CA key with this passphrase, and use it to
+        unlock an encrypted CA key when signing.
+
+    FASTCERT_PROFILE
+        Use a separate CA stored in the named subdirectory of CAROOT,
+        for example \"work\" or \"personal\".
+
+    FASTCERT_NO_AUTO_CA
+        Fail instead of creating a new local CA when none exists.
+
+    FASTCERT_CMD_TIMEOUT
+        Seconds to wait for security, certutil or keytool before giving
+        up (default: 60).
+
+    FASTCERT_OFFLINE
+        Skip NSS and Java detection and use only the system trust store.
+
+    FASTCERT_DRY_RUN
+        Print the trust store commands --install would run instead of
+        running them (same as --dry-run).
+
+    FASTCERT_SERIAL_MODE
+        Set to \"counter\" to give leaf certificates increasing serial
+        numbers from a counter kept in CAROOT/serial.txt (default: random).
+
+    FASTCERT_STRICT
+        Warn about common host list mistakes, such as a wildcard listed
+        without its apex domain.
 ";
 
 /// Command-line interface structure.
@@ -58,10 +120,26 @@ struct Cli {
     #[arg(long)]
     uninstall: bool,
 
+    /// Never modify trust stores, even with --install; only write files
+    #[arg(long = "no-trust-store")]
+    no_trust_store: bool,
+
+    /// Show the trust store commands --install would run without running them
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+
     /// Print the CA certificate and key storage location
     #[arg(long = "CAROOT")]
     caroot: bool,
 
+    /// Print a certificate file as human-readable text
+    #[arg(long, value_name = "CERT")]
+    inspect: Option<String>,
+
+    /// Re-issue the certificate given by --cert-file if it expires within DAYS
+    #[arg(long = "renew-if-expiring", value_name = "DAYS")]
+    renew_if_expiring: Option<u32>,
+
     /// Customize the output certificate file path
     #[arg(long = "cert-file", value_name = "FILE")]
     cert_file: Option<String>,
@@ -82,10 +160,22 @@ struct Cli {
     #[arg(long)]
     ecdsa: bool,
 
+    /// Put the single domain in the Common Name and omit the subjectAltName
+    #[arg(long = "no-san")]
+    no_san: bool,
+
+    /// Write the key before the certificate when cert and key share a file (for HAProxy)
+    #[arg(long = "key-first")]
+    key_first: bool,
+
     /// Generate a PKCS#12 file (also known as .pfx) containing certificate and key
     #[arg(long)]
     pkcs12: bool,
 
+    /// Use legacy RC2/3DES encryption for the PKCS#12 file (default: AES-256)
+    #[arg(long = "pkcs12-legacy")]
+    pkcs12_legacy: bool,
+
     /// Generate a certificate based on the supplied CSR
     #[arg(long, value_name = "CSR")]
     csr: Option<String>,
@@ -102,7 +192,7 @@ struct Cli {
     #[arg(short, long)]
     quiet: bool,
 
-    /// Domain names or IP addresses to generate certificates for
+    /// Domain names or IP addresses to generate certificates for ("-" reads them from stdin)
     #[arg(value_name = "DOMAINS")]
     domains: Vec<String>,
 }
@@ -113,6 +203,8 @@ struct Cli {
 /// - `--install`: Install the local CA to system trust stores
 /// - `--uninstall`: Remove the local CA from system trust stores
 /// - `--CAROOT`: Print the CA storage location
+/// - `--inspect <file>`: Print a certificate as text
+/// - `--renew-if-expiring <days>`: Re-issue `--cert-file` close to expiry
 /// - `<domains...>`: Generate certificates for specified hosts
 /// - `--csr <file>`: Generate certificate from a CSR
 ///
@@ -127,27 +219,34 @@ struct Cli {
 /// - CA operations fail
 /// - Certificate generation fails
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
 
     // Set verbose mode if requested
     if cli.verbose {
         unsafe {
-            std::env::set_var("RSCERT_VERBOSE", "1");
+            std::env::set_var("FASTCERT_VERBOSE", "1");
         }
     }
 
     // Set debug mode if requested (implies verbose)
     if cli.debug {
         unsafe {
-            std::env::set_var("RSCERT_DEBUG", "1");
-            std::env::set_var("RSCERT_VERBOSE", "1");
+            std::env::set_var("FASTCERT_DEBUG", "1");
+            std::env::set_var("FASTCERT_VERBOSE", "1");
         }
     }
 
     // Set quiet mode if requested (overrides verbose/debug)
     if cli.quiet {
         unsafe {
-            std::env::set_var("RSCERT_QUIET", "1");
+            std::env::set_var("FASTCERT_QUIET", "1");
+        }
+    }
+
+    // Set dry-run mode if requested
+    if cli.dry_run {
+        unsafe {
+            std::env::set_var("FASTCERT_DRY_RUN", "1");
         }
     }
 
@@ -161,11 +260,61 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Handle --inspect flag
+    if let Some(path) = cli.inspect {
+        let pem = std::fs::read(&path)?;
+        print!("{}", fastcert::cert::certificate_text(&pem)?);
+        return Ok(());
+    }
+
+    // Handle --renew-if-expiring flag
+    if let Some(days) = cli.renew_if_expiring {
+        let Some(cert_file) = cli.cert_file.map(std::path::PathBuf::from) else {
+            eprintln!("ERROR: --renew-if-expiring needs the certificate in --cert-file");
+            std::process::exit(1);
+        };
+        let key_file = cli
+            .key_file
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(|| {
+                let stem = cert_file
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("cert");
+                cert_file.with_file_name(format!("{}-key.pem", stem))
+            });
+        match fastcert::cert::renew_if_expiring(&cert_file, &key_file, days)? {
+            fastcert::RenewOutcome::Renewed(result) => {
+                println!("Renewed {:?}", result.cert_path);
+            }
+            fastcert::RenewOutcome::Skipped { days_remaining } => {
+                println!(
+                    "{:?} is valid for another {} days, not renewing",
+                    cert_file, days_remaining
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    // "-" reads the hosts from stdin, one per line
+    if cli.domains == ["-"] {
+        cli.domains = fastcert::cert::read_hosts_from_reader(std::io::stdin().lock())?;
+        if cli.domains.is_empty() {
+            eprintln!("ERROR: no hosts on stdin");
+            std::process::exit(1);
+        }
+    }
+
     // Handle conflicting flags
     if cli.install && cli.uninstall {
         eprintln!("ERROR: you can't set --install and --uninstall at the same time");
         std::process::exit(1);
     }
+    if cli.uninstall && cli.no_trust_store {
+        eprintln!("ERROR: you can't set --uninstall and --no-trust-store at the same time");
+        std::process::exit(1);
+    }
 
     // Handle CSR conflicts
     if cli.csr.is_some() {
@@ -188,7 +337,11 @@ fn main() -> Result<()> {
     // Handle --install mode
     if cli.install {
         let ca = CA::load_or_create()?;
-        ca.install()?;
+        if cli.no_trust_store {
+            fastcert::log_event("install", "Skipping trust store installation");
+        } else {
+            ca.install()?;
+        }
         if cli.domains.is_empty() && cli.csr.is_none() {
             return Ok(());
         }
@@ -218,26 +371,30 @@ fn main() -> Result<()> {
         if cli.client {
             builder = builder.client_cert(true);
         }
+        if cli.no_san {
+            builder = builder.omit_san(true);
+        }
         if let Some(ref f) = cli.cert_file {
             builder = builder.cert_file(f);
         }
         if let Some(ref f) = cli.key_file {
             builder = builder.key_file(f);
         }
+        if cli.key_first {
+            builder = builder.combined_order(CombinedOrder::KeyFirst);
+        }
         // Handle PKCS#12 mode: either explicit p12-file or --pkcs12 flag
         if let Some(ref f) = cli.p12_file {
             builder = builder.pkcs12_file(f);
         } else if cli.pkcs12 {
-            // When --pkcs12 is set without --p12-file, generate default p12 filename
-            // using the same naming convention as generate_file_names()
-            let mut default_name = cli.domains[0].replace(':', "_").replace('*', "_wildcard");
-            if cli.domains.len() > 1 {
-                default_name.push_str(&format!("+{}", cli.domains.len() - 1));
-            }
-            if cli.client {
-                default_name.push_str("-client");
-            }
-            builder = builder.pkcs12_file(format!("{}.p12", default_name));
+            // When --pkcs12 is set without --p12-file, use the default p12 filename
+            let mut config = CertificateConfig::new(cli.domains.clone());
+            config.client_cert = cli.client;
+            let (_, _, p12_file) = generate_file_names(&config);
+            builder = builder.pkcs12_file(p12_file.to_string_lossy());
+        }
+        if cli.pkcs12_legacy {
+            builder = builder.p12_encryption(P12Encryption::Legacy);
         }
 
         builder.build()?;
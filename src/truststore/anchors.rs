@@ -0,0 +1,87 @@
+//! Extra trust anchor directories
+//!
+//! Some tools ship their own CA bundle instead of using a system store, for
+//! example Python's certifi. Directories listed in `FASTCERT_EXTRA_ANCHORS`
+//! get a copy of the CA PEM on install, which is removed again on uninstall.
+
+use super::TrustStore;
+use crate::Result;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A directory that receives a copy of the CA certificate.
+pub struct ExtraAnchorStore {
+    cert_path: PathBuf,
+    target: PathBuf,
+}
+
+impl ExtraAnchorStore {
+    /// Create a store copying `cert_path` into `dir`.
+    ///
+    /// The copy is named after the CA's trust store nickname, so CAs from
+    /// different profiles can share a directory.
+    pub fn new(cert_path: &Path, dir: &Path, unique_name: &str) -> Self {
+        Self {
+            cert_path: cert_path.to_path_buf(),
+            target: dir.join(format!("{}.pem", unique_name.replace(' ', "-"))),
+        }
+    }
+
+    /// Path the CA certificate is copied to
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+}
+
+impl TrustStore for ExtraAnchorStore {
+    fn check(&self) -> Result<bool> {
+        match fs::read(&self.target) {
+            Ok(copy) => Ok(copy == fs::read(&self.cert_path)?),
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn install(&self) -> Result<()> {
+        if let Some(dir) = self.target.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::copy(&self.cert_path, &self.target)?;
+        Ok(())
+    }
+
+    fn uninstall(&self) -> Result<()> {
+        if self.target.exists() {
+            fs::remove_file(&self.target)?;
+        }
+        Ok(())
+    }
+
+    fn install_plan(&self) -> Vec<String> {
+        let copy = if cfg!(windows) { "Copy-Item" } else { "cp" };
+        vec![format!(
+            "{} \"{}\" \"{}\"",
+            copy,
+            self.cert_path.display(),
+            self.target.display()
+        )]
+    }
+}
+
+/// Get the directories listed in `FASTCERT_EXTRA_ANCHORS`.
+///
+/// Entries are separated like `PATH` (`:` on Unix, `;` on Windows).
+pub fn extra_anchor_dirs() -> Vec<PathBuf> {
+    env::var_os("FASTCERT_EXTRA_ANCHORS")
+        .map(|value| {
+            env::split_paths(&value)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Report name for an extra anchor directory
+pub(crate) fn report_name(dir: &Path) -> String {
+    format!("anchor:{}", dir.display())
+}
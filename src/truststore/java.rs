@@ -1,6 +1,7 @@
 //! Java keystore
 
 use super::TrustStore;
+use crate::fileutil::run_command;
 use crate::{Error, Result};
 use sha1::Sha1;
 use sha2::{Digest, Sha256};
@@ -60,48 +61,43 @@ impl JavaTrustStore {
         })
     }
 
-    /// Check if Java is available
+    /// Check if Java is available. Always `false` in offline mode.
     pub fn is_available() -> bool {
-        Self::detect_java().is_some()
+        !super::is_offline() && Self::detect_java().is_some()
     }
 
-    /// Check if keytool is available
+    /// Check if keytool is available. Always `false` in offline mode.
     pub fn has_keytool() -> bool {
+        if super::is_offline() {
+            return false;
+        }
         Self::detect_java()
             .map(|cfg| cfg.keytool_path.exists())
             .unwrap_or(false)
     }
 
     /// Execute keytool command
-    /// If the command fails with FileNotFoundException on Unix, retry with sudo
+    /// If the command fails with FileNotFoundException on Unix, retry with sudo.
+    /// Each attempt is limited by `FASTCERT_CMD_TIMEOUT`.
     fn exec_keytool(args: &[&str]) -> Result<std::process::Output> {
         let config = Self::detect_java()
             .ok_or_else(|| Error::TrustStore("Java not found. Please set JAVA_HOME".to_string()))?;
 
-        let output = Command::new(&config.keytool_path)
-            .args(args)
-            .output()
-            .map_err(|e| Error::CommandFailed(format!("Failed to execute keytool: {}", e)))?;
+        let output = run_command(Command::new(&config.keytool_path).args(args))?;
 
         // Check if we need to retry with sudo (FileNotFoundException on Unix)
         #[cfg(unix)]
         {
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                if stderr.contains("java.io.FileNotFoundException") {
+                // Running as root already, so the failure is not a permission issue
+                if stderr.contains("java.io.FileNotFoundException") && !super::is_elevated() {
                     // Retry with sudo and set JAVA_HOME environment variable
-                    let output = Command::new("sudo")
-                        .arg(&config.keytool_path)
-                        .args(args)
-                        .env("JAVA_HOME", &config.java_home)
-                        .output()
-                        .map_err(|e| {
-                            Error::CommandFailed(format!(
-                                "Failed to execute keytool with sudo: {}",
-                                e
-                            ))
-                        })?;
-                    return Ok(output);
+                    return run_command(
+                        super::privileged_command(&config.keytool_path)
+                            .args(args)
+                            .env("JAVA_HOME", &config.java_home),
+                    );
                 }
             }
         }
@@ -211,6 +207,19 @@ impl TrustStore for JavaTrustStore {
         Ok(())
     }
 
+    fn install_plan(&self) -> Vec<String> {
+        let Some(config) = Self::detect_java() else {
+            return Vec::new();
+        };
+
+        vec![format!(
+            "keytool -importcert -noprompt -keystore \"{}\" -storepass changeit -file \"{}\" -alias \"{}\"",
+            config.cacerts_path.display(),
+            self.cert_path.display(),
+            self.unique_name
+        )]
+    }
+
     fn uninstall(&self) -> Result<()> {
         if !Self::has_keytool() {
             // If keytool is not available, we can't uninstall but this is not an error
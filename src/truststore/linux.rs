@@ -1,10 +1,13 @@
 //! Linux trust store
 
-use super::TrustStore;
+use super::{TrustStore, note_admin_required, privileged_command, sudo_prefix};
 use crate::{Error, Result};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Kernel version string, which names Microsoft on WSL kernels
+const PROC_VERSION: &str = "/proc/version";
+
 /// Supported Linux distributions
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum LinuxDistro {
@@ -102,12 +105,14 @@ impl LinuxTrustStore {
         self.distro.cert_path("fastcert-rootCA")
     }
 
-    /// Run a command with sudo if needed
+    /// Run a command with sudo, unless already running as root
     fn run_with_sudo(&self, args: &[&str]) -> Result<std::process::Output> {
-        let output = Command::new("sudo")
-            .args(args)
+        let output = privileged_command(args[0])
+            .args(&args[1..])
             .output()
-            .map_err(|e| Error::CommandFailed(format!("Failed to execute sudo command: {}", e)))?;
+            .map_err(|e| {
+                Error::CommandFailed(format!("Failed to execute {} command: {}", args[0], e))
+            })?;
 
         Ok(output)
     }
@@ -148,7 +153,7 @@ impl TrustStore for LinuxTrustStore {
         }
 
         println!("Installing CA certificate to Linux system trust store...");
-        println!("Note: This will require administrator privileges.");
+        note_admin_required();
 
         // Get the target path
         let sys_path = self.system_cert_path().ok_or_else(|| {
@@ -161,8 +166,7 @@ impl TrustStore for LinuxTrustStore {
 
         // Copy certificate to system trust store using tee
         let sys_path_str = sys_path.to_string_lossy();
-        let output = Command::new("sudo")
-            .arg("tee")
+        let output = privileged_command("tee")
             .arg(sys_path_str.as_ref())
             .stdin(std::process::Stdio::piped())
             .output()
@@ -177,8 +181,7 @@ impl TrustStore for LinuxTrustStore {
         }
 
         // Write the certificate content
-        let mut child = Command::new("sudo")
-            .arg("tee")
+        let mut child = privileged_command("tee")
             .arg(sys_path_str.as_ref())
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::null())
@@ -219,6 +222,26 @@ impl TrustStore for LinuxTrustStore {
         Ok(())
     }
 
+    fn install_plan(&self) -> Vec<String> {
+        let Some(sys_path) = self.system_cert_path() else {
+            return vec![format!(
+                "# unsupported distribution, install {:?} manually",
+                self.cert_path
+            )];
+        };
+
+        let mut plan = vec![format!(
+            "{}tee \"{}\" < \"{}\" > /dev/null",
+            sudo_prefix(),
+            sys_path.display(),
+            self.cert_path.display()
+        )];
+        if let Some(update_cmd) = self.distro.update_command() {
+            plan.push(format!("{}{}", sudo_prefix(), update_cmd.join(" ")));
+        }
+        plan
+    }
+
     fn uninstall(&self) -> Result<()> {
         // Check if distribution is supported
         if self.distro == LinuxDistro::Unknown {
@@ -233,7 +256,7 @@ impl TrustStore for LinuxTrustStore {
         }
 
         println!("Removing CA certificate from Linux system trust store...");
-        println!("Note: This will require administrator privileges.");
+        note_admin_required();
 
         // Get the target path
         let sys_path = self.system_cert_path().ok_or_else(|| {
@@ -269,3 +292,71 @@ impl TrustStore for LinuxTrustStore {
         Ok(())
     }
 }
+
+/// Check whether fastcert is running under Windows Subsystem for Linux.
+///
+/// Browsers on the Windows host do not read the Linux trust stores, so a CA
+/// installed from WSL also needs to go into the Windows certificate store.
+pub fn is_wsl() -> bool {
+    is_wsl_with(|| std::fs::read_to_string(PROC_VERSION))
+}
+
+/// [`is_wsl`] with the `/proc/version` reader injected
+fn is_wsl_with(read_version: impl FnOnce() -> std::io::Result<String>) -> bool {
+    read_version()
+        .map(|version| version.to_lowercase().contains("microsoft"))
+        .unwrap_or(false)
+}
+
+/// Import the CA certificate into the Windows user's root store from WSL,
+/// using `wslpath` and `powershell.exe` from the Windows interop layer.
+///
+/// Windows asks the user to confirm adding a root certificate.
+pub fn install_into_windows_host(cert_path: &Path) -> Result<()> {
+    let output = crate::fileutil::run_command(Command::new("wslpath").arg("-w").arg(cert_path))?;
+    if !output.status.success() {
+        return Err(Error::TrustStore(format!(
+            "Failed to translate {} to a Windows path: {}",
+            cert_path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let windows_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let script = format!(
+        "Import-Certificate -FilePath '{}' -CertStoreLocation Cert:\\CurrentUser\\Root",
+        windows_path.replace('\'', "''")
+    );
+    let output = crate::fileutil::run_command(Command::new("powershell.exe").args([
+        "-NoProfile",
+        "-NonInteractive",
+        "-Command",
+        &script,
+    ]))?;
+    if !output.status.success() {
+        return Err(Error::TrustStore(format!(
+            "Failed to import certificate into the Windows store: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_wsl_with() {
+        let wsl2 =
+            "Linux version 5.15.153.1-microsoft-standard-WSL2 (root@1234) (gcc (GCC) 11.2.0)";
+        let wsl1 =
+            "Linux version 4.4.0-19041-Microsoft (Microsoft@Microsoft.com) (gcc version 5.4.0)";
+        let native = "Linux version 6.8.0-45-generic (buildd@lcy02-amd64-075) (gcc 13.2.0)";
+
+        assert!(is_wsl_with(|| Ok(wsl2.to_string())));
+        assert!(is_wsl_with(|| Ok(wsl1.to_string())));
+        assert!(!is_wsl_with(|| Ok(native.to_string())));
+        assert!(!is_wsl_with(|| Err(std::io::ErrorKind::NotFound.into())));
+    }
+}
@@ -1,6 +1,7 @@
 //! macOS Keychain trust store
 
-use super::TrustStore;
+use super::{TrustStore, note_admin_required, privileged_command, sudo_prefix};
+use crate::fileutil::run_command;
 use crate::{Error, Result};
 use std::path::Path;
 use std::process::Command;
@@ -47,15 +48,14 @@ impl MacOSTrustStore {
         }
     }
 
-    /// Run a security command, optionally with sudo
+    /// Run a security command, optionally with sudo, limited by
+    /// `FASTCERT_CMD_TIMEOUT`. sudo is skipped when already running as root.
     fn run_security_command(&self, args: &[&str], with_sudo: bool) -> Result<std::process::Output> {
-        let output = if with_sudo {
-            Command::new("sudo").arg("security").args(args).output()
+        if with_sudo {
+            run_command(privileged_command("security").args(args))
         } else {
-            Command::new("security").args(args).output()
-        };
-
-        output.map_err(|e| Error::TrustStore(format!("Failed to run security command: {}", e)))
+            run_command(Command::new("security").args(args))
+        }
     }
 
     /// Check if the CA certificate is already installed in the system keychain
@@ -89,7 +89,7 @@ impl TrustStore for MacOSTrustStore {
         }
 
         println!("Installing CA certificate to macOS keychain...");
-        println!("Note: This will require administrator privileges.");
+        note_admin_required();
 
         // Add the certificate as a trusted cert to the system keychain
         let output = self.run_security_command(
@@ -124,6 +124,14 @@ impl TrustStore for MacOSTrustStore {
         Ok(())
     }
 
+    fn install_plan(&self) -> Vec<String> {
+        vec![format!(
+            "{}security add-trusted-cert -d -k /Library/Keychains/System.keychain \"{}\"",
+            sudo_prefix(),
+            self.cert_path
+        )]
+    }
+
     fn uninstall(&self) -> Result<()> {
         // Check if not installed
         if !self.is_installed()? {
@@ -132,7 +140,7 @@ impl TrustStore for MacOSTrustStore {
         }
 
         println!("Removing CA certificate from macOS keychain...");
-        println!("Note: This will require administrator privileges.");
+        note_admin_required();
 
         // Remove the certificate from the system keychain
         let output =
@@ -7,16 +7,123 @@
 //! - Java KeyStore
 //!
 //! Trust store selection can be controlled via the `TRUST_STORES` environment
-//! variable (comma-separated list of: system, nss, java).
+//! variable (comma-separated list of: system, nss, java). Directories listed
+//! in `FASTCERT_EXTRA_ANCHORS` additionally get a copy of the CA certificate.
+//! With `FASTCERT_OFFLINE` set, only the system store is considered.
 
-use crate::Result;
+use crate::{Error, OutputFormat, Result};
+use serde::Serialize;
 use std::env;
 use std::path::Path;
+use std::sync::Mutex;
+
+/// Outcome of a trust store operation for a single store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreOutcome {
+    /// The certificate was installed in the store
+    Installed,
+    /// The certificate was already trusted by the store; nothing changed
+    AlreadyInstalled,
+    /// The certificate was removed from the store (or was not present)
+    Uninstalled,
+    /// The store or its tooling is not available on this system
+    NotAvailable,
+    /// The store is present but cannot take the certificate until the user
+    /// acts, e.g. NSS without certutil or without a Firefox profile;
+    /// contains a hint on what to do
+    NotReady(String),
+    /// The store was not enabled via `TRUST_STORES`
+    Skipped,
+    /// The operation failed; contains the error message
+    Failed(String),
+}
+
+/// Outcome of a trust store operation for a named store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreReport {
+    /// Store name as used in `TRUST_STORES` (system, nss, java or a
+    /// registered store), `nss:chromium` for Chromium's shared NSS database,
+    /// or `anchor:<dir>` for a `FASTCERT_EXTRA_ANCHORS` directory
+    pub store: String,
+    /// What happened for this store
+    pub outcome: StoreOutcome,
+}
+
+/// Per-store results of an install or uninstall across all trust stores.
+///
+/// A failure in one store does not abort the operation; it is recorded as
+/// `StoreOutcome::Failed` and the remaining stores are still processed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstallReport {
+    /// Results in the order the stores were processed
+    pub stores: Vec<StoreReport>,
+    /// SHA-256 fingerprint of the CA certificate, for checking out-of-band
+    /// that the right root was trusted
+    pub fingerprint: Option<String>,
+}
+
+impl InstallReport {
+    /// Get the outcome recorded for a store, if it was processed.
+    pub fn outcome(&self, store: &str) -> Option<&StoreOutcome> {
+        self.stores
+            .iter()
+            .find(|r| r.store == store)
+            .map(|r| &r.outcome)
+    }
+
+    /// Check if any store was modified.
+    ///
+    /// `false` when every store was already in the requested state, was
+    /// skipped or is unavailable, so callers can report "no change".
+    pub fn changed(&self) -> bool {
+        self.stores.iter().any(|r| {
+            matches!(
+                r.outcome,
+                StoreOutcome::Installed | StoreOutcome::Uninstalled
+            )
+        })
+    }
+
+    /// Check if any store reported a failure.
+    pub fn has_failures(&self) -> bool {
+        self.stores
+            .iter()
+            .any(|r| matches!(r.outcome, StoreOutcome::Failed(_)))
+    }
+
+    /// Names of the stores that reported a failure, in processing order.
+    pub fn failed_stores(&self) -> Vec<&str> {
+        self.stores
+            .iter()
+            .filter(|r| matches!(r.outcome, StoreOutcome::Failed(_)))
+            .map(|r| r.store.as_str())
+            .collect()
+    }
+
+    fn record(&mut self, store: &str, outcome: StoreOutcome) {
+        crate::log_event("truststore", &format!("{}: {:?}", store, outcome));
+        self.stores.push(StoreReport {
+            store: store.to_string(),
+            outcome,
+        });
+    }
+}
+
+/// Whether the CA is trusted by a single store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StoreStatus {
+    /// Store name as used in `TRUST_STORES` (system, nss, java)
+    pub store: String,
+    /// Whether the store and its tooling exist on this system
+    pub available: bool,
+    /// Whether the CA certificate is installed in the store
+    pub installed: bool,
+}
 
 /// Parse TRUST_STORES environment variable to determine which stores to use.
 ///
 /// If the environment variable is not set, all available stores are enabled
-/// by default (system, nss, java).
+/// by default (system, nss, java and any [registered](register_store) ones).
 ///
 /// # Returns
 ///
@@ -30,7 +137,9 @@ pub fn get_enabled_stores() -> Vec<String> {
             .collect()
     } else {
         // Default: all stores
-        vec!["system".to_string(), "nss".to_string(), "java".to_string()]
+        let mut stores = vec!["system".to_string(), "nss".to_string(), "java".to_string()];
+        stores.extend(registered_store_names());
+        stores
     }
 }
 
@@ -48,10 +157,20 @@ pub fn is_store_enabled(store: &str) -> bool {
     enabled.contains(&store.to_lowercase())
 }
 
+/// Whether `FASTCERT_OFFLINE` is set.
+///
+/// Offline mode treats NSS and Java as unavailable without probing for
+/// them, so no `certutil` or `keytool` lookups, `JAVA_HOME` checks or
+/// profile scans happen. Only the system store is used.
+pub fn is_offline() -> bool {
+    crate::env_flag("FASTCERT_OFFLINE")
+}
+
 /// Enumerate all available trust stores on this system.
 ///
 /// Checks for the presence of required tools (certutil, keytool) and
-/// returns a list of trust stores that can be used.
+/// returns a list of trust stores that can be used. In
+/// [offline mode](is_offline) only the system store is listed.
 ///
 /// # Returns
 ///
@@ -69,6 +188,10 @@ pub fn enumerate_available_stores() -> Vec<String> {
     #[cfg(target_os = "windows")]
     stores.push("system (Windows Certificate Store)".to_string());
 
+    if is_offline() {
+        return stores;
+    }
+
     // Check for NSS/Firefox
     if nss::NssTrustStore::is_available() && nss::NssTrustStore::has_certutil() {
         stores.push("nss (Firefox/Chromium)".to_string());
@@ -91,8 +214,54 @@ pub mod linux;
 #[cfg(target_os = "windows")]
 pub mod windows;
 
+pub mod anchors;
 pub mod java;
 pub mod nss;
+pub mod selftest;
+
+/// Whether fastcert runs with an effective UID of 0, in which case
+/// privileged steps run directly rather than through `sudo`.
+#[cfg(unix)]
+pub(crate) fn is_elevated() -> bool {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Build a command that runs `program` as root: through `sudo`, or directly
+/// when fastcert is already elevated.
+#[cfg(unix)]
+pub(crate) fn privileged_command(program: impl AsRef<std::ffi::OsStr>) -> std::process::Command {
+    privileged_command_as(program, is_elevated())
+}
+
+#[cfg(unix)]
+fn privileged_command_as(
+    program: impl AsRef<std::ffi::OsStr>,
+    elevated: bool,
+) -> std::process::Command {
+    if elevated {
+        std::process::Command::new(program)
+    } else {
+        let mut command = std::process::Command::new("sudo");
+        command.arg(program);
+        command
+    }
+}
+
+/// Prefix for privileged commands in an install plan
+#[cfg(unix)]
+pub(crate) fn sudo_prefix() -> &'static str {
+    if is_elevated() { "" } else { "sudo " }
+}
+
+/// Tell the user a step will prompt for administrator privileges, unless
+/// fastcert already has them.
+#[cfg(unix)]
+pub(crate) fn note_admin_required() {
+    if !is_elevated() {
+        println!("Note: This will require administrator privileges.");
+    }
+}
 
 /// Common interface for trust store operations.
 ///
@@ -107,64 +276,743 @@ pub trait TrustStore {
 
     /// Remove the certificate from this trust store.
     fn uninstall(&self) -> Result<()>;
+
+    /// Describe the commands and file writes `install()` would perform.
+    ///
+    /// Used for dry runs; nothing is executed.
+    fn install_plan(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
-/// Install CA certificate to macOS trust stores.
+/// Creates a trust store for a CA certificate path and trust store nickname.
+pub type StoreFactory = Box<dyn Fn(&Path, &str) -> Box<dyn TrustStore> + Send + Sync>;
+
+/// Stores added with [`register_store`], in registration order
+static REGISTERED_STORES: Mutex<Vec<(String, StoreFactory)>> = Mutex::new(Vec::new());
+
+/// Register an additional trust store, such as a corporate certificate
+/// service.
 ///
-/// Installs the certificate to the System Keychain and optionally to
-/// Firefox NSS and Java KeyStore if available.
+/// Registered stores take part in [`install_all`] and [`uninstall_all`]
+/// after the built-in ones, and are enabled through `TRUST_STORES` by
+/// `name` like them (all are enabled when it is unset). The factory is
+/// called with the CA certificate path and its trust store nickname.
+/// Registering a name again replaces the earlier factory.
+pub fn register_store(name: &str, factory: StoreFactory) {
+    let name = name.trim().to_lowercase();
+    let mut stores = REGISTERED_STORES.lock().unwrap_or_else(|e| e.into_inner());
+    stores.retain(|(existing, _)| *existing != name);
+    stores.push((name, factory));
+}
+
+/// Remove a store added with [`register_store`].
+///
+/// # Returns
+///
+/// `true` if a store was registered under `name`.
+pub fn unregister_store(name: &str) -> bool {
+    let name = name.trim().to_lowercase();
+    let mut stores = REGISTERED_STORES.lock().unwrap_or_else(|e| e.into_inner());
+    let before = stores.len();
+    stores.retain(|(existing, _)| *existing != name);
+    stores.len() != before
+}
+
+/// Names of the registered stores
+fn registered_store_names() -> Vec<String> {
+    let stores = REGISTERED_STORES.lock().unwrap_or_else(|e| e.into_inner());
+    stores.iter().map(|(name, _)| name.clone()).collect()
+}
+
+/// Create every registered store for a CA certificate
+fn registered_stores(cert_path: &Path, unique_name: &str) -> Vec<(String, Box<dyn TrustStore>)> {
+    let stores = REGISTERED_STORES.lock().unwrap_or_else(|e| e.into_inner());
+    stores
+        .iter()
+        .map(|(name, factory)| (name.clone(), factory(cert_path, unique_name)))
+        .collect()
+}
+
+/// Get the system trust store for the current platform, if supported.
+fn system_store(cert_path: &Path) -> Option<Box<dyn TrustStore>> {
+    #[cfg(target_os = "macos")]
+    return Some(Box::new(macos::MacOSTrustStore::new(cert_path)));
+
+    #[cfg(target_os = "linux")]
+    return Some(Box::new(linux::LinuxTrustStore::new(cert_path)));
+
+    #[cfg(target_os = "windows")]
+    return Some(Box::new(windows::WindowsTrustStore::new(cert_path)));
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    {
+        let _ = cert_path;
+        None
+    }
+}
+
+/// Run an uninstall on a store and convert the result into an outcome.
+fn uninstall_store(store: &dyn TrustStore) -> StoreOutcome {
+    match store.uninstall() {
+        Ok(()) => StoreOutcome::Uninstalled,
+        Err(e) => StoreOutcome::Failed(e.to_string()),
+    }
+}
+
+/// Run an install on a store and convert the result into an outcome.
+///
+/// The store's `check()` decides first, so a store that already trusts the
+/// CA is reported as `AlreadyInstalled` and left untouched.
+fn install_store(store: &dyn TrustStore) -> StoreOutcome {
+    if store.check().unwrap_or(false) {
+        return StoreOutcome::AlreadyInstalled;
+    }
+
+    match store.install() {
+        Ok(()) => StoreOutcome::Installed,
+        Err(e) => StoreOutcome::Failed(e.to_string()),
+    }
+}
+
+/// Every store [`install_all`] reports on, in report order, paired with the
+/// store itself when it is enabled and its tools are available.
+fn planned_stores(
+    cert_path: &Path,
+    unique_name: &str,
+    is_enabled: impl Fn(&str) -> bool,
+) -> Vec<(String, Option<Box<dyn TrustStore>>)> {
+    let nss_available = is_enabled("nss")
+        && nss::NssTrustStore::is_available()
+        && nss::NssTrustStore::has_certutil();
+    let java_available = is_enabled("java")
+        && java::JavaTrustStore::is_available()
+        && java::JavaTrustStore::has_keytool();
+
+    let mut stores: Vec<(String, Option<Box<dyn TrustStore>>)> = vec![
+        (
+            "system".to_string(),
+            system_store(cert_path).filter(|_| is_enabled("system")),
+        ),
+        (
+            "nss".to_string(),
+            nss_available.then(|| {
+                Box::new(nss::NssTrustStore::new(cert_path, unique_name.to_string()))
+                    as Box<dyn TrustStore>
+            }),
+        ),
+        (
+            "java".to_string(),
+            java_available.then(|| {
+                Box::new(java::JavaTrustStore::new(
+                    cert_path,
+                    unique_name.to_string(),
+                )) as Box<dyn TrustStore>
+            }),
+        ),
+    ];
+    for (name, store) in registered_stores(cert_path, unique_name) {
+        let store = is_enabled(&name).then_some(store);
+        stores.push((name, store));
+    }
+    // Extra anchor directories are not subject to TRUST_STORES
+    for dir in anchors::extra_anchor_dirs() {
+        let store = anchors::ExtraAnchorStore::new(cert_path, &dir, unique_name);
+        stores.push((anchors::report_name(&dir), Some(Box::new(store))));
+    }
+    stores
+}
+
+/// Render the commands [`install_all`] would run for the enabled stores as
+/// a script.
+///
+/// The script is PowerShell on Windows and bash elsewhere, with one
+/// commented section per store. It lets an administrator review exactly
+/// what would run with elevated privileges, and run it by hand instead.
+/// Stores that are disabled through `TRUST_STORES` or whose tools are
+/// missing are left out.
+///
+/// # Errors
+///
+/// Returns an error if the CAROOT cannot be determined, the CA certificate
+/// does not exist, or the certificate cannot be parsed.
+pub fn install_script() -> Result<String> {
+    let ca = crate::ca::get_ca()?;
+    if !ca.cert_exists() {
+        return Err(Error::Certificate(
+            "CA certificate does not exist, run 'fastcert -install' first".to_string(),
+        ));
+    }
+
+    let cert_path = ca.cert_path();
+    let unique_name = ca.unique_name()?;
+    let enabled = get_enabled_stores();
+    let is_enabled = |store: &str| enabled.iter().any(|s| s == store);
+
+    let mut script = if cfg!(windows) {
+        String::from("$ErrorActionPreference = \"Stop\"\n")
+    } else {
+        String::from("#!/usr/bin/env bash\nset -euo pipefail\n")
+    };
+    script.push_str(&format!(
+        "# Install {} (SHA-256 {})\n",
+        unique_name,
+        ca.fingerprint_sha256()?
+    ));
+    for (name, store) in planned_stores(&cert_path, &unique_name, is_enabled) {
+        let Some(store) = store else { continue };
+        let plan = store.install_plan();
+        if plan.is_empty() {
+            continue;
+        }
+        script.push_str(&format!("\n# {}\n", name));
+        for step in plan {
+            script.push_str(&step);
+            script.push('\n');
+        }
+    }
+    Ok(script)
+}
+
+/// Install a CA certificate into an explicit set of trust stores.
+///
+/// Unlike `TRUST_STORES`, the store list is passed directly so embedding code
+/// does not need to mutate the process environment. Stores not in the list
+/// are reported as `StoreOutcome::Skipped`. An empty list falls back to
+/// `get_enabled_stores()`.
 ///
 /// # Arguments
 ///
 /// * `cert_path` - Path to the CA certificate file
+/// * `stores` - Store names to install into (system, nss, java or registered)
 ///
 /// # Returns
 ///
-/// `Ok(())` on success, or an error if installation fails.
-#[cfg(target_os = "macos")]
-pub fn install_macos(cert_path: &Path) -> Result<()> {
-    // Install to system store if enabled
-    if is_store_enabled("system") {
-        eprintln!("Installing to system trust store...");
-        let store = macos::MacOSTrustStore::new(cert_path);
-        store.install()?;
+/// An `InstallReport` with one entry per store (system, nss, java, then
+/// registered stores and extra anchor directories).
+///
+/// # Errors
+///
+/// Returns an error if the certificate cannot be read or parsed.
+///
+/// With `FASTCERT_DRY_RUN` set, nothing is installed: the commands each
+/// available store would run are printed and every store is reported as
+/// `StoreOutcome::Skipped`.
+pub fn install_all(cert_path: &Path, stores: &[&str]) -> Result<InstallReport> {
+    let enabled: Vec<String> = if stores.is_empty() {
+        get_enabled_stores()
+    } else {
+        stores.iter().map(|s| s.trim().to_lowercase()).collect()
+    };
+    let is_enabled = |store: &str| enabled.iter().any(|s| s == store);
+
+    let cert_pem = std::fs::read_to_string(cert_path)?;
+    let unique_name = crate::ca::unique_name_from_pem(&cert_pem)?;
+    let mut report = InstallReport {
+        fingerprint: Some(crate::ca::fingerprint_from_pem(&cert_pem)?),
+        ..Default::default()
+    };
+
+    if crate::is_dry_run() {
+        for (name, store) in planned_stores(cert_path, &unique_name, is_enabled) {
+            if let Some(store) = store {
+                for step in store.install_plan() {
+                    crate::info_print(&format!("[dry-run] {}: {}", name, step));
+                }
+            }
+            report.record(&name, StoreOutcome::Skipped);
+        }
+        return Ok(report);
+    }
+
+    let outcome = if !is_enabled("system") {
+        StoreOutcome::Skipped
+    } else {
+        match system_store(cert_path) {
+            Some(store) => install_store(store.as_ref()),
+            None => StoreOutcome::NotAvailable,
+        }
+    };
+    report.record("system", outcome);
+
+    let nss_job: StoreJob = if !is_enabled("nss") {
+        Err(StoreOutcome::Skipped)
+    } else if !nss::NssTrustStore::is_available() {
+        Err(StoreOutcome::NotAvailable)
+    } else {
+        // A missing certutil or Firefox profile is reported as NotReady
+        // with a hint, rather than silently treated as "not available"
+        let store = nss::NssTrustStore::new(cert_path, unique_name.clone());
+        Ok(Box::new(move || install_nss_store(&store)))
+    };
+
+    let java_job: StoreJob = if !is_enabled("java") {
+        Err(StoreOutcome::Skipped)
+    } else if !(java::JavaTrustStore::is_available() && java::JavaTrustStore::has_keytool()) {
+        Err(StoreOutcome::NotAvailable)
+    } else {
+        let store = java::JavaTrustStore::new(cert_path, unique_name.clone());
+        Ok(Box::new(move || install_store(&store)))
+    };
+
+    // NSS and Java need no privileges, so they are installed concurrently
+    for (name, outcome) in install_concurrently(vec![("nss", nss_job), ("java", java_job)]) {
+        if name == "nss" {
+            record_nss(&mut report, outcome);
+        } else {
+            report.record(name, outcome);
+        }
+    }
+
+    for (name, store) in registered_stores(cert_path, &unique_name) {
+        let outcome = if is_enabled(&name) {
+            install_store(store.as_ref())
+        } else {
+            StoreOutcome::Skipped
+        };
+        report.record(&name, outcome);
+    }
+
+    // Extra anchors are not trust stores, so TRUST_STORES does not apply
+    for dir in anchors::extra_anchor_dirs() {
+        let store = anchors::ExtraAnchorStore::new(cert_path, &dir, &unique_name);
+        report.record(&anchors::report_name(&dir), install_store(&store));
+    }
+
+    Ok(report)
+}
+
+/// Report name for Chromium's shared NSS database, `~/.pki/nssdb`
+pub const CHROMIUM_NSS_STORE: &str = "nss:chromium";
+
+/// Record the NSS outcome, repeated as [`CHROMIUM_NSS_STORE`] when
+/// Chromium's shared database is one of the NSS databases, so users can see
+/// Chrome and Chromium were covered.
+fn record_nss(report: &mut InstallReport, outcome: StoreOutcome) {
+    let chromium = !matches!(
+        outcome,
+        StoreOutcome::Skipped | StoreOutcome::NotAvailable | StoreOutcome::NotReady(_)
+    ) && nss::NssTrustStore::uses_chromium_db();
+    report.record("nss", outcome.clone());
+    if chromium {
+        report.record(CHROMIUM_NSS_STORE, outcome);
+    }
+}
+
+/// Install into an NSS store, reporting a missing certutil or Firefox
+/// profile as [`StoreOutcome::NotReady`] rather than a failure.
+fn install_nss_store(store: &nss::NssTrustStore) -> StoreOutcome {
+    if store.check().unwrap_or(false) {
+        return StoreOutcome::AlreadyInstalled;
+    }
+
+    match store.install_nss() {
+        Ok(nss::NssOutcome::Installed) => StoreOutcome::Installed,
+        Ok(outcome) => StoreOutcome::NotReady(outcome.to_string()),
+        Err(e) => StoreOutcome::Failed(e.to_string()),
+    }
+}
+
+/// An install to run, or the outcome to report without touching the store.
+type StoreJob = std::result::Result<Box<dyn FnOnce() -> StoreOutcome + Send>, StoreOutcome>;
+
+/// Install into independent stores on separate threads.
+///
+/// Outcomes are returned in the order of `jobs`, whichever store finishes
+/// first, so reports stay deterministic.
+fn install_concurrently(jobs: Vec<(&str, StoreJob)>) -> Vec<(&str, StoreOutcome)> {
+    std::thread::scope(|scope| {
+        let running: Vec<_> = jobs
+            .into_iter()
+            .map(|(name, job)| {
+                let job = job.map(|install| scope.spawn(install));
+                (name, job)
+            })
+            .collect();
+
+        running
+            .into_iter()
+            .map(|(name, job)| {
+                let outcome = match job {
+                    Ok(handle) => handle.join().unwrap_or_else(|_| {
+                        StoreOutcome::Failed(format!("{} install thread panicked", name))
+                    }),
+                    Err(outcome) => outcome,
+                };
+                (name, outcome)
+            })
+            .collect()
+    })
+}
+
+/// Retry the stores that failed in an earlier install.
+///
+/// Only stores recorded as `StoreOutcome::Failed` are attempted again; the
+/// others keep their previous outcome, so a flaky Java keytool run can be
+/// retried without repeating the system store step (and its sudo prompt).
+///
+/// # Arguments
+///
+/// * `cert_path` - Path to the CA certificate file
+/// * `report` - The report of the earlier [`install_all`]
+///
+/// # Returns
+///
+/// The earlier report with the failed stores' outcomes replaced by the
+/// result of the retry.
+///
+/// # Errors
+///
+/// Returns an error if the certificate cannot be read or parsed.
+pub fn resume_from_report(cert_path: &Path, report: &InstallReport) -> Result<InstallReport> {
+    let failed = report.failed_stores();
+    if failed.is_empty() {
+        return Ok(report.clone());
+    }
+
+    let retried = install_all(cert_path, &failed)?;
+    Ok(retry_failed(report, |store| {
+        retried
+            .outcome(store)
+            .cloned()
+            .unwrap_or(StoreOutcome::Skipped)
+    }))
+}
+
+/// Build a new report, re-running `retry` for each failed store only.
+fn retry_failed(
+    report: &InstallReport,
+    mut retry: impl FnMut(&str) -> StoreOutcome,
+) -> InstallReport {
+    let mut resumed = InstallReport {
+        fingerprint: report.fingerprint.clone(),
+        ..Default::default()
+    };
+    for entry in &report.stores {
+        let outcome = match entry.outcome {
+            StoreOutcome::Failed(_) => retry(&entry.store),
+            _ => entry.outcome.clone(),
+        };
+        resumed.record(&entry.store, outcome);
     }
+    resumed
+}
 
+/// Uninstall the CA certificate from every available trust store.
+///
+/// Resolves the CA certificate from the CAROOT directory and removes it from
+/// the system store, NSS and Java wherever each is available and enabled via
+/// `TRUST_STORES`. A failing store does not stop the others; every outcome is
+/// collected in the returned report.
+///
+/// # Returns
+///
+/// An `InstallReport` with one entry per store (system, nss, java, then
+/// registered stores and extra anchor directories).
+///
+/// # Errors
+///
+/// Returns an error if the CAROOT cannot be determined, the CA certificate
+/// does not exist, or the certificate cannot be parsed.
+pub fn uninstall_all() -> Result<InstallReport> {
     let ca = crate::ca::get_ca()?;
+    if !ca.cert_exists() {
+        return Err(Error::Certificate(
+            "CA certificate does not exist, nothing to uninstall".to_string(),
+        ));
+    }
+
+    let cert_path = ca.cert_path();
     let unique_name = ca.unique_name()?;
+    let mut report = InstallReport {
+        fingerprint: Some(ca.fingerprint_sha256()?),
+        ..Default::default()
+    };
 
-    // Also install to NSS/Firefox if available and enabled
-    if is_store_enabled("nss")
-        && nss::NssTrustStore::is_available()
-        && nss::NssTrustStore::has_certutil()
-    {
-        eprintln!("Installing to Firefox/NSS trust store...");
-        let nss_store = nss::NssTrustStore::new(cert_path, unique_name.clone());
-        if let Err(e) = nss_store.install() {
-            eprintln!("Warning: Failed to install certificate in Firefox: {}", e);
+    let outcome = if !is_store_enabled("system") {
+        StoreOutcome::Skipped
+    } else {
+        match system_store(&cert_path) {
+            Some(store) => uninstall_store(store.as_ref()),
+            None => StoreOutcome::NotAvailable,
+        }
+    };
+    report.record("system", outcome);
+
+    let outcome = if !is_store_enabled("nss") {
+        StoreOutcome::Skipped
+    } else if !(nss::NssTrustStore::is_available() && nss::NssTrustStore::has_certutil()) {
+        StoreOutcome::NotAvailable
+    } else {
+        uninstall_store(&nss::NssTrustStore::new(&cert_path, unique_name.clone()))
+    };
+    record_nss(&mut report, outcome);
+
+    let outcome = if !is_store_enabled("java") {
+        StoreOutcome::Skipped
+    } else if !(java::JavaTrustStore::is_available() && java::JavaTrustStore::has_keytool()) {
+        StoreOutcome::NotAvailable
+    } else {
+        uninstall_store(&java::JavaTrustStore::new(&cert_path, unique_name.clone()))
+    };
+    report.record("java", outcome);
+
+    for (name, store) in registered_stores(&cert_path, &unique_name) {
+        let outcome = if is_store_enabled(&name) {
+            uninstall_store(store.as_ref())
         } else {
-            println!("The local CA is now installed in Firefox trust store!");
+            StoreOutcome::Skipped
+        };
+        report.record(&name, outcome);
+    }
+
+    for dir in anchors::extra_anchor_dirs() {
+        let store = anchors::ExtraAnchorStore::new(&cert_path, &dir, &unique_name);
+        report.record(&anchors::report_name(&dir), uninstall_store(&store));
+    }
+
+    Ok(report)
+}
+
+/// Report where the CA certificate is currently trusted.
+///
+/// Calls each available store's `check()` to see whether the CA from CAROOT
+/// is installed. Stores are listed even when unavailable, so the result can
+/// be shown as a checklist. If the CA does not exist yet, every store reports
+/// `installed: false`.
+///
+/// # Returns
+///
+/// One `StoreStatus` per store (system, nss, java).
+///
+/// # Errors
+///
+/// Returns an error if the CAROOT cannot be determined or the CA certificate
+/// cannot be parsed.
+pub fn trust_status() -> Result<Vec<StoreStatus>> {
+    let ca = crate::ca::get_ca()?;
+    let cert_path = ca.cert_path();
+    let ca_exists = ca.cert_exists();
+    let unique_name = if ca_exists {
+        ca.unique_name()?
+    } else {
+        String::new()
+    };
+
+    let is_installed = |store: &dyn TrustStore| ca_exists && store.check().unwrap_or(false);
+
+    let system = system_store(&cert_path);
+    let nss_available = nss::NssTrustStore::is_available() && nss::NssTrustStore::has_certutil();
+    let java_available =
+        java::JavaTrustStore::is_available() && java::JavaTrustStore::has_keytool();
+
+    Ok(vec![
+        StoreStatus {
+            store: "system".to_string(),
+            available: system.is_some(),
+            installed: system.as_deref().is_some_and(is_installed),
+        },
+        StoreStatus {
+            store: "nss".to_string(),
+            available: nss_available,
+            installed: nss_available
+                && is_installed(&nss::NssTrustStore::new(&cert_path, unique_name.clone())),
+        },
+        StoreStatus {
+            store: "java".to_string(),
+            available: java_available,
+            installed: java_available
+                && is_installed(&java::JavaTrustStore::new(&cert_path, unique_name)),
+        },
+    ])
+}
+
+/// Check whether the CA from CAROOT is trusted by the system store.
+///
+/// Resolves the CA certificate path itself and delegates to the platform
+/// store's `check()`. Only the system store is consulted; use
+/// [`trust_status`] for NSS and Java as well.
+///
+/// # Returns
+///
+/// `false` if the CA does not exist yet or the platform has no system store.
+///
+/// # Errors
+///
+/// Returns an error if the CAROOT cannot be determined or the store check
+/// fails.
+pub fn is_ca_installed() -> Result<bool> {
+    let ca = crate::ca::get_ca()?;
+    if !ca.cert_exists() {
+        return Ok(false);
+    }
+
+    match system_store(&ca.cert_path()) {
+        Some(store) => store.check(),
+        None => Ok(false),
+    }
+}
+
+/// Render trust store statuses in the format selected by `FASTCERT_FORMAT`.
+///
+/// Text output is a checklist, one store per line.
+pub fn format_trust_status(statuses: &[StoreStatus]) -> String {
+    match crate::get_output_format() {
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(statuses).expect("store status is always serializable")
         }
+        OutputFormat::Yaml => statuses
+            .iter()
+            .map(|s| {
+                format!(
+                    "- store: {}\n  available: {}\n  installed: {}\n",
+                    s.store, s.available, s.installed
+                )
+            })
+            .collect(),
+        OutputFormat::Text => statuses
+            .iter()
+            .map(|s| {
+                let mark = if s.installed { "x" } else { " " };
+                let note = if s.available { "" } else { " (not available)" };
+                format!("[{}] {}{}\n", mark, s.store, note)
+            })
+            .collect(),
     }
+}
 
-    // Also install to Java keystore if available and enabled
-    if is_store_enabled("java")
+/// Install into the NSS and Java stores and the `FASTCERT_EXTRA_ANCHORS`
+/// directories for the platform install functions.
+///
+/// Neither store needs elevated privileges and they share no state, so they
+/// run on separate threads; the privileged system store is installed before
+/// this, on the calling thread, so any sudo prompt comes first and alone.
+/// Results are printed in a fixed order once both are done.
+#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
+fn install_user_stores(cert_path: &Path, nss_store_name: &str) -> Result<()> {
+    let ca = crate::ca::get_ca()?;
+    let unique_name = ca.unique_name()?;
+
+    let use_nss = is_store_enabled("nss") && nss::NssTrustStore::is_available();
+    let use_java = is_store_enabled("java")
         && java::JavaTrustStore::is_available()
-        && java::JavaTrustStore::has_keytool()
-    {
+        && java::JavaTrustStore::has_keytool();
+    if use_nss {
+        eprintln!("Installing to the {}...", nss_store_name);
+    }
+    if use_java {
         eprintln!("Installing to Java trust store...");
-        let java_store = java::JavaTrustStore::new(cert_path, unique_name.clone());
-        if let Err(e) = java_store.install() {
+    }
+
+    let (nss_result, java_result) = std::thread::scope(|scope| {
+        let nss = use_nss.then(|| {
+            scope.spawn(|| nss::NssTrustStore::new(cert_path, unique_name.clone()).install_nss())
+        });
+        let java = use_java.then(|| {
+            scope.spawn(|| java::JavaTrustStore::new(cert_path, unique_name.clone()).install())
+        });
+        (
+            nss.map(|handle| join_install(handle, "NSS")),
+            java.map(|handle| join_install(handle, "Java")),
+        )
+    });
+
+    if let Some(result) = nss_result {
+        report_nss_install(result, nss_store_name);
+    }
+    match java_result {
+        Some(Ok(())) => println!("The local CA is now installed in Java trust store!"),
+        Some(Err(e)) => eprintln!(
+            "Warning: Failed to install certificate in Java keystore: {}",
+            e
+        ),
+        None => {}
+    }
+
+    for dir in anchors::extra_anchor_dirs() {
+        let store = anchors::ExtraAnchorStore::new(cert_path, &dir, &unique_name);
+        match store.install() {
+            Ok(()) => println!("The local CA is now copied to {}", store.target().display()),
+            Err(e) => eprintln!("Warning: Failed to copy the CA to {}: {}", dir.display(), e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Wait for an install thread, turning a panic into an error.
+#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
+fn join_install<T>(handle: std::thread::ScopedJoinHandle<'_, Result<T>>, store: &str) -> Result<T> {
+    handle.join().unwrap_or_else(|_| {
+        Err(Error::TrustStore(format!(
+            "{} install thread panicked",
+            store
+        )))
+    })
+}
+
+/// Remove the CA copies from `FASTCERT_EXTRA_ANCHORS` directories for the
+/// platform uninstall functions.
+#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
+fn uninstall_extra_anchors(cert_path: &Path, unique_name: &str) {
+    for dir in anchors::extra_anchor_dirs() {
+        let store = anchors::ExtraAnchorStore::new(cert_path, &dir, unique_name);
+        if let Err(e) = store.uninstall() {
             eprintln!(
-                "Warning: Failed to install certificate in Java keystore: {}",
+                "Warning: Failed to remove the CA from {}: {}",
+                dir.display(),
                 e
             );
-        } else {
-            println!("The local CA is now installed in Java trust store!");
         }
     }
+}
 
-    Ok(())
+/// Print the result of an NSS install done by the platform install functions.
+///
+/// NSS problems never fail the overall install; they are printed as
+/// warnings, with a hint when certutil or a Firefox profile is missing.
+#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
+fn report_nss_install(result: Result<nss::NssOutcome>, store_name: &str) {
+    match result {
+        Ok(nss::NssOutcome::Installed) => {
+            println!("The local CA is now installed in the {}!", store_name);
+        }
+        Ok(outcome) => eprintln!("Warning: {}", outcome),
+        Err(e) => eprintln!(
+            "Warning: Failed to install certificate in the {}: {}",
+            store_name, e
+        ),
+    }
+}
+
+/// Install CA certificate to macOS trust stores.
+///
+/// Installs the certificate to the System Keychain and optionally to
+/// Firefox NSS and Java KeyStore if available.
+///
+/// # Arguments
+///
+/// * `cert_path` - Path to the CA certificate file
+///
+/// # Returns
+///
+/// `Ok(())` on success, or an error if installation fails.
+#[cfg(target_os = "macos")]
+pub fn install_macos(cert_path: &Path) -> Result<()> {
+    if crate::is_dry_run() {
+        install_all(cert_path, &[])?;
+        return Ok(());
+    }
+
+    // Install to system store if enabled
+    if is_store_enabled("system") {
+        eprintln!("Installing to system trust store...");
+        let store = macos::MacOSTrustStore::new(cert_path);
+        store.install()?;
+    }
+
+    // Also install to NSS/Firefox and Java if available and enabled
+    install_user_stores(cert_path, "Firefox trust store")
 }
 
 /// Uninstall CA certificate from macOS trust stores.
@@ -206,6 +1054,8 @@ pub fn uninstall_macos(cert_path: &Path) -> Result<()> {
                 );
             }
         }
+
+        uninstall_extra_anchors(cert_path, &unique_name);
     }
 
     Ok(())
@@ -225,53 +1075,46 @@ pub fn uninstall_macos(cert_path: &Path) -> Result<()> {
 /// `Ok(())` on success, or an error if installation fails.
 #[cfg(target_os = "linux")]
 pub fn install_linux(cert_path: &Path) -> Result<()> {
+    if crate::is_dry_run() {
+        install_all(cert_path, &[])?;
+        return Ok(());
+    }
+
     // Install to system store if enabled
     if is_store_enabled("system") {
         eprintln!("Installing to system trust store...");
         let store = linux::LinuxTrustStore::new(cert_path);
         store.install()?;
-    }
 
-    let ca = crate::ca::get_ca()?;
-    let unique_name = ca.unique_name()?;
-
-    // Also install to NSS/Firefox if available and enabled
-    if is_store_enabled("nss")
-        && nss::NssTrustStore::is_available()
-        && nss::NssTrustStore::has_certutil()
-    {
-        eprintln!("Installing to Firefox/Chromium trust store...");
-        let nss_store = nss::NssTrustStore::new(cert_path, unique_name.clone());
-        if let Err(e) = nss_store.install() {
-            eprintln!(
-                "Warning: Failed to install certificate in Firefox/Chromium: {}",
-                e
-            );
-        } else {
-            println!(
-                "The local CA is now installed in the Firefox and/or Chrome/Chromium trust store!"
-            );
+        if linux::is_wsl() {
+            install_wsl_host(cert_path);
         }
     }
 
-    // Also install to Java keystore if available and enabled
-    if is_store_enabled("java")
-        && java::JavaTrustStore::is_available()
-        && java::JavaTrustStore::has_keytool()
-    {
-        eprintln!("Installing to Java trust store...");
-        let java_store = java::JavaTrustStore::new(cert_path, unique_name.clone());
-        if let Err(e) = java_store.install() {
-            eprintln!(
-                "Warning: Failed to install certificate in Java keystore: {}",
-                e
-            );
-        } else {
-            println!("The local CA is now installed in Java trust store!");
-        }
+    // Also install to NSS/Firefox and Java if available and enabled
+    install_user_stores(cert_path, "Firefox and/or Chrome/Chromium trust store")
+}
+
+/// Install into the Windows host when running under WSL, or explain how to.
+#[cfg(target_os = "linux")]
+fn install_wsl_host(cert_path: &Path) {
+    eprintln!("Note: Running under WSL. Browsers on Windows do not use the Linux trust store.");
+    if !crate::fileutil::command_exists("powershell.exe") {
+        eprintln!(
+            "To trust the CA there too, import {} into the Windows \"Trusted Root Certification Authorities\" store.",
+            cert_path.display()
+        );
+        return;
     }
 
-    Ok(())
+    eprintln!("Installing to the Windows certificate store...");
+    match linux::install_into_windows_host(cert_path) {
+        Ok(()) => eprintln!("The local CA is now installed in the Windows certificate store."),
+        Err(e) => eprintln!(
+            "Warning: Failed to install certificate into the Windows store: {}",
+            e
+        ),
+    }
 }
 
 /// Uninstall CA certificate from Linux trust stores.
@@ -313,6 +1156,8 @@ pub fn uninstall_linux(cert_path: &Path) -> Result<()> {
                 );
             }
         }
+
+        uninstall_extra_anchors(cert_path, &unique_name);
     }
 
     Ok(())
@@ -332,6 +1177,11 @@ pub fn uninstall_linux(cert_path: &Path) -> Result<()> {
 /// `Ok(())` on success, or an error if installation fails.
 #[cfg(target_os = "windows")]
 pub fn install_windows(cert_path: &Path) -> Result<()> {
+    if crate::is_dry_run() {
+        install_all(cert_path, &[])?;
+        return Ok(());
+    }
+
     // Install to system store if enabled
     if is_store_enabled("system") {
         eprintln!("Installing to system trust store...");
@@ -339,41 +1189,8 @@ pub fn install_windows(cert_path: &Path) -> Result<()> {
         store.install()?;
     }
 
-    let ca = crate::ca::get_ca()?;
-    let unique_name = ca.unique_name()?;
-
-    // Also install to NSS/Firefox if available and enabled
-    if is_store_enabled("nss")
-        && nss::NssTrustStore::is_available()
-        && nss::NssTrustStore::has_certutil()
-    {
-        eprintln!("Installing to Firefox trust store...");
-        let nss_store = nss::NssTrustStore::new(cert_path, unique_name.clone());
-        if let Err(e) = nss_store.install() {
-            eprintln!("Warning: Failed to install certificate in Firefox: {}", e);
-        } else {
-            println!("The local CA is now installed in Firefox trust store!");
-        }
-    }
-
-    // Also install to Java keystore if available and enabled
-    if is_store_enabled("java")
-        && java::JavaTrustStore::is_available()
-        && java::JavaTrustStore::has_keytool()
-    {
-        eprintln!("Installing to Java trust store...");
-        let java_store = java::JavaTrustStore::new(cert_path, unique_name.clone());
-        if let Err(e) = java_store.install() {
-            eprintln!(
-                "Warning: Failed to install certificate in Java keystore: {}",
-                e
-            );
-        } else {
-            println!("The local CA is now installed in Java trust store!");
-        }
-    }
-
-    Ok(())
+    // Also install to NSS/Firefox and Java if available and enabled
+    install_user_stores(cert_path, "Firefox trust store")
 }
 
 /// Uninstall CA certificate from Windows trust stores.
@@ -415,6 +1232,8 @@ pub fn uninstall_windows(cert_path: &Path) -> Result<()> {
                 );
             }
         }
+
+        uninstall_extra_anchors(cert_path, &unique_name);
     }
 
     Ok(())
@@ -424,6 +1243,21 @@ pub fn uninstall_windows(cert_path: &Path) -> Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    #[cfg(unix)]
+    fn test_privileged_command_skips_sudo_when_elevated() {
+        let command = privileged_command_as("update-ca-certificates", true);
+        assert_eq!(command.get_program(), "update-ca-certificates");
+        assert_eq!(command.get_args().count(), 0);
+
+        let command = privileged_command_as("update-ca-certificates", false);
+        assert_eq!(command.get_program(), "sudo");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            ["update-ca-certificates"]
+        );
+    }
+
     #[test]
     fn test_enumerate_available_stores() {
         let stores = enumerate_available_stores();
@@ -438,8 +1272,12 @@ mod tests {
         );
     }
 
+    /// Serializes tests that change `TRUST_STORES` or the store registry
+    static TEST_LOCK: Mutex<()> = Mutex::new(());
+
     #[test]
     fn test_get_enabled_stores_default() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         // Clear TRUST_STORES env var for this test
         unsafe {
             std::env::remove_var("TRUST_STORES");
@@ -453,6 +1291,7 @@ mod tests {
 
     #[test]
     fn test_is_store_enabled() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         unsafe {
             std::env::remove_var("TRUST_STORES");
         }
@@ -461,4 +1300,207 @@ mod tests {
         assert!(is_store_enabled("nss"));
         assert!(is_store_enabled("java"));
     }
+
+    /// In-memory store for exercising outcome reporting
+    struct FakeStore {
+        installed: std::cell::Cell<bool>,
+    }
+
+    impl TrustStore for FakeStore {
+        fn check(&self) -> Result<bool> {
+            Ok(self.installed.get())
+        }
+
+        fn install(&self) -> Result<()> {
+            self.installed.set(true);
+            Ok(())
+        }
+
+        fn uninstall(&self) -> Result<()> {
+            self.installed.set(false);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_install_store_twice_reports_already_installed() {
+        let store = FakeStore {
+            installed: std::cell::Cell::new(false),
+        };
+
+        let mut report = InstallReport::default();
+        report.record("system", install_store(&store));
+        assert_eq!(report.outcome("system"), Some(&StoreOutcome::Installed));
+        assert!(report.changed());
+
+        let mut report = InstallReport::default();
+        report.record("system", install_store(&store));
+        assert_eq!(
+            report.outcome("system"),
+            Some(&StoreOutcome::AlreadyInstalled)
+        );
+        assert!(!report.changed());
+    }
+
+    /// Store that logs calls into shared memory
+    struct MemoryStore {
+        log: std::sync::Arc<Mutex<Vec<String>>>,
+        unique_name: String,
+    }
+
+    impl TrustStore for MemoryStore {
+        fn check(&self) -> Result<bool> {
+            Ok(false)
+        }
+
+        fn install(&self) -> Result<()> {
+            let entry = format!("install {}", self.unique_name);
+            self.log.lock().unwrap().push(entry);
+            Ok(())
+        }
+
+        fn uninstall(&self) -> Result<()> {
+            self.log.lock().unwrap().push("uninstall".to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_registered_store_takes_part_in_install_all() {
+        /// Unregisters the store even if an assertion fails
+        struct Registered;
+        impl Drop for Registered {
+            fn drop(&mut self) {
+                unregister_store("corp-test");
+            }
+        }
+
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut ca = crate::ca::CertificateAuthority::new(temp_dir.path().to_path_buf());
+        ca.init_ca().unwrap();
+        let log = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let factory_log = log.clone();
+        register_store(
+            "Corp-Test",
+            Box::new(move |_, unique_name| {
+                Box::new(MemoryStore {
+                    log: factory_log.clone(),
+                    unique_name: unique_name.to_string(),
+                })
+            }),
+        );
+        let registered = Registered;
+
+        let report = install_all(&ca.cert_path(), &["corp-test"]).unwrap();
+        assert_eq!(report.outcome("corp-test"), Some(&StoreOutcome::Installed));
+        assert_eq!(report.outcome("system"), Some(&StoreOutcome::Skipped));
+        assert_eq!(
+            *log.lock().unwrap(),
+            [format!("install {}", ca.unique_name().unwrap())]
+        );
+
+        // Not enabled, not touched
+        let report = install_all(&ca.cert_path(), &["none"]).unwrap();
+        assert_eq!(report.outcome("corp-test"), Some(&StoreOutcome::Skipped));
+        assert_eq!(log.lock().unwrap().len(), 1);
+        assert!(registered_store_names().contains(&"corp-test".to_string()));
+
+        drop(registered);
+        assert!(!registered_store_names().contains(&"corp-test".to_string()));
+        assert!(!unregister_store("corp-test"));
+    }
+
+    #[test]
+    fn test_resume_only_retries_failed_stores() {
+        let mut report = InstallReport::default();
+        report.record("system", StoreOutcome::Installed);
+        report.record("nss", StoreOutcome::AlreadyInstalled);
+        report.record("java", StoreOutcome::Failed("keytool error".to_string()));
+        assert_eq!(report.failed_stores(), ["java"]);
+
+        let java = FakeStore {
+            installed: std::cell::Cell::new(false),
+        };
+        let mut retried = Vec::new();
+        let resumed = retry_failed(&report, |store| {
+            retried.push(store.to_string());
+            install_store(&java)
+        });
+
+        assert_eq!(retried, ["java"]);
+        assert_eq!(resumed.outcome("system"), Some(&StoreOutcome::Installed));
+        assert_eq!(
+            resumed.outcome("nss"),
+            Some(&StoreOutcome::AlreadyInstalled)
+        );
+        assert_eq!(resumed.outcome("java"), Some(&StoreOutcome::Installed));
+        assert!(!resumed.has_failures());
+    }
+
+    /// Store that takes a while to install, to shuffle completion order
+    struct SlowStore {
+        delay: std::time::Duration,
+        fail: bool,
+    }
+
+    impl TrustStore for SlowStore {
+        fn check(&self) -> Result<bool> {
+            Ok(false)
+        }
+
+        fn install(&self) -> Result<()> {
+            std::thread::sleep(self.delay);
+            if self.fail {
+                return Err(Error::TrustStore("keytool error".to_string()));
+            }
+            Ok(())
+        }
+
+        fn uninstall(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_install_concurrently_keeps_order() {
+        let slow = |millis, fail| -> StoreJob {
+            let store = SlowStore {
+                delay: std::time::Duration::from_millis(millis),
+                fail,
+            };
+            Ok(Box::new(move || install_store(&store)))
+        };
+        // The first store finishes last
+        let outcomes = install_concurrently(vec![
+            ("nss", slow(100, false)),
+            ("java", slow(1, true)),
+            ("extra", Err(StoreOutcome::NotAvailable)),
+        ]);
+
+        assert_eq!(
+            outcomes,
+            vec![
+                ("nss", StoreOutcome::Installed),
+                (
+                    "java",
+                    StoreOutcome::Failed("Trust store operation failed: keytool error".to_string())
+                ),
+                ("extra", StoreOutcome::NotAvailable),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_install_report_outcomes() {
+        let mut report = InstallReport::default();
+        report.record("system", StoreOutcome::Uninstalled);
+        report.record("nss", StoreOutcome::NotAvailable);
+        assert_eq!(report.outcome("system"), Some(&StoreOutcome::Uninstalled));
+        assert_eq!(report.outcome("java"), None);
+        assert!(!report.has_failures());
+
+        report.record("java", StoreOutcome::Failed("keytool error".to_string()));
+        assert!(report.has_failures());
+    }
 }
@@ -1,9 +1,46 @@
 //! NSS/Firefox trust store
 
 use super::TrustStore;
+use crate::fileutil::run_command;
 use crate::{Error, Result};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output};
+use std::time::Duration;
+
+/// Backoff before each retry of a certutil call that hit a locked database
+const LOCK_RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_millis(200),
+    Duration::from_millis(400),
+    Duration::from_millis(800),
+];
+
+/// What an NSS install did, telling apart the reasons nothing was installed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NssOutcome {
+    /// The certificate was added to every NSS database found
+    Installed,
+    /// certutil is not installed, so no NSS database can be changed
+    CertutilMissing,
+    /// certutil is available but there is no NSS database (Firefox has
+    /// never been started, and there is no `~/.pki/nssdb`)
+    NoProfiles,
+}
+
+impl std::fmt::Display for NssOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NssOutcome::Installed => write!(f, "installed in the NSS trust store"),
+            NssOutcome::CertutilMissing => write!(
+                f,
+                "certutil not found; install the NSS tools (e.g. libnss3-tools or nss) so Firefox trusts the local CA"
+            ),
+            NssOutcome::NoProfiles => write!(
+                f,
+                "Firefox profile not found; start Firefox once to create one"
+            ),
+        }
+    }
+}
 
 pub struct NssTrustStore {
     cert_path: PathBuf,
@@ -18,12 +55,18 @@ impl NssTrustStore {
         }
     }
 
+    /// Get the shared NSS database Chrome and Chromium use on Linux,
+    /// `~/.pki/nssdb`
+    pub fn chromium_db() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".pki/nssdb"))
+    }
+
     /// Get NSS database directories (user home and system-wide)
     fn get_nss_dbs() -> Vec<PathBuf> {
         let mut dbs = Vec::new();
 
         if let Some(home) = dirs::home_dir() {
-            // Standard NSS database location
+            // Standard NSS database location, shared by Chrome and Chromium
             dbs.push(home.join(".pki/nssdb"));
 
             // Snapcraft Chromium
@@ -102,10 +145,73 @@ impl NssTrustStore {
         false
     }
 
-    /// Check if NSS is available
+    /// Check if Chrome or Chromium is installed, on Linux where they read
+    /// certificates from the shared NSS database
+    #[cfg(target_os = "linux")]
+    fn has_chromium() -> bool {
+        let names = [
+            "chromium",
+            "chromium-browser",
+            "google-chrome",
+            "google-chrome-stable",
+        ];
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        std::env::split_paths(&path).any(|dir| names.iter().any(|name| dir.join(name).is_file()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn has_chromium() -> bool {
+        false
+    }
+
+    /// Get Chromium's shared NSS database if Chromium is installed but has
+    /// not created it yet
+    fn missing_chromium_db() -> Option<PathBuf> {
+        Self::chromium_db().filter(|db| {
+            Self::has_chromium() && !db.join("cert9.db").exists() && !db.join("cert8.db").exists()
+        })
+    }
+
+    /// Create Chromium's shared NSS database if it is missing, so installing
+    /// makes Chromium trust the CA too. A failure only means Chromium is
+    /// left out.
+    fn ensure_chromium_db() {
+        let Some(db) = Self::missing_chromium_db() else {
+            return;
+        };
+
+        let created = std::fs::create_dir_all(&db)
+            .map_err(Error::from)
+            .and_then(|_| {
+                let db_arg = format!("sql:{}", db.display());
+                Self::exec_certutil(&["-N", "--empty-password", "-d", &db_arg])
+            });
+        match created {
+            Ok(output) if output.status.success() => crate::verbose_print(&format!(
+                "Created the Chromium NSS database in {}",
+                db.display()
+            )),
+            Ok(output) => eprintln!(
+                "Warning: Failed to create the Chromium NSS database in {}: {}",
+                db.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => eprintln!(
+                "Warning: Failed to create the Chromium NSS database in {}: {}",
+                db.display(),
+                e
+            ),
+        }
+    }
+
+    /// Check if NSS is available. Always `false` in offline mode.
     pub fn is_available() -> bool {
-        // Check if Firefox or NSS databases exist
-        if Self::has_firefox() {
+        if super::is_offline() {
+            return false;
+        }
+
+        // Check if Firefox, Chromium or NSS databases exist
+        if Self::has_firefox() || Self::has_chromium() {
             return true;
         }
 
@@ -118,6 +224,21 @@ impl NssTrustStore {
         false
     }
 
+    /// Get the NSS database directories certificates are installed into:
+    /// Chromium's shared database, the system-wide one and Firefox profiles
+    /// that exist on this system.
+    pub fn profiles() -> Vec<PathBuf> {
+        Self::find_nss_profiles()
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect()
+    }
+
+    /// Whether Chromium's shared database is among the NSS profiles
+    pub fn uses_chromium_db() -> bool {
+        Self::chromium_db().is_some_and(|db| Self::profiles().contains(&db))
+    }
+
     /// Find all NSS profile directories with databases
     fn find_nss_profiles() -> Vec<(String, PathBuf)> {
         let mut profiles = Vec::new();
@@ -212,92 +333,55 @@ impl NssTrustStore {
         None
     }
 
-    /// Check if certutil is available
+    /// Check if certutil is available. Always `false` in offline mode.
     pub fn has_certutil() -> bool {
-        Self::find_certutil().is_some()
+        !super::is_offline() && Self::find_certutil().is_some()
     }
 
     /// Execute certutil command
-    /// If the command fails with SEC_ERROR_READ_ONLY on Unix, retry with sudo
+    /// If the command fails with SEC_ERROR_READ_ONLY on Unix, retry with sudo.
+    /// Each attempt is limited by `FASTCERT_CMD_TIMEOUT`.
     fn exec_certutil(args: &[&str]) -> Result<std::process::Output> {
         let certutil_path = Self::find_certutil()
             .ok_or_else(|| Error::TrustStore("certutil not found".to_string()))?;
 
-        let output = Command::new(&certutil_path)
-            .args(args)
-            .output()
-            .map_err(|e| Error::CommandFailed(format!("Failed to execute certutil: {}", e)))?;
+        let output = run_command(Command::new(&certutil_path).args(args))?;
 
         // Check if we need to retry with sudo (SEC_ERROR_READ_ONLY on Unix)
         #[cfg(unix)]
         {
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                if stderr.contains("SEC_ERROR_READ_ONLY") {
+                // Running as root already, so the failure is not a permission issue
+                if stderr.contains("SEC_ERROR_READ_ONLY") && !super::is_elevated() {
                     // Retry with sudo
-                    let output = Command::new("sudo")
-                        .arg(&certutil_path)
-                        .args(args)
-                        .output()
-                        .map_err(|e| {
-                            Error::CommandFailed(format!(
-                                "Failed to execute certutil with sudo: {}",
-                                e
-                            ))
-                        })?;
-                    return Ok(output);
+                    return run_command(super::privileged_command(&certutil_path).args(args));
                 }
             }
         }
 
         Ok(output)
     }
-}
-
-impl TrustStore for NssTrustStore {
-    fn check(&self) -> Result<bool> {
-        if !Self::has_certutil() {
-            return Ok(false);
-        }
-
-        let profiles = Self::find_nss_profiles();
-        if profiles.is_empty() {
-            return Ok(false);
-        }
-
-        let mut success = true;
-        for (db_type, profile_path) in profiles {
-            let db_arg = format!("{}:{}", db_type, profile_path.display());
-
-            let args = vec!["-V", "-d", &db_arg, "-u", "L", "-n", &self.unique_name];
-
-            match Self::exec_certutil(&args) {
-                Ok(output) => {
-                    if !output.status.success() {
-                        success = false;
-                    }
-                }
-                Err(_) => {
-                    success = false;
-                }
-            }
-        }
-
-        Ok(success)
-    }
 
-    fn install(&self) -> Result<()> {
+    /// Install the certificate into every NSS database found.
+    ///
+    /// Unlike [`TrustStore::install`], a missing certutil or the absence of
+    /// any Firefox profile is reported as an [`NssOutcome`] rather than an
+    /// error, so callers can explain what the user needs to do.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if certutil fails for a database or the install
+    /// cannot be verified.
+    pub fn install_nss(&self) -> Result<NssOutcome> {
         if !Self::has_certutil() {
-            return Err(Error::TrustStore(
-                "certutil not found. Please install NSS tools.".to_string(),
-            ));
+            return Ok(NssOutcome::CertutilMissing);
         }
 
+        Self::ensure_chromium_db();
         let profiles = Self::find_nss_profiles();
         if profiles.is_empty() {
-            return Err(Error::TrustStore(
-                "No NSS security databases found. Please start Firefox at least once.".to_string(),
-            ));
+            return Ok(NssOutcome::NoProfiles);
         }
 
         let cert_path_str = self
@@ -320,7 +404,7 @@ impl TrustStore for NssTrustStore {
                 cert_path_str,
             ];
 
-            let output = Self::exec_certutil(&args)?;
+            let output = Self::exec_certutil_retrying(&args)?;
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
                 return Err(Error::TrustStore(format!(
@@ -339,7 +423,120 @@ impl TrustStore for NssTrustStore {
             ));
         }
 
-        Ok(())
+        Ok(NssOutcome::Installed)
+    }
+
+    /// Execute a certutil command that modifies a database, retrying while
+    /// the database is locked (e.g. by a running Firefox)
+    fn exec_certutil_retrying(args: &[&str]) -> Result<Output> {
+        retry_while_locked(|| Self::exec_certutil(args), &LOCK_RETRY_DELAYS)
+    }
+}
+
+/// Check certutil stderr for signs of a database held open by another process.
+///
+/// Only SQLite's lock messages count; other `SEC_ERROR_*` failures such as a
+/// corrupt database are returned to the caller instead of being retried.
+fn is_db_locked(stderr: &str) -> bool {
+    stderr.contains("database is locked") || stderr.contains("SQLITE_BUSY")
+}
+
+/// Run a command, retrying after each delay while it fails because the NSS
+/// database is locked.
+///
+/// Returns the first output that is not a lock failure. If the database is
+/// still locked after the last retry, returns `Error::TrustStore` asking the
+/// user to close their browser.
+fn retry_while_locked<F>(mut run: F, delays: &[Duration]) -> Result<Output>
+where
+    F: FnMut() -> Result<Output>,
+{
+    let mut delays = delays.iter();
+    loop {
+        let output = run()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() || !is_db_locked(&stderr) {
+            return Ok(output);
+        }
+
+        match delays.next() {
+            Some(delay) => {
+                crate::log_event(
+                    "truststore",
+                    &format!("NSS database busy, retrying in {:?}", delay),
+                );
+                std::thread::sleep(*delay);
+            }
+            None => {
+                return Err(Error::TrustStore(format!(
+                    "NSS database is locked: {}. Close Firefox or other browsers using it and try again",
+                    stderr.trim()
+                )));
+            }
+        }
+    }
+}
+
+impl TrustStore for NssTrustStore {
+    fn check(&self) -> Result<bool> {
+        if !Self::has_certutil() {
+            return Ok(false);
+        }
+
+        let profiles = Self::find_nss_profiles();
+        if profiles.is_empty() {
+            return Ok(false);
+        }
+
+        let mut success = true;
+        for (db_type, profile_path) in profiles {
+            let db_arg = format!("{}:{}", db_type, profile_path.display());
+
+            let args = vec!["-V", "-d", &db_arg, "-u", "L", "-n", &self.unique_name];
+
+            match Self::exec_certutil(&args) {
+                Ok(output) => {
+                    if !output.status.success() {
+                        success = false;
+                    }
+                }
+                Err(_) => {
+                    success = false;
+                }
+            }
+        }
+
+        Ok(success)
+    }
+
+    fn install(&self) -> Result<()> {
+        match self.install_nss()? {
+            NssOutcome::Installed => Ok(()),
+            outcome => Err(Error::TrustStore(outcome.to_string())),
+        }
+    }
+
+    fn install_plan(&self) -> Vec<String> {
+        let mut plan = Vec::new();
+        let mut profiles = Self::find_nss_profiles();
+        if let Some(db) = Self::missing_chromium_db() {
+            plan.push(format!("mkdir -p \"{}\"", db.display()));
+            plan.push(format!(
+                "certutil -N --empty-password -d \"sql:{}\"",
+                db.display()
+            ));
+            profiles.insert(0, ("sql".to_string(), db));
+        }
+        plan.extend(profiles.iter().map(|(db_type, profile_path)| {
+            format!(
+                "certutil -A -d \"{}:{}\" -t C,, -n \"{}\" -i \"{}\"",
+                db_type,
+                profile_path.display(),
+                self.unique_name,
+                self.cert_path.display()
+            )
+        }));
+        plan
     }
 
     fn uninstall(&self) -> Result<()> {
@@ -376,18 +573,99 @@ impl TrustStore for NssTrustStore {
             // Certificate exists, delete it
             let delete_args = vec!["-D", "-d", &db_arg, "-n", &self.unique_name];
 
-            let output = Self::exec_certutil(&delete_args)?;
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                // Log but don't fail on uninstall errors
-                eprintln!(
+            // Log but don't fail on uninstall errors, so the remaining
+            // profiles are still cleaned up
+            match Self::exec_certutil_retrying(&delete_args) {
+                Ok(output) if output.status.success() => {}
+                Ok(output) => eprintln!(
                     "Warning: Failed to remove certificate from NSS database {}: {}",
                     profile_path.display(),
-                    stderr
-                );
+                    String::from_utf8_lossy(&output.stderr)
+                ),
+                Err(e) => eprintln!(
+                    "Warning: Failed to remove certificate from NSS database {}: {}",
+                    profile_path.display(),
+                    e
+                ),
             }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_retry_while_locked() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let counter = temp_dir.path().join("attempts");
+        let certutil = temp_dir.path().join("certutil");
+        std::fs::write(
+            &certutil,
+            format!(
+                "#!/bin/sh\n\
+                 echo x >> {0}\n\
+                 if [ $(wc -l < {0}) -le 2 ]; then\n\
+                 echo 'certutil: SEC_ERROR_BAD_DATABASE: database is locked' >&2\n\
+                 exit 255\n\
+                 fi\n",
+                counter.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&certutil, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let delays = [Duration::from_millis(1); 3];
+        let output =
+            retry_while_locked(|| run_command(&mut Command::new(&certutil)), &delays).unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&counter).unwrap().lines().count(),
+            3
+        );
+
+        // Still locked after every retry
+        std::fs::write(&counter, "").unwrap();
+        let err = retry_while_locked(|| run_command(&mut Command::new(&certutil)), &delays[..1])
+            .unwrap_err();
+        assert!(err.to_string().contains("Close Firefox"), "{}", err);
+
+        // Other NSS errors are returned at once, not retried as a lock
+        let broken = temp_dir.path().join("certutil-broken");
+        std::fs::write(
+            &broken,
+            format!(
+                "#!/bin/sh\n\
+                 echo x >> {0}\n\
+                 echo 'certutil: SEC_ERROR_BAD_DATABASE: security library: bad database.' >&2\n\
+                 exit 255\n",
+                counter.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&broken, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(&counter, "").unwrap();
+        let output =
+            retry_while_locked(|| run_command(&mut Command::new(&broken)), &delays).unwrap();
+        assert!(!output.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&counter).unwrap().lines().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_is_db_locked() {
+        assert!(is_db_locked("certutil: database is locked"));
+        assert!(is_db_locked("SEC_ERROR_BAD_DATABASE: database is locked"));
+        assert!(!is_db_locked("SEC_ERROR_BAD_DATABASE: bad database."));
+        assert!(!is_db_locked("SEC_ERROR_BAD_DER"));
+        assert!(!is_db_locked("certutil: could not find certificate named"));
+    }
+}
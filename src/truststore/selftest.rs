@@ -0,0 +1,131 @@
+//! TLS handshake self-test
+//!
+//! Checking a store's contents says the CA was added, not that clients
+//! accept it. The self-test serves a throwaway certificate from a local
+//! listener and connects to it with a client that only trusts the
+//! platform's roots, which is what browsers and other tools see.
+
+use crate::{Error, Result};
+use rcgen::{KeyPair, PKCS_ECDSA_P256_SHA256};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, ServerConfig, ServerConnection};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long either side of the handshake waits for the other
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Check that a certificate from the local CA passes TLS verification.
+///
+/// Issues a throwaway certificate for `hosts` (`127.0.0.1` if empty),
+/// serves it from a TLS listener on a loopback port and connects a client
+/// that loads the platform's trusted roots, using the first host as the
+/// server name. Nothing is written to disk. `SSL_CERT_FILE` and
+/// `SSL_CERT_DIR` replace the platform roots if set.
+///
+/// # Returns
+///
+/// `true` if the handshake succeeds, `false` if the client rejects the
+/// certificate, meaning the CA is not trusted (or the certificate does not
+/// cover the first host).
+///
+/// # Errors
+///
+/// Returns an error if no CA exists, a host is invalid, or the handshake
+/// fails for a reason other than certificate verification.
+pub fn self_test_tls(hosts: &[String]) -> Result<bool> {
+    let hosts = if hosts.is_empty() {
+        vec!["127.0.0.1".to_string()]
+    } else {
+        hosts.to_vec()
+    };
+    let server_name = ServerName::try_from(hosts[0].clone())
+        .map_err(|e| Error::InvalidHostname(format!("{}: {}", hosts[0], e)))?;
+
+    let (chain, key) = issue_test_certificate(&hosts)?;
+    let tls_error = |e: rustls::Error| Error::TrustStore(format!("TLS self-test failed: {}", e));
+    let server_config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(chain, key)
+        .map_err(tls_error)?;
+
+    let mut roots = RootCertStore::empty();
+    roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+    let client_config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let server = std::thread::spawn(move || -> std::io::Result<()> {
+        let (mut socket, _) = listener.accept()?;
+        socket.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let mut conn =
+            ServerConnection::new(Arc::new(server_config)).map_err(std::io::Error::other)?;
+        while conn.is_handshaking() {
+            conn.complete_io(&mut socket)?;
+        }
+        Ok(())
+    });
+
+    let mut socket = TcpStream::connect(addr)?;
+    socket.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let mut conn =
+        ClientConnection::new(Arc::new(client_config), server_name).map_err(tls_error)?;
+    let mut outcome = Ok(());
+    while conn.is_handshaking() && outcome.is_ok() {
+        outcome = conn.complete_io(&mut socket).map(|_| ());
+    }
+    // The server fails too once the client gives up; only the client's
+    // view of the handshake matters
+    drop(socket);
+    let _ = server.join();
+
+    match outcome {
+        Ok(()) => Ok(true),
+        Err(e) => match e
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+        {
+            Some(rustls::Error::InvalidCertificate(reason)) => {
+                crate::verbose_print(&format!(
+                    "TLS self-test: certificate rejected: {:?}",
+                    reason
+                ));
+                Ok(false)
+            }
+            _ => Err(Error::TrustStore(format!("TLS self-test failed: {}", e))),
+        },
+    }
+}
+
+/// Issue an in-memory server certificate from the current CA, returning the
+/// chain to serve and its private key.
+fn issue_test_certificate(
+    hosts: &[String],
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+    let mut ca = crate::ca::get_ca()?;
+    if !ca.cert_exists() {
+        return Err(Error::CARootNotFound);
+    }
+    ca.load()?;
+    let (ca_cert_pem, ca_key_pem) = ca.signing_pems()?;
+    let issuer = crate::ca::load_issuer(&ca_cert_pem, &ca_key_pem)?;
+
+    let mut params = crate::cert::create_cert_params(hosts)?;
+    crate::cert::add_server_auth(&mut params);
+    let key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256)
+        .map_err(|e| Error::Certificate(format!("Failed to generate ECDSA key pair: {}", e)))?;
+    let cert = params
+        .signed_by(&key, &issuer)
+        .map_err(|e| Error::Certificate(format!("Failed to create signed certificate: {}", e)))?;
+
+    let mut chain = vec![cert.der().clone()];
+    // Clients only know the root, so an intermediate has to be sent along
+    if ca.intermediate_exists() {
+        chain.push(crate::cert::parse_pem_cert(ca_cert_pem.as_bytes())?.into());
+    }
+    let key = PrivatePkcs8KeyDer::from(key.serialize_der()).into();
+    Ok((chain, key))
+}
@@ -2,6 +2,7 @@
 
 use super::TrustStore;
 use crate::{Error, Result};
+use sha2::{Digest, Sha256};
 use std::path::Path;
 
 #[cfg(target_os = "windows")]
@@ -26,6 +27,32 @@ fn windows_error_string(error: windows::core::Error) -> String {
     )
 }
 
+/// Matches certificates from the store against our CA certificate.
+///
+/// The SHA-256 fingerprint of our certificate is computed once; stored
+/// certificates are compared by length, then fingerprint, and only byte for
+/// byte when the fingerprints agree.
+struct CertMatcher<'a> {
+    cert_der: &'a [u8],
+    fingerprint: [u8; 32],
+}
+
+impl<'a> CertMatcher<'a> {
+    fn new(cert_der: &'a [u8]) -> Self {
+        Self {
+            cert_der,
+            fingerprint: Sha256::digest(cert_der).into(),
+        }
+    }
+
+    /// Check whether a DER certificate from the store is ours.
+    fn cert_matches(&self, stored_cert: &[u8]) -> bool {
+        stored_cert.len() == self.cert_der.len()
+            && Sha256::digest(stored_cert).as_slice() == self.fingerprint
+            && stored_cert == self.cert_der
+    }
+}
+
 pub struct WindowsTrustStore {
     cert_path: String,
 }
@@ -108,6 +135,7 @@ impl WindowsRootStore {
     }
 
     fn has_cert(&self, cert_der: &[u8]) -> Result<bool> {
+        let matcher = CertMatcher::new(cert_der);
         unsafe {
             let mut prev_cert: *const CERT_CONTEXT = ptr::null();
 
@@ -124,7 +152,7 @@ impl WindowsRootStore {
                     cert_context.cbCertEncoded as usize,
                 );
 
-                if stored_cert == cert_der {
+                if matcher.cert_matches(stored_cert) {
                     return Ok(true);
                 }
             }
@@ -162,6 +190,7 @@ impl WindowsRootStore {
     }
 
     fn delete_cert(&self, cert_der: &[u8]) -> Result<bool> {
+        let matcher = CertMatcher::new(cert_der);
         unsafe {
             let mut prev_cert: *const CERT_CONTEXT = ptr::null();
             let mut deleted_any = false;
@@ -179,7 +208,7 @@ impl WindowsRootStore {
                     cert_context.cbCertEncoded as usize,
                 );
 
-                if stored_cert == cert_der {
+                if matcher.cert_matches(stored_cert) {
                     // Duplicate the context so it doesn't stop enumeration when we delete it
                     let dup_cert = CertDuplicateCertificateContext(Some(prev_cert));
 
@@ -224,6 +253,13 @@ impl Drop for WindowsRootStore {
 struct WindowsRootStore;
 
 impl TrustStore for WindowsTrustStore {
+    fn install_plan(&self) -> Vec<String> {
+        vec![format!(
+            "Import-Certificate -FilePath \"{}\" -CertStoreLocation Cert:\\CurrentUser\\Root",
+            self.cert_path
+        )]
+    }
+
     fn check(&self) -> Result<bool> {
         self.is_installed()
     }
@@ -286,3 +322,23 @@ impl TrustStore for WindowsTrustStore {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cert_matches() {
+        let ours = b"0\x82\x01\x0aour certificate".to_vec();
+        let matcher = CertMatcher::new(&ours);
+
+        assert!(matcher.cert_matches(&ours));
+        // Same length, different content
+        let mut other = ours.clone();
+        other[5] ^= 0xff;
+        assert!(!matcher.cert_matches(&other));
+        // Different length
+        assert!(!matcher.cert_matches(&ours[..ours.len() - 1]));
+        assert!(!matcher.cert_matches(&[]));
+    }
+}
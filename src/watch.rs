@@ -0,0 +1,167 @@
+//! Regenerate certificates when a config file changes
+//!
+//! A long-running development environment can keep its hosts in a small
+//! TOML file and have certificates reissued whenever that file is edited:
+//!
+//! ```toml
+//! hosts = ["app.local", "api.local", "127.0.0.1"]
+//! ecdsa = true
+//! cert-file = "certs/dev.pem"
+//! key-file = "certs/dev-key.pem"
+//! validity-days = 30
+//! ```
+//!
+//! Only `hosts` is required. Relative file paths are resolved against the
+//! directory holding the config file.
+
+use crate::cert::{CertificateConfig, GenerationResult, generate_from_config};
+use crate::{Error, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// How long the file must stay quiet before regenerating, so the burst of
+/// events from a single save triggers one regeneration
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// On-disk form of a watched config file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct ConfigFile {
+    hosts: Vec<String>,
+    #[serde(default)]
+    ecdsa: bool,
+    #[serde(default)]
+    client: bool,
+    cert_file: Option<PathBuf>,
+    key_file: Option<PathBuf>,
+    validity_days: Option<u32>,
+}
+
+/// Read a certificate configuration from a TOML file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, is not valid TOML, has
+/// unknown keys, or describes an invalid configuration.
+pub fn load_config(path: &Path) -> Result<CertificateConfig> {
+    let invalid = |msg: String| Error::InvalidConfig(path.to_path_buf(), msg);
+    let content = std::fs::read_to_string(path)?;
+    let file: ConfigFile =
+        toml::from_str(&content).map_err(|e| invalid(e.message().to_string()))?;
+
+    let base = path.parent().unwrap_or(Path::new(""));
+    let mut builder = CertificateConfig::builder().hosts(file.hosts);
+    if file.ecdsa {
+        builder = builder.ecdsa();
+    }
+    if file.client {
+        builder = builder.client();
+    }
+    if let Some(cert_file) = file.cert_file {
+        builder = builder.cert_file(base.join(cert_file));
+    }
+    if let Some(key_file) = file.key_file {
+        builder = builder.key_file(base.join(key_file));
+    }
+    if let Some(days) = file.validity_days {
+        builder = builder.validity_days(days);
+    }
+    builder.build().map_err(|e| invalid(e.to_string()))
+}
+
+/// Generate a certificate from a config file, then regenerate it each time
+/// the file changes.
+///
+/// `on_regen` is called after the initial generation and after every
+/// regeneration. Changes are debounced, and a change that fails to load or
+/// generate is reported on stderr without stopping the watch, so a
+/// half-finished edit is harmless. Blocks until the config file is deleted.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be watched or the initial
+/// generation fails.
+pub fn watch_config(path: &Path, mut on_regen: impl FnMut(&GenerationResult)) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| Error::InvalidConfig(path.to_path_buf(), "not a file".to_string()))?
+        .to_os_string();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(std::io::Error::other)?;
+    // Watch the directory, since many editors save by replacing the file
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(std::io::Error::other)?;
+
+    on_regen(&generate_from_config(&load_config(path)?)?);
+
+    loop {
+        let event = match rx.recv() {
+            Ok(Ok(event)) => event,
+            Ok(Err(_)) => continue,
+            Err(_) => return Ok(()),
+        };
+        // Reading the file ourselves produces access events
+        let changed = !matches!(event.kind, EventKind::Access(_))
+            && event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == Some(file_name.as_os_str()));
+        if !changed {
+            continue;
+        }
+
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+        if !path.exists() {
+            return Ok(());
+        }
+
+        crate::verbose_print(&format!("{} changed, regenerating", path.display()));
+        match load_config(path).and_then(|config| generate_from_config(&config)) {
+            Ok(result) => on_regen(&result),
+            Err(e) => eprintln!("Warning: Not regenerating from {}: {}", path.display(), e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_config_resolves_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("fastcert.toml");
+        std::fs::write(
+            &path,
+            "hosts = [\"app.local\"]\necdsa = true\ncert-file = \"app.pem\"\n",
+        )
+        .unwrap();
+
+        let config = load_config(&path).unwrap();
+        assert_eq!(config.hosts, ["app.local"]);
+        assert!(config.use_ecdsa);
+        assert_eq!(config.cert_file, Some(temp_dir.path().join("app.pem")));
+        assert_eq!(config.key_file, None);
+    }
+
+    #[test]
+    fn test_load_config_rejects_unknown_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("fastcert.toml");
+        std::fs::write(&path, "hosts = [\"app.local\"]\nhost = \"typo\"\n").unwrap();
+
+        let err = load_config(&path).unwrap_err();
+        assert!(matches!(err, Error::InvalidConfig(..)));
+        assert!(err.to_string().contains("host"));
+    }
+}
@@ -0,0 +1,63 @@
+//! Audit log integration tests
+
+mod common;
+
+use common::get_test_lock;
+use std::env;
+use tempfile::TempDir;
+
+#[test]
+fn test_audit_log_records_issued_certificates() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+    let caroot = temp_dir.path().join("ca");
+
+    unsafe {
+        env::set_var("CAROOT", caroot.to_str().unwrap());
+        env::set_var("FASTCERT_AUDIT", "1");
+    }
+
+    let issue = |host: &str| {
+        let config = fastcert::cert::CertificateConfig::builder()
+            .host(host)
+            .cert_file(temp_dir.path().join(format!("{}.pem", host)))
+            .key_file(temp_dir.path().join(format!("{}-key.pem", host)))
+            .build()?;
+        fastcert::cert::generate_from_config(&config)
+    };
+    let first = issue("app.local");
+    let second = issue("api.local");
+    let entries = fastcert::audit::read_audit_log();
+
+    unsafe {
+        env::remove_var("FASTCERT_AUDIT");
+    }
+    // Without FASTCERT_AUDIT nothing more is recorded
+    let unaudited = issue("quiet.local");
+    let after = fastcert::audit::read_audit_log();
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+
+    let (first, second) = (first.unwrap(), second.unwrap());
+    let entries = entries.unwrap();
+    assert_eq!(entries.len(), 2);
+    for (entry, result) in entries.iter().zip([&first, &second]) {
+        assert_eq!(entry.serial, result.serial);
+        assert_eq!(entry.hosts, result.hosts);
+        assert_eq!(entry.not_after, result.not_after);
+        assert_eq!(entry.fingerprint, result.fingerprint);
+    }
+
+    let log = std::fs::read_to_string(caroot.join(fastcert::audit::AUDIT_FILE)).unwrap();
+    for line in log.lines() {
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        for key in ["timestamp", "serial", "hosts", "not_after", "fingerprint"] {
+            assert!(value.get(key).is_some(), "missing {} in {}", key, line);
+        }
+    }
+
+    unaudited.unwrap();
+    assert_eq!(after.unwrap().len(), 2);
+}
@@ -72,11 +72,20 @@ pub fn get_cert_text(cert_path: &std::path::Path) -> Result<String, String> {
     ])
 }
 
-/// Check if certificate contains a specific SAN
+/// Check if certificate contains a specific SAN (DNS name, IP, email or URI)
+///
+/// Uses fastcert's own parser, so it works without the openssl binary.
 #[allow(dead_code)]
 pub fn cert_contains_san(cert_path: &std::path::Path, san: &str) -> Result<bool, String> {
-    let text = get_cert_text(cert_path)?;
-    Ok(text.contains(san))
+    let expected = fastcert::cert::HostType::parse(san).map_err(|e| e.to_string())?;
+    Ok(cert_sans(cert_path)?.contains(&expected))
+}
+
+/// Get all SANs of a PEM certificate file
+#[allow(dead_code)]
+pub fn cert_sans(cert_path: &std::path::Path) -> Result<Vec<fastcert::cert::HostType>, String> {
+    let pem = std::fs::read(cert_path).map_err(|e| e.to_string())?;
+    fastcert::cert::certificate_sans(&pem).map_err(|e| e.to_string())
 }
 
 #[cfg(test)]
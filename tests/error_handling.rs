@@ -180,6 +180,72 @@ fn test_invalid_email_empty() {
     assert!(result.is_err(), "Should fail with empty email");
 }
 
+#[test]
+fn test_validators_return_specific_errors() {
+    use fastcert::Error;
+
+    assert!(matches!(
+        fastcert::cert::validate_email_address("nope"),
+        Err(Error::InvalidEmail(email)) if email == "nope"
+    ));
+    assert!(matches!(
+        fastcert::cert::validate_uri("not-a-uri"),
+        Err(Error::InvalidUri(uri, _)) if uri == "not-a-uri"
+    ));
+    assert!(matches!(
+        fastcert::cert::validate_wildcard_depth("*.*.example.com"),
+        Err(Error::InvalidWildcard(name, _)) if name == "*.*.example.com"
+    ));
+    assert!(matches!(
+        fastcert::cert::validate_wildcard_depth("example.*.com"),
+        Err(Error::InvalidWildcard(..))
+    ));
+}
+
+#[test]
+fn test_validate_hosts_reports_failing_host() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+    let hosts = vec![
+        "app.local".to_string(),
+        "127.0.0.1".to_string(),
+        "bad host!".to_string(),
+        "also bad!".to_string(),
+    ];
+
+    let parsed = fastcert::cert::validate_hosts(&hosts[..2]).unwrap();
+    assert_eq!(
+        parsed,
+        vec![
+            fastcert::cert::HostType::DnsName("app.local".to_string()),
+            fastcert::cert::HostType::IpAddress("127.0.0.1".parse().unwrap()),
+        ]
+    );
+
+    let err = fastcert::cert::validate_hosts(&hosts).unwrap_err();
+    assert!(
+        matches!(&err, fastcert::Error::InvalidHost { index: 2, host, .. } if host == "bad host!"),
+        "{:?}",
+        err
+    );
+    assert!(err.to_string().contains("#3 'bad host!'"));
+
+    // Generation validates before creating a CA
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+    let result =
+        fastcert::cert::generate_certificate(&hosts, None, None, None, false, false, false);
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+    assert!(matches!(
+        result,
+        Err(fastcert::Error::InvalidHost { index: 2, .. })
+    ));
+    assert!(!temp_dir.path().join("rootCA.pem").exists());
+}
+
 #[test]
 fn test_invalid_ip_address() {
     let _lock = get_test_lock();
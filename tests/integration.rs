@@ -2,7 +2,7 @@
 
 mod common;
 
-use common::get_test_lock;
+use common::{get_test_lock, run_openssl, verify_cert_with_ca};
 use std::env;
 use tempfile::TempDir;
 
@@ -321,3 +321,268 @@ fn test_certificate_uses_ecdsa_p256_with_flag() {
         env::remove_var("CAROOT");
     }
 }
+
+#[test]
+fn test_ecdsa_ca_signs_verifiable_leaf() {
+    let _lock = get_test_lock();
+
+    let temp_dir = TempDir::new().unwrap();
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+        env::set_var("FASTCERT_CA_KEY", "ecdsa-p384");
+    }
+
+    let ca = fastcert::CA::load_or_create().unwrap();
+    let ca_cert = temp_dir.path().join("rootCA.pem");
+    let ca_text =
+        run_openssl(&["x509", "-in", ca_cert.to_str().unwrap(), "-noout", "-text"]).unwrap();
+    assert!(ca_text.contains("secp384r1"), "CA should use ECDSA P-384");
+
+    // Both key types must chain to the non-RSA root
+    for (name, key_type) in [
+        ("rsa", fastcert::KeyType::RSA2048),
+        ("ecdsa", fastcert::KeyType::ECDSA),
+    ] {
+        let cert_file = temp_dir.path().join(format!("{}.pem", name));
+        ca.issue_certificate()
+            .unwrap()
+            .domains(vec!["ecdsa-ca.local".to_string()])
+            .key_type(key_type)
+            .cert_file(cert_file.to_str().unwrap())
+            .key_file(
+                temp_dir
+                    .path()
+                    .join(format!("{}-key.pem", name))
+                    .to_str()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        assert!(verify_cert_with_ca(&cert_file, &ca_cert));
+    }
+
+    unsafe {
+        env::remove_var("FASTCERT_CA_KEY");
+        env::remove_var("CAROOT");
+    }
+}
+
+#[test]
+fn test_rsa_pss_signed_leaf() {
+    use fastcert::cert::SignatureAlgorithm;
+
+    let _lock = get_test_lock();
+
+    let temp_dir = TempDir::new().unwrap();
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    let ca = fastcert::CA::load_or_create().unwrap();
+    let ca_cert = temp_dir.path().join("rootCA.pem");
+    let cert_file = temp_dir.path().join("pss.pem");
+    ca.issue_certificate()
+        .unwrap()
+        .domains(vec!["pss.local".to_string()])
+        .signature_algorithm(SignatureAlgorithm::RsaPss)
+        .cert_file(cert_file.to_str().unwrap())
+        .key_file(temp_dir.path().join("pss-key.pem").to_str().unwrap())
+        .build()
+        .unwrap();
+
+    let text = run_openssl(&[
+        "x509",
+        "-in",
+        cert_file.to_str().unwrap(),
+        "-noout",
+        "-text",
+    ])
+    .unwrap();
+    assert!(
+        text.contains("Signature Algorithm: rsassaPss"),
+        "Leaf should be signed with RSA-PSS:\n{}",
+        text
+    );
+    assert!(verify_cert_with_ca(&cert_file, &ca_cert));
+    fastcert::cert::verify_chain_pem(
+        &std::fs::read(&cert_file).unwrap(),
+        &std::fs::read(&ca_cert).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        "rsa-pss".parse::<SignatureAlgorithm>().unwrap(),
+        SignatureAlgorithm::RsaPss
+    );
+    assert!("sha1".parse::<SignatureAlgorithm>().is_err());
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+}
+
+#[test]
+fn test_rsa_pss_rejects_ecdsa_ca() {
+    let _lock = get_test_lock();
+
+    let temp_dir = TempDir::new().unwrap();
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+        env::set_var("FASTCERT_CA_KEY", "ecdsa-p256");
+    }
+
+    let ca = fastcert::CA::load_or_create().unwrap();
+    let cert_file = temp_dir.path().join("pss.pem");
+    let result = ca
+        .issue_certificate()
+        .unwrap()
+        .domains(vec!["pss.local".to_string()])
+        .signature_algorithm(fastcert::cert::SignatureAlgorithm::RsaPss)
+        .cert_file(cert_file.to_str().unwrap())
+        .key_file(temp_dir.path().join("pss-key.pem").to_str().unwrap())
+        .build();
+
+    unsafe {
+        env::remove_var("FASTCERT_CA_KEY");
+        env::remove_var("CAROOT");
+    }
+
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("RSA CA key"), "{}", err);
+    assert!(!cert_file.exists());
+}
+
+#[test]
+fn test_ed25519_ca_signs_verifiable_leaf() {
+    let _lock = get_test_lock();
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut ca = fastcert::CA::new(temp_dir.path().to_path_buf());
+    ca.init().unwrap();
+    let options = fastcert::ca::CaOptions {
+        key_algorithm: fastcert::KeyAlgorithm::Ed25519,
+        ..Default::default()
+    };
+    ca.create_ca_with(&options).unwrap();
+    ca.save().unwrap();
+
+    let cert_file = temp_dir.path().join("leaf.pem");
+    ca.issue_certificate()
+        .unwrap()
+        .domains(vec!["ed25519-ca.local".to_string()])
+        .cert_file(cert_file.to_str().unwrap())
+        .key_file(temp_dir.path().join("leaf-key.pem").to_str().unwrap())
+        .build()
+        .unwrap();
+
+    let ca_cert = temp_dir.path().join("rootCA.pem");
+    assert!(verify_cert_with_ca(&cert_file, &ca_cert));
+}
+
+#[test]
+fn test_sha384_signed_leaf() {
+    use fastcert::cert::HashAlgorithm;
+
+    let _lock = get_test_lock();
+
+    let temp_dir = TempDir::new().unwrap();
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    let ca = fastcert::CA::load_or_create().unwrap();
+    let ca_cert = temp_dir.path().join("rootCA.pem");
+    for (hash, expected) in [
+        (HashAlgorithm::Sha384, "sha384WithRSAEncryption"),
+        (HashAlgorithm::Sha512, "sha512WithRSAEncryption"),
+    ] {
+        let cert_file = temp_dir.path().join("hash.pem");
+        ca.issue_certificate()
+            .unwrap()
+            .domains(vec!["hash.local".to_string()])
+            .key_type(fastcert::KeyType::ECDSA)
+            .hash(hash)
+            .cert_file(cert_file.to_str().unwrap())
+            .key_file(temp_dir.path().join("hash-key.pem").to_str().unwrap())
+            .build()
+            .unwrap();
+
+        let text = run_openssl(&[
+            "x509",
+            "-in",
+            cert_file.to_str().unwrap(),
+            "-noout",
+            "-text",
+        ])
+        .unwrap();
+        assert!(
+            text.contains(&format!("Signature Algorithm: {}", expected)),
+            "Leaf should be signed with {}:\n{}",
+            expected,
+            text
+        );
+        assert!(verify_cert_with_ca(&cert_file, &ca_cert));
+    }
+
+    assert_eq!(
+        "SHA-384".parse::<HashAlgorithm>().unwrap(),
+        HashAlgorithm::Sha384
+    );
+    assert!("md5".parse::<HashAlgorithm>().is_err());
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+}
+
+#[test]
+fn test_hash_must_match_ecdsa_ca_curve() {
+    use fastcert::cert::HashAlgorithm;
+
+    let _lock = get_test_lock();
+
+    let temp_dir = TempDir::new().unwrap();
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+        env::set_var("FASTCERT_CA_KEY", "ecdsa-p384");
+    }
+
+    let ca = fastcert::CA::load_or_create().unwrap();
+    let ca_cert = temp_dir.path().join("rootCA.pem");
+    let cert_file = temp_dir.path().join("hash.pem");
+    let issue = |hash| {
+        ca.issue_certificate()
+            .unwrap()
+            .domains(vec!["hash.local".to_string()])
+            .hash(hash)
+            .cert_file(cert_file.to_str().unwrap())
+            .key_file(temp_dir.path().join("hash-key.pem").to_str().unwrap())
+            .build()
+    };
+    let mismatched = issue(HashAlgorithm::Sha256);
+    let matching = issue(HashAlgorithm::Sha384);
+
+    unsafe {
+        env::remove_var("FASTCERT_CA_KEY");
+        env::remove_var("CAROOT");
+    }
+
+    let err = mismatched.unwrap_err().to_string();
+    assert!(err.contains("ECDSA P-384"), "{}", err);
+    matching.unwrap();
+    let text = run_openssl(&[
+        "x509",
+        "-in",
+        cert_file.to_str().unwrap(),
+        "-noout",
+        "-text",
+    ])
+    .unwrap();
+    assert!(
+        text.contains("Signature Algorithm: ecdsa-with-SHA384"),
+        "{}",
+        text
+    );
+    assert!(verify_cert_with_ca(&cert_file, &ca_cert));
+}
@@ -327,3 +327,104 @@ fn test_issue_certificate_with_rsa() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_diagnose_reports_environment() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    let report = fastcert::diagnose();
+    assert!(!report.caroot.is_empty());
+    assert!(report.caroot_writable);
+    assert!(!report.ca_exists);
+    assert!(report.available_stores.iter().any(|s| s.contains("system")));
+
+    fastcert::CA::load_or_create().unwrap();
+    let report = fastcert::diagnose();
+    assert!(report.ca_exists);
+    assert!(report.ca_expires.is_some());
+
+    let json = report.to_json();
+    assert!(json.contains("\"caroot\""));
+    assert!(json.contains("\"available_stores\""));
+    assert!(json.contains("\"signature_provider\""));
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+}
+
+#[test]
+fn test_crypto_versions() {
+    let info = fastcert::crypto_versions();
+    assert!(!info.rcgen_version.is_empty());
+    assert!(!info.signature_provider.is_empty());
+    assert!(!info.ring_version.is_empty());
+    // Either resolved from a lock file (0.14.5) or the requirement (0.14)
+    assert!(info.rcgen_version.starts_with("0.14"), "{:?}", info);
+    assert!(info.ring_version.starts_with("0.17"), "{:?}", info);
+    assert!(
+        info.signature_provider.starts_with("aws-lc-rs"),
+        "{:?}",
+        info
+    );
+    assert_eq!(fastcert::diagnose().crypto, info);
+}
+
+#[test]
+fn test_renew_if_expiring() -> Result<()> {
+    use fastcert::RenewOutcome;
+    use fastcert::cert::{CertificateConfig, generate_from_config, renew_if_expiring};
+
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+    let cert_file = temp_dir.path().join("app.pem");
+    let key_file = temp_dir.path().join("app-key.pem");
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().join("ca"));
+    }
+
+    let issue = |days: u32| {
+        generate_from_config(
+            &CertificateConfig::builder()
+                .hosts(vec!["app.local".to_string(), "127.0.0.1".to_string()])
+                .ecdsa()
+                .cert_file(&cert_file)
+                .key_file(&key_file)
+                .validity_days(days)
+                .build()?,
+        )
+    };
+
+    let fresh = issue(365)?;
+    let skipped = renew_if_expiring(&cert_file, &key_file, 30);
+    let near_expiry = issue(5)?;
+    let renewed = renew_if_expiring(&cert_file, &key_file, 30);
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+
+    match skipped? {
+        RenewOutcome::Skipped { days_remaining } => assert!(days_remaining > 300),
+        other => panic!("expected Skipped, got {:?}", other),
+    }
+    assert_ne!(fresh.serial, near_expiry.serial);
+
+    let RenewOutcome::Renewed(result) = renewed? else {
+        panic!("a certificate expiring in 5 days should be renewed");
+    };
+    assert_eq!(result.cert_path, cert_file);
+    assert_eq!(result.hosts, ["app.local", "127.0.0.1"]);
+    assert_ne!(result.serial, near_expiry.serial);
+    assert!(result.not_after > near_expiry.not_after);
+
+    let info = fastcert::cert::inspect_certificate(&fs::read(&cert_file)?)?;
+    assert_eq!(info.serial, result.serial);
+    Ok(())
+}
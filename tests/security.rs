@@ -62,6 +62,50 @@ fn test_security_private_key_permissions() {
     }
 }
 
+#[test]
+#[cfg(unix)]
+fn test_security_custom_file_modes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let _lock = get_test_lock();
+
+    let temp_dir = TempDir::new().unwrap();
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    let cert_file = temp_dir.path().join("group.pem");
+    let key_file = temp_dir.path().join("group-key.pem");
+    let config = fastcert::cert::CertificateConfig::builder()
+        .host("group-read.local")
+        .cert_file(&cert_file)
+        .key_file(&key_file)
+        .key_mode(0o640)
+        .build()
+        .unwrap();
+    fastcert::cert::generate_from_config(&config).unwrap();
+
+    let key_mode = fs::metadata(&key_file).unwrap().permissions().mode() & 0o777;
+    assert_eq!(key_mode, 0o640, "Key should have 0640, got {:o}", key_mode);
+    let cert_mode = fs::metadata(&cert_file).unwrap().permissions().mode() & 0o777;
+    assert_eq!(
+        cert_mode, 0o644,
+        "Cert should keep 0644, got {:o}",
+        cert_mode
+    );
+
+    // A world-writable key is refused
+    let result = fastcert::cert::CertificateConfig::builder()
+        .host("group-read.local")
+        .key_mode(0o646)
+        .build();
+    assert!(result.is_err());
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+}
+
 #[test]
 fn test_security_certificate_not_self_signed() {
     let _lock = get_test_lock();
@@ -100,6 +144,14 @@ fn test_security_certificate_not_self_signed() {
     // Certificate should have different issuer and subject
     assert!(text.contains("issuer="), "Should have issuer field");
     assert!(text.contains("subject="), "Should have subject field");
+    let field = |name: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(str::trim)
+            .unwrap_or_default()
+            .to_string()
+    };
+    assert_ne!(field("issuer="), field("subject="));
 
     // Issuer should contain "fastcert" (CA name)
     assert!(text.contains("fastcert"), "Should be signed by fastcert CA");
@@ -327,6 +379,60 @@ fn test_certificate_expiration_date() {
     }
 }
 
+#[test]
+fn test_certificate_explicit_validity_window() {
+    use std::process::Command;
+    use time::{Duration, OffsetDateTime};
+
+    let _lock = get_test_lock();
+
+    let temp_dir = TempDir::new().unwrap();
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    // An already-expired certificate, for negative testing
+    let now = OffsetDateTime::now_utc();
+    let cert_file = temp_dir.path().join("expired.pem");
+    let config = fastcert::cert::CertificateConfig::builder()
+        .host("expired.local")
+        .not_before(now - Duration::days(10))
+        .not_after(now - Duration::days(1))
+        .cert_file(&cert_file)
+        .key_file(temp_dir.path().join("expired-key.pem"))
+        .build()
+        .unwrap();
+    let generated = fastcert::cert::generate_from_config(&config);
+
+    let reversed = fastcert::cert::CertificateConfig::builder()
+        .host("reversed.local")
+        .not_before(now)
+        .not_after(now - Duration::days(1))
+        .build();
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+
+    let result = generated.unwrap();
+    assert_eq!(
+        result.not_after.unix_timestamp(),
+        (now - Duration::days(1)).unix_timestamp()
+    );
+    let output = Command::new("openssl")
+        .args(["x509", "-noout", "-checkend", "0"])
+        .arg("-in")
+        .arg(&cert_file)
+        .output()
+        .unwrap();
+    assert!(
+        !output.status.success(),
+        "Certificate should already be expired"
+    );
+
+    assert!(reversed.is_err(), "notBefore must precede notAfter");
+}
+
 #[test]
 fn test_certificate_key_usage() {
     let _lock = get_test_lock();
@@ -0,0 +1,638 @@
+//! Trust store integration tests
+
+mod common;
+
+use common::get_test_lock;
+use fastcert::truststore::{self, StoreOutcome};
+use std::env;
+use tempfile::TempDir;
+
+#[test]
+fn test_uninstall_all_respects_trust_stores() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+        env::set_var("TRUST_STORES", "none");
+    }
+
+    let mut ca = fastcert::CA::new(temp_dir.path().to_path_buf());
+    ca.init_ca().unwrap();
+
+    let report = truststore::uninstall_all().unwrap();
+    assert_eq!(report.stores.len(), 3);
+    for store in ["system", "nss", "java"] {
+        assert_eq!(report.outcome(store), Some(&StoreOutcome::Skipped));
+    }
+    assert!(!report.has_failures());
+
+    unsafe {
+        env::remove_var("TRUST_STORES");
+        env::remove_var("CAROOT");
+    }
+}
+
+#[test]
+fn test_uninstall_all_unavailable_store_leaves_caroot_alone() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    // Java is enabled but not configured; "bogus" names no store at all
+    let original_java_home = env::var_os("JAVA_HOME");
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+        env::set_var("TRUST_STORES", "java,bogus");
+        env::remove_var("JAVA_HOME");
+    }
+
+    let mut ca = fastcert::CA::new(temp_dir.path().to_path_buf());
+    ca.init_ca().unwrap();
+    let snapshot = || {
+        let mut files: Vec<(std::path::PathBuf, Vec<u8>)> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let contents = std::fs::read(&path).unwrap();
+                (path, contents)
+            })
+            .collect();
+        files.sort();
+        files
+    };
+    let before = snapshot();
+
+    let report = truststore::uninstall_all();
+
+    unsafe {
+        env::remove_var("TRUST_STORES");
+        env::remove_var("CAROOT");
+        if let Some(java_home) = original_java_home {
+            env::set_var("JAVA_HOME", java_home);
+        }
+    }
+
+    let report = report.unwrap();
+    let stores: Vec<&str> = report.stores.iter().map(|r| r.store.as_str()).collect();
+    assert_eq!(stores, ["system", "nss", "java"]);
+    assert_eq!(report.outcome("system"), Some(&StoreOutcome::Skipped));
+    assert_eq!(report.outcome("nss"), Some(&StoreOutcome::Skipped));
+    assert_eq!(report.outcome("java"), Some(&StoreOutcome::NotAvailable));
+    assert!(!report.changed());
+    assert!(!report.has_failures());
+    assert_eq!(snapshot(), before, "CAROOT files were changed");
+}
+
+#[test]
+fn test_uninstall_all_without_ca() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    let result = truststore::uninstall_all();
+    assert!(result.is_err(), "Should fail when no CA exists");
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+}
+
+#[test]
+fn test_install_all_explicit_stores() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    // The explicit list must win over the environment. Java is left
+    // unconfigured so no real trust store is touched.
+    let original_java_home = env::var_os("JAVA_HOME");
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+        env::set_var("TRUST_STORES", "system,nss");
+        env::remove_var("JAVA_HOME");
+    }
+
+    let mut ca = fastcert::CA::new(temp_dir.path().to_path_buf());
+    ca.init_ca().unwrap();
+
+    let report = truststore::install_all(&ca.cert_path(), &["java"]);
+
+    unsafe {
+        env::remove_var("TRUST_STORES");
+        env::remove_var("CAROOT");
+        if let Some(java_home) = original_java_home {
+            env::set_var("JAVA_HOME", java_home);
+        }
+    }
+
+    let report = report.unwrap();
+    assert_eq!(report.stores.len(), 3);
+    assert_eq!(report.outcome("system"), Some(&StoreOutcome::Skipped));
+    assert_eq!(report.outcome("nss"), Some(&StoreOutcome::Skipped));
+    assert_eq!(report.outcome("java"), Some(&StoreOutcome::NotAvailable));
+    assert!(!report.changed());
+}
+
+#[test]
+fn test_install_all_missing_cert() {
+    let temp_dir = TempDir::new().unwrap();
+    let result = truststore::install_all(&temp_dir.path().join("rootCA.pem"), &["nss"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_trust_status_lists_stores() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    let mut ca = fastcert::CA::new(temp_dir.path().to_path_buf());
+    ca.init_ca().unwrap();
+
+    let statuses = truststore::trust_status().unwrap();
+    let stores: Vec<&str> = statuses.iter().map(|s| s.store.as_str()).collect();
+    assert_eq!(stores, ["system", "nss", "java"]);
+
+    #[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
+    assert!(statuses[0].available, "System store should be available");
+
+    unsafe {
+        env::set_var("FASTCERT_FORMAT", "json");
+    }
+    let json: serde_json::Value =
+        serde_json::from_str(&truststore::format_trust_status(&statuses)).unwrap();
+    assert_eq!(json[0]["store"], "system");
+
+    unsafe {
+        env::remove_var("FASTCERT_FORMAT");
+        env::remove_var("CAROOT");
+    }
+}
+
+#[test]
+fn test_install_all_dry_run() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    let mut ca = fastcert::CA::new(temp_dir.path().to_path_buf());
+    ca.init_ca().unwrap();
+    let status_before: Vec<bool> = truststore::trust_status()
+        .unwrap()
+        .iter()
+        .map(|s| s.installed)
+        .collect();
+
+    unsafe {
+        env::set_var("FASTCERT_DRY_RUN", "1");
+    }
+    let report = truststore::install_all(&ca.cert_path(), &["system", "nss", "java"]).unwrap();
+    unsafe {
+        env::remove_var("FASTCERT_DRY_RUN");
+    }
+
+    for store in ["system", "nss", "java"] {
+        assert_eq!(report.outcome(store), Some(&StoreOutcome::Skipped));
+    }
+    let status_after: Vec<bool> = truststore::trust_status()
+        .unwrap()
+        .iter()
+        .map(|s| s.installed)
+        .collect();
+    assert_eq!(status_before, status_after, "Dry run must not install");
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+}
+
+#[test]
+fn test_is_ca_installed_without_ca() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    assert!(!truststore::is_ca_installed().unwrap());
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+}
+
+#[test]
+#[cfg(target_os = "macos")]
+fn test_is_ca_installed_resolves_caroot() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    let mut ca = fastcert::CA::new(temp_dir.path().to_path_buf());
+    ca.init_ca().unwrap();
+
+    // A fresh CA has never been added to the keychain
+    let installed: bool = truststore::is_ca_installed().unwrap();
+    assert!(!installed);
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+}
+
+#[test]
+fn test_generation_with_skip_trust_leaves_stores_untouched() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+    let cert_file = temp_dir.path().join("app.pem");
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().join("ca").to_str().unwrap());
+    }
+
+    // generate_and_install writes to the current directory
+    let original_dir = env::current_dir().unwrap();
+    env::set_current_dir(temp_dir.path()).unwrap();
+    let generated = fastcert::generate_and_install(&["app.local".to_string()], true);
+    env::set_current_dir(original_dir).unwrap();
+    // Plain generation must not install either
+    let plain = fastcert::CA::load_or_create().and_then(|ca| {
+        ca.issue_certificate()?
+            .domains(vec!["app.local".to_string()])
+            .cert_file(cert_file.to_str().unwrap())
+            .key_file(temp_dir.path().join("app-key.pem").to_str().unwrap())
+            .build()
+    });
+    let installed = truststore::is_ca_installed();
+    let statuses = truststore::trust_status();
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+
+    assert!(temp_dir.path().join(generated.unwrap().cert_path).exists());
+    plain.unwrap();
+    assert!(!installed.unwrap());
+    for status in statuses.unwrap() {
+        assert!(!status.installed, "{} store was modified", status.store);
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_nss_install_without_profiles() {
+    use fastcert::truststore::nss::{NssOutcome, NssTrustStore};
+    use std::os::unix::fs::PermissionsExt;
+
+    // The system-wide database would count as a profile
+    if std::path::Path::new("/etc/pki/nssdb").exists() {
+        return;
+    }
+
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+    let bin_dir = temp_dir.path().join("bin");
+    let home_dir = temp_dir.path().join("home");
+    std::fs::create_dir_all(&bin_dir).unwrap();
+    std::fs::create_dir_all(&home_dir).unwrap();
+    let certutil = bin_dir.join("certutil");
+    std::fs::write(&certutil, "#!/bin/sh\nexit 0\n").unwrap();
+    std::fs::set_permissions(&certutil, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let original_path = env::var_os("PATH").unwrap_or_default();
+    let original_home = env::var_os("HOME");
+    let mut paths = vec![bin_dir.clone()];
+    paths.extend(env::split_paths(&original_path));
+    unsafe {
+        env::set_var("PATH", env::join_paths(paths).unwrap());
+        env::set_var("HOME", &home_dir);
+    }
+
+    let store = NssTrustStore::new(&temp_dir.path().join("rootCA.pem"), "test".to_string());
+    let with_certutil = store.install_nss();
+
+    // An empty Chromium database directory makes NSS count as present
+    std::fs::create_dir_all(home_dir.join(".pki/nssdb")).unwrap();
+    let mut ca = fastcert::CA::new(temp_dir.path().join("ca"));
+    ca.init_ca().unwrap();
+    let report = truststore::install_all(&ca.cert_path(), &["nss"]);
+
+    unsafe {
+        env::set_var("PATH", temp_dir.path().join("empty"));
+    }
+    let without_certutil = store.install_nss();
+    let report_without_certutil = truststore::install_all(&ca.cert_path(), &["nss"]);
+
+    unsafe {
+        env::set_var("PATH", original_path);
+        match original_home {
+            Some(home) => env::set_var("HOME", home),
+            None => env::remove_var("HOME"),
+        }
+    }
+
+    let outcome = with_certutil.unwrap();
+    assert_eq!(outcome, NssOutcome::NoProfiles);
+    assert!(outcome.to_string().contains("start Firefox once"));
+    assert_eq!(without_certutil.unwrap(), NssOutcome::CertutilMissing);
+
+    // install_all reports both as NotReady with the hint, not as failures
+    for (report, expected) in [
+        (report.unwrap(), NssOutcome::NoProfiles),
+        (
+            report_without_certutil.unwrap(),
+            NssOutcome::CertutilMissing,
+        ),
+    ] {
+        assert_eq!(
+            report.outcome("nss"),
+            Some(&StoreOutcome::NotReady(expected.to_string()))
+        );
+        assert!(!report.has_failures());
+        assert!(!report.changed());
+    }
+}
+
+#[test]
+fn test_extra_anchors_install_and_uninstall() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+    let anchors = temp_dir.path().join("anchors");
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().join("ca").to_str().unwrap());
+        env::set_var("FASTCERT_EXTRA_ANCHORS", &anchors);
+        env::set_var("TRUST_STORES", "none");
+    }
+
+    let ca = fastcert::CA::load_or_create().unwrap();
+    let installed = truststore::install_all(&ca.cert_path(), &["nss"]);
+    let copies: Vec<_> = std::fs::read_dir(&anchors)
+        .map(|entries| entries.map(|e| e.unwrap().path()).collect())
+        .unwrap_or_default();
+    let copied = copies.first().map(|copy| std::fs::read(copy).unwrap());
+    let reinstalled = truststore::install_all(&ca.cert_path(), &["nss"]);
+    let uninstalled = truststore::uninstall_all();
+
+    unsafe {
+        env::remove_var("TRUST_STORES");
+        env::remove_var("FASTCERT_EXTRA_ANCHORS");
+        env::remove_var("CAROOT");
+    }
+
+    let name = format!("anchor:{}", anchors.display());
+    assert_eq!(
+        installed.unwrap().outcome(&name),
+        Some(&StoreOutcome::Installed)
+    );
+    assert_eq!(copies.len(), 1);
+    assert_eq!(copied, Some(std::fs::read(ca.cert_path()).unwrap()));
+    assert_eq!(
+        reinstalled.unwrap().outcome(&name),
+        Some(&StoreOutcome::AlreadyInstalled)
+    );
+    assert_eq!(
+        uninstalled.unwrap().outcome(&name),
+        Some(&StoreOutcome::Uninstalled)
+    );
+    assert!(!copies[0].exists());
+}
+
+#[test]
+fn test_install_report_includes_ca_fingerprint() {
+    use sha2::{Digest, Sha256};
+
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+    }
+
+    let ca = fastcert::CA::load_or_create().unwrap();
+    let report = truststore::install_all(&ca.cert_path(), &["none"]);
+    let fingerprint = fastcert::ca::fingerprint_sha256();
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+
+    let pem = pem::parse(std::fs::read(ca.cert_path()).unwrap()).unwrap();
+    let expected = Sha256::digest(pem.contents())
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":");
+    assert_eq!(report.unwrap().fingerprint, Some(expected.clone()));
+    assert_eq!(fingerprint.unwrap(), expected);
+}
+
+#[test]
+fn test_install_script_lists_system_commands() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().to_str().unwrap());
+        env::set_var("TRUST_STORES", "system");
+    }
+
+    let missing = truststore::install_script();
+    let ca = fastcert::CA::load_or_create().unwrap();
+    let script = truststore::install_script();
+
+    unsafe {
+        env::remove_var("TRUST_STORES");
+        env::remove_var("CAROOT");
+    }
+
+    assert!(missing.is_err(), "Should fail when no CA exists");
+    let script = script.unwrap();
+    assert!(script.contains("# system"), "{}", script);
+    assert!(!script.contains("# nss"), "{}", script);
+    assert!(
+        script.contains(&ca.cert_path().display().to_string()),
+        "{}",
+        script
+    );
+
+    #[cfg(target_os = "macos")]
+    assert!(script.contains("security add-trusted-cert"), "{}", script);
+    #[cfg(target_os = "linux")]
+    assert!(
+        [
+            "update-ca-trust",
+            "update-ca-certificates",
+            "trust extract-compat"
+        ]
+        .iter()
+        .any(|cmd| script.contains(cmd))
+            || script.contains("unsupported distribution"),
+        "{}",
+        script
+    );
+    #[cfg(target_os = "windows")]
+    assert!(script.contains("Import-Certificate"), "{}", script);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_nss_install_creates_chromium_db() {
+    use fastcert::truststore::nss::NssTrustStore;
+    use std::os::unix::fs::PermissionsExt;
+
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+    let bin_dir = temp_dir.path().join("bin");
+    let home_dir = temp_dir.path().join("home");
+    std::fs::create_dir_all(&bin_dir).unwrap();
+    std::fs::create_dir_all(&home_dir).unwrap();
+    // certutil -N --empty-password -d sql:<dir> creates the database
+    let certutil = bin_dir.join("certutil");
+    std::fs::write(
+        &certutil,
+        "#!/bin/sh\nif [ \"$1\" = \"-N\" ]; then touch \"${4#sql:}/cert9.db\"; fi\nexit 0\n",
+    )
+    .unwrap();
+    let chromium = bin_dir.join("chromium");
+    std::fs::write(&chromium, "#!/bin/sh\n").unwrap();
+    for file in [&certutil, &chromium] {
+        std::fs::set_permissions(file, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let mut ca = fastcert::CA::new(temp_dir.path().join("ca"));
+    ca.init_ca().unwrap();
+
+    let original_path = env::var_os("PATH").unwrap_or_default();
+    let original_home = env::var_os("HOME");
+    let mut paths = vec![bin_dir.clone()];
+    paths.extend(env::split_paths(&original_path));
+    unsafe {
+        env::set_var("PATH", env::join_paths(paths).unwrap());
+        env::set_var("HOME", &home_dir);
+    }
+
+    let profiles_before = NssTrustStore::profiles();
+    let report = truststore::install_all(&ca.cert_path(), &["nss"]);
+    let profiles_after = NssTrustStore::profiles();
+
+    unsafe {
+        env::set_var("PATH", original_path);
+        match original_home {
+            Some(home) => env::set_var("HOME", home),
+            None => env::remove_var("HOME"),
+        }
+    }
+
+    let chromium_db = home_dir.join(".pki/nssdb");
+    assert!(!profiles_before.contains(&chromium_db));
+    assert!(chromium_db.join("cert9.db").exists());
+    assert!(
+        profiles_after.contains(&chromium_db),
+        "{:?}",
+        profiles_after
+    );
+
+    let report = report.unwrap();
+    assert_eq!(report.outcome("nss"), Some(&StoreOutcome::Installed));
+    assert_eq!(
+        report.outcome(truststore::CHROMIUM_NSS_STORE),
+        Some(&StoreOutcome::Installed)
+    );
+}
+
+#[test]
+fn test_offline_mode_lists_only_system_store() {
+    let _lock = get_test_lock();
+    let java_home = TempDir::new().unwrap();
+    std::fs::create_dir_all(java_home.path().join("bin")).unwrap();
+    std::fs::create_dir_all(java_home.path().join("lib/security")).unwrap();
+    std::fs::write(java_home.path().join("bin/keytool"), "").unwrap();
+    std::fs::write(java_home.path().join("lib/security/cacerts"), "").unwrap();
+
+    let original_java_home = env::var("JAVA_HOME").ok();
+    unsafe {
+        env::set_var("JAVA_HOME", java_home.path());
+    }
+    assert!(truststore::java::JavaTrustStore::is_available());
+
+    unsafe {
+        env::set_var("FASTCERT_OFFLINE", "1");
+    }
+    let stores = truststore::enumerate_available_stores();
+    let java_detected = truststore::java::JavaTrustStore::is_available()
+        || truststore::java::JavaTrustStore::has_keytool();
+    let nss_detected = truststore::nss::NssTrustStore::is_available()
+        || truststore::nss::NssTrustStore::has_certutil();
+
+    unsafe {
+        env::remove_var("FASTCERT_OFFLINE");
+        match original_java_home {
+            Some(value) => env::set_var("JAVA_HOME", value),
+            None => env::remove_var("JAVA_HOME"),
+        }
+    }
+
+    assert_eq!(stores.len(), 1, "{:?}", stores);
+    assert!(stores[0].starts_with("system"));
+    assert!(!java_detected);
+    assert!(!nss_detected);
+}
+
+#[test]
+fn test_self_test_tls_with_trusted_ca() {
+    let _lock = get_test_lock();
+    let trusted = TempDir::new().unwrap();
+    let untrusted = TempDir::new().unwrap();
+
+    // SSL_CERT_FILE stands in for the platform store, so the test neither
+    // needs nor changes the real one
+    let original_cert_file = env::var("SSL_CERT_FILE").ok();
+    let original_cert_dir = env::var("SSL_CERT_DIR").ok();
+    unsafe {
+        env::remove_var("SSL_CERT_DIR");
+        env::set_var("CAROOT", untrusted.path());
+    }
+    let other_ca = fastcert::CA::load_or_create().unwrap();
+    unsafe {
+        env::set_var("CAROOT", trusted.path());
+    }
+    let ca = fastcert::CA::load_or_create().unwrap();
+
+    unsafe {
+        env::set_var("SSL_CERT_FILE", ca.cert_path());
+    }
+    let with_ca = fastcert::self_test_tls(&[]);
+    let with_name = fastcert::self_test_tls(&["localhost".to_string()]);
+    unsafe {
+        env::set_var("SSL_CERT_FILE", other_ca.cert_path());
+    }
+    let without_ca = fastcert::self_test_tls(&[]);
+
+    unsafe {
+        env::remove_var("CAROOT");
+        match original_cert_file {
+            Some(value) => env::set_var("SSL_CERT_FILE", value),
+            None => env::remove_var("SSL_CERT_FILE"),
+        }
+        if let Some(value) = original_cert_dir {
+            env::set_var("SSL_CERT_DIR", value);
+        }
+    }
+
+    assert!(with_ca.unwrap());
+    assert!(with_name.unwrap());
+    assert!(!without_ca.unwrap());
+}
@@ -292,15 +292,7 @@ fn test_generate_file_names_single() {
 
     use fastcert::cert::CertificateConfig;
 
-    let config = CertificateConfig {
-        hosts: vec!["example.com".to_string()],
-        cert_file: None,
-        key_file: None,
-        p12_file: None,
-        client_cert: false,
-        use_ecdsa: false,
-        pkcs12: false,
-    };
+    let config = CertificateConfig::new(vec!["example.com".to_string()]);
 
     let (cert, _key, _) = fastcert::cert::generate_file_names(&config);
     assert!(
@@ -315,19 +307,11 @@ fn test_generate_file_names_multiple() {
 
     use fastcert::cert::CertificateConfig;
 
-    let config = CertificateConfig {
-        hosts: vec![
-            "example.com".to_string(),
-            "localhost".to_string(),
-            "127.0.0.1".to_string(),
-        ],
-        cert_file: None,
-        key_file: None,
-        p12_file: None,
-        client_cert: false,
-        use_ecdsa: false,
-        pkcs12: false,
-    };
+    let config = CertificateConfig::new(vec![
+        "example.com".to_string(),
+        "localhost".to_string(),
+        "127.0.0.1".to_string(),
+    ]);
 
     let (cert, _key, _) = fastcert::cert::generate_file_names(&config);
     assert!(
@@ -342,15 +326,7 @@ fn test_generate_file_names_wildcard() {
 
     use fastcert::cert::CertificateConfig;
 
-    let config = CertificateConfig {
-        hosts: vec!["*.example.com".to_string()],
-        cert_file: None,
-        key_file: None,
-        p12_file: None,
-        client_cert: false,
-        use_ecdsa: false,
-        pkcs12: false,
-    };
+    let config = CertificateConfig::new(vec!["*.example.com".to_string()]);
 
     let (cert, _, _) = fastcert::cert::generate_file_names(&config);
     assert!(
@@ -359,6 +335,42 @@ fn test_generate_file_names_wildcard() {
     );
 }
 
+#[test]
+fn test_default_filenames() {
+    use fastcert::cert::default_filenames;
+    use std::path::PathBuf;
+
+    let names = |hosts: &[&str]| {
+        default_filenames(&hosts.iter().map(|h| h.to_string()).collect::<Vec<_>>()).unwrap()
+    };
+
+    assert_eq!(
+        names(&["example.com"]),
+        (
+            PathBuf::from("./example.com.pem"),
+            PathBuf::from("./example.com-key.pem")
+        )
+    );
+    assert_eq!(
+        names(&["example.com", "localhost", "127.0.0.1"]),
+        (
+            PathBuf::from("./example.com+2.pem"),
+            PathBuf::from("./example.com+2-key.pem")
+        )
+    );
+    assert_eq!(
+        names(&["*.example.com"]),
+        (
+            PathBuf::from("./_wildcard.example.com.pem"),
+            PathBuf::from("./_wildcard.example.com-key.pem")
+        )
+    );
+    assert_eq!(names(&["::1"]).0, PathBuf::from("./__1.pem"));
+
+    assert!(default_filenames(&[]).is_err());
+    assert!(default_filenames(&["0.0.0.0".to_string()]).is_err());
+}
+
 #[test]
 fn test_certificate_with_client_auth() {
     let _lock = get_test_lock();
@@ -416,3 +428,62 @@ fn test_certificate_with_ecdsa() {
         env::remove_var("CAROOT");
     }
 }
+
+#[test]
+fn test_strict_mode_wildcard_without_apex() {
+    let _lock = get_test_lock();
+
+    let wildcard_only = vec!["*.example.com".to_string()];
+    let with_apex = vec!["*.example.com".to_string(), "Example.com".to_string()];
+
+    unsafe {
+        env::remove_var("FASTCERT_STRICT");
+    }
+    assert!(fastcert::cert::lint_wildcard_apex(&wildcard_only).is_empty());
+
+    unsafe {
+        env::set_var("FASTCERT_STRICT", "1");
+    }
+    let warnings = fastcert::cert::lint_wildcard_apex(&wildcard_only);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("\"example.com\""));
+    assert!(fastcert::cert::lint_wildcard_apex(&with_apex).is_empty());
+    // The lint only warns; building the SAN list still succeeds
+    assert!(fastcert::cert::build_san_list(&wildcard_only).is_ok());
+
+    unsafe {
+        env::remove_var("FASTCERT_STRICT");
+    }
+}
+
+#[test]
+fn test_strict_mode_public_tld_advisory() {
+    let _lock = get_test_lock();
+
+    let public = vec!["example.com".to_string()];
+    let reserved = vec![
+        "example.local".to_string(),
+        "app.test".to_string(),
+        "localhost".to_string(),
+        "127.0.0.1".to_string(),
+    ];
+
+    unsafe {
+        env::remove_var("FASTCERT_STRICT");
+    }
+    assert!(fastcert::cert::lint_public_tlds(&public).is_empty());
+
+    unsafe {
+        env::set_var("FASTCERT_STRICT", "1");
+    }
+    let advisories = fastcert::cert::lint_public_tlds(&public);
+    assert_eq!(advisories.len(), 1);
+    assert!(advisories[0].contains("\"example.com\""));
+    assert!(fastcert::cert::lint_public_tlds(&reserved).is_empty());
+    // Advisory only; the SAN list is still built
+    assert!(fastcert::cert::build_san_list(&public).is_ok());
+
+    unsafe {
+        env::remove_var("FASTCERT_STRICT");
+    }
+}
@@ -0,0 +1,60 @@
+//! Config watch integration tests
+
+mod common;
+
+use common::get_test_lock;
+use std::env;
+use std::sync::mpsc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+#[test]
+fn test_watch_config_regenerates_on_change() {
+    let _lock = get_test_lock();
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("fastcert.toml");
+    let write_hosts = |hosts: &str| {
+        std::fs::write(
+            &config_path,
+            format!(
+                "hosts = [{}]\ncert-file = \"dev.pem\"\nkey-file = \"dev-key.pem\"\n",
+                hosts
+            ),
+        )
+        .unwrap();
+    };
+    write_hosts("\"app.local\"");
+
+    unsafe {
+        env::set_var("CAROOT", temp_dir.path().join("ca").to_str().unwrap());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let watched = config_path.clone();
+    let watcher = std::thread::spawn(move || {
+        fastcert::watch::watch_config(&watched, |result| {
+            tx.send((result.hosts.clone(), result.serial.clone()))
+                .unwrap();
+        })
+    });
+
+    let first = rx.recv_timeout(Duration::from_secs(10));
+    write_hosts("\"app.local\", \"api.local\"");
+    let second = rx.recv_timeout(Duration::from_secs(10));
+
+    // Deleting the config ends the watch
+    std::fs::remove_file(&config_path).unwrap();
+    let finished = watcher.join().unwrap();
+
+    unsafe {
+        env::remove_var("CAROOT");
+    }
+
+    let (first_hosts, first_serial) = first.expect("initial generation");
+    let (second_hosts, second_serial) = second.expect("regeneration after edit");
+    assert_eq!(first_hosts, ["app.local"]);
+    assert_eq!(second_hosts, ["app.local", "api.local"]);
+    assert_ne!(first_serial, second_serial);
+    assert!(temp_dir.path().join("dev.pem").exists());
+    finished.unwrap();
+}